@@ -2,12 +2,12 @@
 #[macro_use]
 extern crate criterion;
 
+use accumulator::bigint::Integer;
 use accumulator::group::{ClassGroup, Rsa2048, UnknownOrderGroup};
 use accumulator::hash::hash_to_prime;
 use accumulator::{Accumulator, MembershipProof};
 use criterion::Criterion;
 use rand::Rng;
-use rug::Integer;
 
 fn bench_add<G: UnknownOrderGroup>(elems: &[Integer]) {
   let acc = Accumulator::<G, Integer>::empty();
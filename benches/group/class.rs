@@ -2,9 +2,9 @@
 #[macro_use]
 extern crate criterion;
 
+use accumulator::bigint::Integer;
 use accumulator::group::{ClassGroup, ElemFrom, Group, UnknownOrderGroup};
 use criterion::Criterion;
-use rug::Integer;
 use std::str::FromStr;
 
 fn criterion_benchmark(c: &mut Criterion) {
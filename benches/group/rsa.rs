@@ -2,9 +2,9 @@
 #[macro_use]
 extern crate criterion;
 
+use accumulator::bigint::Integer;
+use accumulator::group::{ElemFrom, Group, Rsa2048, UnknownOrderGroup};
 use criterion::Criterion;
-use accumulator::group::{ElemFrom, Group, Rsa2048};
-use rug::Integer;
 use std::str::FromStr;
 
 fn bench_op<G: Group + ElemFrom<Integer>>() {
@@ -62,6 +62,31 @@ fn bench_exp<G: Group + ElemFrom<u8>>() {
   );
 }
 
+fn bench_exp_sec<G: Group + ElemFrom<u8>>() {
+  G::exp_sec(
+    &G::elem(2),
+    &Integer::from_str(
+      "65315136833896061809557254466951240071191890612435768575001173256020447546800029221544380288\
+      474666886816442984548106882909827295319824031764930714696522619672276938781971873901815262421\
+      654562691730669161126673833543570922556193096897121287444423696122691826661878849856991509472\
+      508677693535083051665283493383",
+    )
+    .unwrap(),
+  );
+}
+
+fn bench_exp_unknown_order_elem<G: UnknownOrderGroup>() {
+  G::exp_unknown_order_elem(
+    &Integer::from_str(
+      "65315136833896061809557254466951240071191890612435768575001173256020447546800029221544380288\
+      474666886816442984548106882909827295319824031764930714696522619672276938781971873901815262421\
+      654562691730669161126673833543570922556193096897121287444423696122691826661878849856991509472\
+      508677693535083051665283493383",
+    )
+    .unwrap(),
+  );
+}
+
 fn bench_inv<G: Group + ElemFrom<u8>>() {
   G::inv(&G::elem(2));
 }
@@ -70,6 +95,10 @@ fn criterion_benchmark(c: &mut Criterion) {
   c.bench_function("group_rsa_op", |b| b.iter(bench_op::<Rsa2048>));
   c.bench_function("group_rsa_op_large", |b| b.iter(bench_op_large::<Rsa2048>));
   c.bench_function("group_rsa_exp", |b| b.iter(bench_exp::<Rsa2048>));
+  c.bench_function("group_rsa_exp_sec", |b| b.iter(bench_exp_sec::<Rsa2048>));
+  c.bench_function("group_rsa_exp_fixed_base", |b| {
+    b.iter(bench_exp_unknown_order_elem::<Rsa2048>)
+  });
   c.bench_function("group_rsa_inv", |b| b.iter(bench_inv::<Rsa2048>));
 }
 
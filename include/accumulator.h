@@ -0,0 +1,239 @@
+/*
+ * C bindings for accumulators over RSA-2048 and class groups, whose elements are byte strings.
+ *
+ * Every pointer argument must be null or valid for the access its type implies, and handles must
+ * not be used after they are freed. Element lists are passed as an array of `n` `AccSlice`s, which
+ * may be null if `n` is zero; the bytes of each slice may likewise be null if its length is zero.
+ * Null handles and out-parameters fail with `ACC_STATUS_NULL_POINTER`. Out-parameters are left
+ * untouched on failure. A panic inside a call fails it with `ACC_STATUS_FAILED` (or, for `acc_new`,
+ * returns null) instead of unwinding into C.
+ */
+
+#ifndef ACCUMULATOR_H
+#define ACCUMULATOR_H
+
+/* Generated by cbindgen from src/ffi.rs. Do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// The groups an accumulator can be created over.
+typedef enum AccGroup {
+  // The RSA-2048 group.
+  ACC_GROUP_RSA2048,
+  // The class group.
+  ACC_GROUP_CLASS,
+} AccGroup;
+
+// The result of a call.
+typedef enum AccStatus {
+  // The call succeeded.
+  ACC_STATUS_OK,
+  // A required pointer was null.
+  ACC_STATUS_NULL_POINTER,
+  // Handles of different groups were mixed.
+  ACC_STATUS_GROUP_MISMATCH,
+  // Serialized data was malformed.
+  ACC_STATUS_MALFORMED,
+  // A witness or proof did not verify against the accumulator.
+  ACC_STATUS_BAD_WITNESS,
+  // An element appeared twice, or was already accumulated.
+  ACC_STATUS_DUPLICATE_ELEM,
+  // An element was not accumulated.
+  ACC_STATUS_UNKNOWN_ELEM,
+  // Some other accumulator operation failed.
+  ACC_STATUS_FAILED,
+} AccStatus;
+
+// An accumulator of byte strings.
+typedef struct AccAccumulator AccAccumulator;
+
+// A batch membership proof.
+typedef struct AccMembershipProof AccMembershipProof;
+
+// A batch nonmembership proof.
+typedef struct AccNonmembershipProof AccNonmembershipProof;
+
+// A borrowed byte string.
+typedef struct AccSlice {
+  // The bytes, which may be null if `len` is zero.
+  const uint8_t *data;
+  // The number of bytes.
+  size_t len;
+} AccSlice;
+
+// A byte string owned by this library, released with `acc_buffer_free`.
+typedef struct AccBuffer {
+  // The bytes.
+  uint8_t *data;
+  // The number of bytes.
+  size_t len;
+} AccBuffer;
+
+// Returns a new, empty accumulator over `group`, or null if creating it panicked.
+struct AccAccumulator *acc_new(enum AccGroup group);
+
+// Writes the group of `acc` to `group`.
+//
+// # Safety
+//
+// See the module docs (repeated at the top of `accumulator.h`).
+enum AccStatus acc_group(const struct AccAccumulator *acc, enum AccGroup *group);
+
+// Releases an accumulator. Does nothing if `acc` is null.
+//
+// # Safety
+//
+// See the module docs (repeated at the top of `accumulator.h`).
+void acc_free(struct AccAccumulator *acc);
+
+// Adds `elems` to `acc`. If `proof` is not null, a membership proof for `elems` in the new
+// accumulator is written to it.
+//
+// # Safety
+//
+// See the module docs (repeated at the top of `accumulator.h`).
+enum AccStatus acc_add(struct AccAccumulator *acc,
+                       const struct AccSlice *elems,
+                       size_t n,
+                       struct AccMembershipProof **proof);
+
+// Deletes `elems` from `acc`, given a membership proof for them (such as one from `acc_add` or
+// `acc_prove_membership`). Fails with `BadWitness` if the proof's witness does not match.
+//
+// # Safety
+//
+// See the module docs (repeated at the top of `accumulator.h`).
+enum AccStatus acc_delete(struct AccAccumulator *acc,
+                          const struct AccSlice *elems,
+                          size_t n,
+                          const struct AccMembershipProof *proof);
+
+// Proves that `elems` are in `acc`, whose accumulated elements are `set`. Fails with
+// `UnknownElem` if some element is not in `set`.
+//
+// # Safety
+//
+// See the module docs (repeated at the top of `accumulator.h`).
+enum AccStatus acc_prove_membership(const struct AccAccumulator *acc,
+                                    const struct AccSlice *set,
+                                    size_t set_len,
+                                    const struct AccSlice *elems,
+                                    size_t n,
+                                    struct AccMembershipProof **proof);
+
+// Proves that `elems` are not in `acc`, whose accumulated elements are `set`. Fails with
+// `DuplicateElem` if some element is in `set`.
+//
+// # Safety
+//
+// See the module docs (repeated at the top of `accumulator.h`).
+enum AccStatus acc_prove_nonmembership(const struct AccAccumulator *acc,
+                                       const struct AccSlice *set,
+                                       size_t set_len,
+                                       const struct AccSlice *elems,
+                                       size_t n,
+                                       struct AccNonmembershipProof **proof);
+
+// Verifies a membership proof for `elems` in `acc`, writing the result to `valid`.
+//
+// # Safety
+//
+// See the module docs (repeated at the top of `accumulator.h`).
+enum AccStatus acc_verify_membership(const struct AccAccumulator *acc,
+                                     const struct AccSlice *elems,
+                                     size_t n,
+                                     const struct AccMembershipProof *proof,
+                                     bool *valid);
+
+// Verifies a nonmembership proof for `elems` in `acc`, writing the result to `valid`.
+//
+// # Safety
+//
+// See the module docs (repeated at the top of `accumulator.h`).
+enum AccStatus acc_verify_nonmembership(const struct AccAccumulator *acc,
+                                        const struct AccSlice *elems,
+                                        size_t n,
+                                        const struct AccNonmembershipProof *proof,
+                                        bool *valid);
+
+// Releases a membership proof. Does nothing if `proof` is null.
+//
+// # Safety
+//
+// See the module docs (repeated at the top of `accumulator.h`).
+void acc_membership_proof_free(struct AccMembershipProof *proof);
+
+// Releases a nonmembership proof. Does nothing if `proof` is null.
+//
+// # Safety
+//
+// See the module docs (repeated at the top of `accumulator.h`).
+void acc_nonmembership_proof_free(struct AccNonmembershipProof *proof);
+
+// Serializes `acc` into a new buffer at `out`.
+//
+// # Safety
+//
+// See the module docs (repeated at the top of `accumulator.h`).
+enum AccStatus acc_serialize(const struct AccAccumulator *acc, struct AccBuffer *out);
+
+// Deserializes an accumulator over `group` from `len` bytes at `data` into a new handle at `out`.
+//
+// # Safety
+//
+// See the module docs (repeated at the top of `accumulator.h`).
+enum AccStatus acc_deserialize(enum AccGroup group,
+                               const uint8_t *data,
+                               size_t len,
+                               struct AccAccumulator **out);
+
+// Serializes a membership proof into a new buffer at `out`.
+//
+// # Safety
+//
+// See the module docs (repeated at the top of `accumulator.h`).
+enum AccStatus acc_membership_proof_serialize(const struct AccMembershipProof *proof,
+                                              struct AccBuffer *out);
+
+// Deserializes a membership proof over `group` from `len` bytes at `data` into a new handle at
+// `out`.
+//
+// # Safety
+//
+// See the module docs (repeated at the top of `accumulator.h`).
+enum AccStatus acc_membership_proof_deserialize(enum AccGroup group,
+                                                const uint8_t *data,
+                                                size_t len,
+                                                struct AccMembershipProof **out);
+
+// Serializes a nonmembership proof into a new buffer at `out`.
+//
+// # Safety
+//
+// See the module docs (repeated at the top of `accumulator.h`).
+enum AccStatus acc_nonmembership_proof_serialize(const struct AccNonmembershipProof *proof,
+                                                 struct AccBuffer *out);
+
+// Deserializes a nonmembership proof over `group` from `len` bytes at `data` into a new handle at
+// `out`.
+//
+// # Safety
+//
+// See the module docs (repeated at the top of `accumulator.h`).
+enum AccStatus acc_nonmembership_proof_deserialize(enum AccGroup group,
+                                                   const uint8_t *data,
+                                                   size_t len,
+                                                   struct AccNonmembershipProof **out);
+
+// Releases a buffer returned by this library. Does nothing if its data is null.
+//
+// # Safety
+//
+// See the module docs (repeated at the top of `accumulator.h`).
+void acc_buffer_free(struct AccBuffer buffer);
+
+#endif /* ACCUMULATOR_H */
@@ -0,0 +1,104 @@
+import pytest
+
+import accumulator
+from accumulator import (
+    Accumulator,
+    MembershipProof,
+    NonmembershipProof,
+    VectorCommitment,
+    VectorProof,
+)
+
+GROUPS = ["rsa2048", "class"]
+
+
+@pytest.mark.parametrize("group", GROUPS)
+def test_add_and_verify(group):
+    acc = Accumulator(group)
+    assert acc.group == group
+    proof = acc.add([b"dog", b"cat"])
+    assert acc.verify_membership([b"dog", b"cat"], proof)
+    assert not acc.verify_membership([b"dog", b"cow"], proof)
+    assert acc != Accumulator(group)
+
+
+@pytest.mark.parametrize("group", GROUPS)
+def test_prove_and_delete(group):
+    acc = Accumulator(group)
+    acc_set = [b"dog", b"cat", b"bird"]
+    acc.add(acc_set)
+    old = Accumulator.from_bytes(acc.to_bytes(), group)
+
+    proof = acc.prove_membership(acc_set, [b"cat"])
+    assert acc.verify_membership([b"cat"], proof)
+    with pytest.raises(ValueError):
+        acc.prove_membership(acc_set, [b"cow"])
+
+    acc.delete([b"cat"], proof)
+    assert acc != old
+    assert old.verify_membership([b"cat"], proof)
+    # The witness no longer matches.
+    with pytest.raises(ValueError):
+        acc.delete([b"cat"], proof)
+
+    proof = acc.prove_nonmembership([b"dog", b"bird"], [b"cat", b"cow"])
+    assert acc.verify_nonmembership([b"cat", b"cow"], proof)
+    assert not old.verify_nonmembership([b"cat", b"cow"], proof)
+    with pytest.raises(ValueError):
+        acc.prove_nonmembership([b"dog", b"bird"], [b"dog"])
+
+
+@pytest.mark.parametrize("group", GROUPS)
+def test_bytes_round_trip(group):
+    acc = Accumulator(group)
+    membership_proof = acc.add([b"dog"])
+    nonmembership_proof = acc.prove_nonmembership([b"dog"], [b"cat"])
+
+    acc = Accumulator.from_bytes(acc.to_bytes(), group)
+    membership_proof = MembershipProof.from_bytes(membership_proof.to_bytes(), group)
+    nonmembership_proof = NonmembershipProof.from_bytes(nonmembership_proof.to_bytes(), group)
+    assert membership_proof.group == group
+    assert acc.verify_membership([b"dog"], membership_proof)
+    assert acc.verify_nonmembership([b"cat"], nonmembership_proof)
+
+    with pytest.raises(ValueError):
+        Accumulator.from_bytes(acc.to_bytes()[:-1], group)
+    with pytest.raises(ValueError):
+        MembershipProof.from_bytes(membership_proof.to_bytes() + b"\0", group)
+
+
+def test_group_mismatch():
+    rsa_acc, class_acc = Accumulator("rsa2048"), Accumulator("class")
+    proof = rsa_acc.add([b"dog"])
+    with pytest.raises(ValueError):
+        class_acc.verify_membership([b"dog"], proof)
+    with pytest.raises(ValueError):
+        Accumulator("dsa")
+    # An RSA element does not decode as a class group element.
+    with pytest.raises(ValueError):
+        Accumulator.from_bytes(rsa_acc.to_bytes(), "class")
+
+
+def test_hash_to_prime():
+    p = accumulator.hash_to_prime(b"dog")
+    assert p == accumulator.hash_to_prime(b"dog")
+    assert p != accumulator.hash_to_prime(b"cat")
+    assert p > 2 and all(p % d for d in range(2, 1000))
+
+
+@pytest.mark.parametrize("group", GROUPS)
+def test_vector_commitment(group):
+    vc = VectorCommitment(group)
+    proof = vc.set([(True, 0), (False, 1), (True, 3)])
+    assert vc.verify([(True, 0), (False, 1), (True, 3)], proof)
+    assert not vc.verify([(True, 1)], proof)
+    assert vc.get(3) and not vc.get(2)
+
+    values, proof = vc.open([0, 2, 3])
+    assert values == [True, False, True]
+    proof = VectorProof.from_bytes(proof.to_bytes(), group)
+    bits = [(True, 0), (False, 2), (True, 3)]
+    assert VectorCommitment.verify_commitment(vc.to_bytes(), bits, proof)
+    assert not VectorCommitment.verify_commitment(vc.to_bytes(), [(False, 0)], proof)
+    with pytest.raises(ValueError):
+        vc.set([(True, 5), (False, 5)])
@@ -1,20 +1,39 @@
 //! Accumulator library, built on a generic group interface.
+use crate::bigint::Integer;
 use crate::group::UnknownOrderGroup;
 use crate::hash::hash_to_prime;
-use crate::proof::{Poe, Poke2};
-use crate::util::{divide_and_conquer, int, prime_hash_product, shamir_trick};
-use rug::Integer;
+use crate::proof::{Equation, Poe, Poke2};
+use crate::store::Persist;
+use crate::util::{
+  divide_and_conquer, int, join, prime_hash_product, prime_hashes, product, shamir_trick,
+  PrimeHashable,
+};
+use std::collections::{HashMap, HashSet};
+use std::error;
+use std::fmt;
 use std::hash::Hash;
 use std::marker::PhantomData;
 
 #[derive(Debug)]
-/// The different types of accumulator errors.
+/// The different types of accumulator errors. Where an error is caused by one of several inputs,
+/// `index` is its position among them (e.g. in the `elem_witnesses` of `delete`).
 pub enum AccError {
-  /// Bad witness.
-  BadWitness,
+  /// The witness at `index` does not verify against the accumulator, or is for an element that it
+  /// cannot cover.
+  BadWitness {
+    /// Position of the offending input.
+    index: usize,
+  },
 
-  /// Error when updating a witness.
-  BadWitnessUpdate,
+  /// A witness cannot be updated, as the tracked element at `index` is also added or deleted.
+  BadWitnessUpdate {
+    /// Position of the offending input.
+    index: usize,
+  },
+
+  /// A proof does not verify, or an accumulator value does not match the update that supposedly
+  /// produced it.
+  BadProof,
 
   /// Division by zero.
   DivisionByZero,
@@ -22,10 +41,86 @@ pub enum AccError {
   /// Inexact division where exact division was expected.
   InexactDivision,
 
-  /// Inputs not coprime when they were expected to be coprime.
-  InputsNotCoprime,
+  /// The element at `index` shares its prime hash with another input or an accumulated element,
+  /// where they were expected to be coprime.
+  InputsNotCoprime {
+    /// Position of the offending input.
+    index: usize,
+  },
+
+  /// The element at `index` is not in the accumulator.
+  UnknownElem {
+    /// Position of the offending input.
+    index: usize,
+  },
+
+  /// The element at `index` is already in the accumulator, or repeats an earlier input.
+  DuplicateElem {
+    /// Position of the offending input.
+    index: usize,
+  },
+
+  /// The history does not hold `epoch`, or does not track the element set at `epoch`.
+  UnknownEpoch {
+    /// The requested epoch.
+    epoch: u64,
+  },
 }
 
+impl AccError {
+  /// Returns the position of the input that caused this error, if it was caused by one.
+  pub fn index(&self) -> Option<usize> {
+    match self {
+      AccError::BadWitness { index }
+      | AccError::BadWitnessUpdate { index }
+      | AccError::InputsNotCoprime { index }
+      | AccError::UnknownElem { index }
+      | AccError::DuplicateElem { index } => Some(*index),
+      _ => None,
+    }
+  }
+}
+
+impl fmt::Display for AccError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      AccError::BadWitness { index } => write!(
+        f,
+        "witness {} does not verify against the accumulator",
+        index
+      ),
+      AccError::BadWitnessUpdate { index } => write!(
+        f,
+        "witness cannot be updated, as tracked element {} is added or deleted",
+        index
+      ),
+      AccError::BadProof => f.write_str("proof does not verify"),
+      AccError::DivisionByZero => f.write_str("division by zero"),
+      AccError::InexactDivision => {
+        f.write_str("inexact division where exact division was expected")
+      }
+      AccError::InputsNotCoprime { index } => write!(
+        f,
+        "element {} is not coprime to the other inputs, e.g. because it appears twice",
+        index
+      ),
+      AccError::UnknownElem { index } => write!(f, "element {} is not in the accumulator", index),
+      AccError::DuplicateElem { index } => write!(
+        f,
+        "element {} is already in the accumulator, or appears twice among the inputs",
+        index
+      ),
+      AccError::UnknownEpoch { epoch } => write!(
+        f,
+        "epoch {} (or the element set at that epoch) is not in the history",
+        epoch
+      ),
+    }
+  }
+}
+
+impl error::Error for AccError {}
+
 // See https://doc.rust-lang.org/std/marker/struct.PhantomData.html#ownership-and-the-drop-check
 // for recommendations regarding phantom types. Note that we disregard the suggestion to use a
 // const reference in the phantom type parameter, which causes issues for the `Send` trait.
@@ -48,10 +143,39 @@ impl<G: UnknownOrderGroup, T: Hash> Clone for Accumulator<G, T> {
   }
 }
 
+impl<G: UnknownOrderGroup, T> Persist for Accumulator<G, T>
+where
+  G::Elem: Persist,
+{
+  fn encode(&self, out: &mut Vec<u8>) {
+    self.value.encode(out);
+  }
+
+  fn decode(bytes: &mut &[u8]) -> Option<Self> {
+    Some(Self {
+      phantom: PhantomData,
+      value: G::Elem::decode(bytes)?,
+    })
+  }
+}
+
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 /// A witness to one or more values in an accumulator, represented as an accumulator.
 pub struct Witness<G: UnknownOrderGroup, T: Hash>(pub Accumulator<G, T>);
 
+impl<G: UnknownOrderGroup, T: Hash> Persist for Witness<G, T>
+where
+  G::Elem: Persist,
+{
+  fn encode(&self, out: &mut Vec<u8>) {
+    self.0.encode(out);
+  }
+
+  fn decode(bytes: &mut &[u8]) -> Option<Self> {
+    Some(Witness(Accumulator::decode(bytes)?))
+  }
+}
+
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 /// A succinct proof of membership (some element is in some accumulator).
 pub struct MembershipProof<G: UnknownOrderGroup, T: Hash> {
@@ -60,10 +184,27 @@ pub struct MembershipProof<G: UnknownOrderGroup, T: Hash> {
   proof: Poe<G>,
 }
 
+impl<G: UnknownOrderGroup, T: Hash> Persist for MembershipProof<G, T>
+where
+  G::Elem: Persist,
+{
+  fn encode(&self, out: &mut Vec<u8>) {
+    self.witness.encode(out);
+    self.proof.encode(out);
+  }
+
+  fn decode(bytes: &mut &[u8]) -> Option<Self> {
+    Some(Self {
+      witness: Witness::decode(bytes)?,
+      proof: Poe::decode(bytes)?,
+    })
+  }
+}
+
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 /// A succinct proof of nonmembership (some element is not in some accumulator).
 pub struct NonmembershipProof<G: UnknownOrderGroup, T> {
-  phantom: PhantomData<*const T>,
+  phantom: PhantomData<T>,
   d: G::Elem,
   v: G::Elem,
   gv_inv: G::Elem,
@@ -71,7 +212,50 @@ pub struct NonmembershipProof<G: UnknownOrderGroup, T> {
   poe_proof: Poe<G>,
 }
 
-impl<G: UnknownOrderGroup, T: Eq + Hash> Accumulator<G, T> {
+impl<G: UnknownOrderGroup, T> Persist for NonmembershipProof<G, T>
+where
+  G::Elem: Persist,
+{
+  fn encode(&self, out: &mut Vec<u8>) {
+    self.d.encode(out);
+    self.v.encode(out);
+    self.gv_inv.encode(out);
+    self.poke2_proof.encode(out);
+    self.poe_proof.encode(out);
+  }
+
+  fn decode(bytes: &mut &[u8]) -> Option<Self> {
+    Some(Self {
+      phantom: PhantomData,
+      d: G::Elem::decode(bytes)?,
+      v: G::Elem::decode(bytes)?,
+      gv_inv: G::Elem::decode(bytes)?,
+      poke2_proof: Poke2::decode(bytes)?,
+      poe_proof: Poe::decode(bytes)?,
+    })
+  }
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+/// A proof that some elements were added to an accumulator, none of which it held before.
+pub struct UniqueAdditionProof<G: UnknownOrderGroup, T: Hash> {
+  // Membership of the elements in the new accumulator, with the old one as the witness.
+  membership_proof: MembershipProof<G, T>,
+  // Nonmembership of the elements in the old accumulator.
+  nonmembership_proof: NonmembershipProof<G, T>,
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+/// A witness that some elements are not in some accumulator, from which nonmembership proofs can
+/// be computed without knowing the accumulated set. Consists of `d = g^a` and `b`, where
+/// `a * x + b * s = 1` for the prime hash products `x` of the elements and `s` of the set.
+pub struct NonmembershipWitness<G: UnknownOrderGroup, T> {
+  phantom: PhantomData<T>,
+  d: G::Elem,
+  b: Integer,
+}
+
+impl<G: UnknownOrderGroup, T: Eq + PrimeHashable> Accumulator<G, T> {
   /// Returns a new, empty accumulator.
   pub fn empty() -> Self {
     Self {
@@ -84,14 +268,22 @@ impl<G: UnknownOrderGroup, T: Eq + Hash> Accumulator<G, T> {
   /// efficient `add_with_proof`.
   fn add_(&self, elems: &[T]) -> (Self, Integer) {
     let x = prime_hash_product(elems);
-    let acc_elem = G::exp(&self.value, &x);
-    (
-      Self {
-        phantom: PhantomData,
-        value: acc_elem,
-      },
-      x,
-    )
+    (self.add_product(&x), x)
+  }
+
+  /// Like `add`, but takes the prime hash product of the elements, which may contain repeated
+  /// factors (see `MultisetAccumulator`).
+  pub(crate) fn add_product(&self, x: &Integer) -> Self {
+    // Accumulators built from scratch can use the group's fixed-base table.
+    let acc_elem = if self.value == G::unknown_order_elem() {
+      G::exp_unknown_order_elem(x)
+    } else {
+      G::exp(&self.value, x)
+    };
+    Self {
+      phantom: PhantomData,
+      value: acc_elem,
+    }
   }
 
   // The conciseness of `accumulator.add()` and low probability of confusion with implementations of
@@ -118,26 +310,119 @@ impl<G: UnknownOrderGroup, T: Eq + Hash> Accumulator<G, T> {
     )
   }
 
+  /// A duplicate-safe version of `add_with_proof`. Given the `acc_set` committed to by this
+  /// accumulator, fails with `DuplicateElem` if any of `elems` is already in it or appears twice in
+  /// `elems`. Otherwise returns the new accumulator along with a proof, checked by
+  /// `verify_add_unique`, that `elems` were added and that none of them was there before.
+  pub fn add_unique(
+    self,
+    acc_set: &[T],
+    elems: &[T],
+  ) -> Result<(Self, UniqueAdditionProof<G, T>), AccError> {
+    let x =
+      distinct_prime_hash_product(elems).map_err(|index| AccError::DuplicateElem { index })?;
+    let s = prime_hash_product(acc_set);
+    let witness = Self::compute_nonmembership_witness_product(&s, &x).ok_or_else(|| {
+      AccError::DuplicateElem {
+        index: first_dividing(elems, &s),
+      }
+    })?;
+    Ok(self.add_unique_(&x, &witness))
+  }
+
+  /// Like `add_unique`, but takes a `witness` for the nonmembership of `elems` instead of the
+  /// accumulated set. A wrong `witness` yields a proof that fails to verify.
+  pub fn add_unique_with_witness(
+    self,
+    elems: &[T],
+    witness: &NonmembershipWitness<G, T>,
+  ) -> Result<(Self, UniqueAdditionProof<G, T>), AccError> {
+    let x =
+      distinct_prime_hash_product(elems).map_err(|index| AccError::DuplicateElem { index })?;
+    Ok(self.add_unique_(&x, witness))
+  }
+
+  fn add_unique_(
+    self,
+    x: &Integer,
+    witness: &NonmembershipWitness<G, T>,
+  ) -> (Self, UniqueAdditionProof<G, T>) {
+    let nonmembership_proof = self.prove_nonmembership_product(x, witness);
+    let acc = self.add_product(x);
+    let membership_proof = acc.prove_membership_product(x, Witness(self));
+    (
+      acc,
+      UniqueAdditionProof {
+        membership_proof,
+        nonmembership_proof,
+      },
+    )
+  }
+
+  /// Verifies that `new_acc` is this accumulator with `elems` added, where no two of `elems` are
+  /// equal and none of them was in this accumulator.
+  pub fn verify_add_unique(
+    &self,
+    new_acc: &Self,
+    elems: &[T],
+    UniqueAdditionProof {
+      membership_proof,
+      nonmembership_proof,
+    }: &UniqueAdditionProof<G, T>,
+  ) -> bool {
+    let x = match distinct_prime_hash_product(elems) {
+      Ok(x) => x,
+      Err(_) => return false,
+    };
+    membership_proof.witness.0 == *self
+      && new_acc.verify_membership_product(&x, membership_proof)
+      && self.verify_nonmembership_product(&x, nonmembership_proof)
+  }
+
   /// Internal delete method that also returns the prime hash product of deleted elements, enabling
   /// an efficient `delete_with_proof`.
   ///
   /// Uses a divide-and-conquer approach to running the ShamirTrick, which keeps the average input
   /// smaller: For `[a, b, c, d]` do `S(S(a, b), S(c, d))` instead of `S(S(S(a, b), c), d)`.
   fn delete_(self, elem_witnesses: &[(T, Witness<G, T>)]) -> Result<(Self, Integer), AccError> {
-    let prime_witnesses = elem_witnesses
+    let elems = elem_witnesses
       .iter()
-      .map(|(elem, witness)| (hash_to_prime(elem), witness.0.value.clone()))
+      .map(|(elem, _)| elem)
+      .collect::<Vec<_>>();
+    let prime_witnesses = prime_hashes(&elems)
+      .into_iter()
+      .zip(
+        elem_witnesses
+          .iter()
+          .map(|(_, witness)| witness.0.value.clone()),
+      )
       .collect::<Vec<_>>();
 
-    for (p, witness_elem) in &prime_witnesses {
+    let mut seen = HashSet::new();
+    for (index, (p, witness_elem)) in prime_witnesses.iter().enumerate() {
+      if !seen.insert(p) {
+        return Err(AccError::DuplicateElem { index });
+      }
       if G::exp(&witness_elem, &p) != self.value {
-        return Err(AccError::BadWitness);
+        return Err(AccError::BadWitness { index });
       }
     }
+    // Each item also carries the position of its first element, for errors.
+    let prime_witnesses: Vec<_> = prime_witnesses
+      .into_iter()
+      .enumerate()
+      .map(|(index, (p, witness_elem))| (p, witness_elem, index))
+      .collect();
 
-    let (prime_product, acc_elem) = divide_and_conquer(
-      |(p1, v1), (p2, v2)| Ok((int(p1 * p2), shamir_trick::<G>(&v1, &v2, p1, p2).unwrap())),
-      (int(1), self.value),
+    let (prime_product, acc_elem, _) = divide_and_conquer(
+      |(p1, v1, index), (p2, v2, other_index)| {
+        // Cannot fail for distinct elements with valid witnesses, as checked above.
+        let v = shamir_trick::<G>(&v1, &v2, p1, p2).ok_or(AccError::InputsNotCoprime {
+          index: *other_index,
+        })?;
+        Ok((int(p1 * p2), v, *index))
+      },
+      (int(1), self.value, 0),
       &prime_witnesses[..],
     )?;
 
@@ -188,11 +473,7 @@ impl<G: UnknownOrderGroup, T: Eq + Hash> Accumulator<G, T> {
     &self,
     elem_witnesses: &[(T, Witness<G, T>)],
   ) -> Result<MembershipProof<G, T>, AccError> {
-    let witness_accum = self.clone().delete(elem_witnesses)?;
-    let prod = elem_witnesses
-      .iter()
-      .map(|(t, _)| hash_to_prime(t))
-      .product();
+    let (witness_accum, prod) = self.clone().delete_(elem_witnesses)?;
     let proof = Poe::<G>::prove(&witness_accum.value, &prod, &self.value);
     Ok(MembershipProof {
       witness: Witness(witness_accum),
@@ -200,6 +481,27 @@ impl<G: UnknownOrderGroup, T: Eq + Hash> Accumulator<G, T> {
     })
   }
 
+  /// Computes the batch membership proof for `elems` from a single `witness` to all of them (e.g.
+  /// the accumulator of every other element), without needing individual witnesses. A wrong
+  /// `witness` yields a proof that fails to verify.
+  pub fn prove_membership_with_witness(
+    &self,
+    elems: &[T],
+    witness: Witness<G, T>,
+  ) -> MembershipProof<G, T> {
+    self.prove_membership_product(&prime_hash_product(elems), witness)
+  }
+
+  /// Like `prove_membership_with_witness`, but takes the prime hash product of the elements.
+  pub(crate) fn prove_membership_product(
+    &self,
+    x: &Integer,
+    witness: Witness<G, T>,
+  ) -> MembershipProof<G, T> {
+    let proof = Poe::<G>::prove(&witness.0.value, x, &self.value);
+    MembershipProof { witness, proof }
+  }
+
   /// Verifies a membership proof against the current accumulator and an element `t` whose
   /// inclusion is being proven.
   pub fn verify_membership(
@@ -212,13 +514,84 @@ impl<G: UnknownOrderGroup, T: Eq + Hash> Accumulator<G, T> {
   }
 
   /// Batch version of `verify_membership` for multiple `elems`.
-  pub fn verify_membership_batch(
+  pub fn verify_membership_batch(&self, elems: &[T], proof: &MembershipProof<G, T>) -> bool {
+    self.verify_membership_product(&prime_hash_product(elems), proof)
+  }
+
+  /// Like `verify_membership_batch`, but takes the prime hash product of the elements, for callers
+  /// that hash them ahead of time.
+  pub(crate) fn verify_membership_product(
     &self,
-    elems: &[T],
+    x: &Integer,
     MembershipProof { witness, proof }: &MembershipProof<G, T>,
   ) -> bool {
-    let exp = prime_hash_product(elems);
-    Poe::verify(&witness.0.value, &exp, &self.value, proof)
+    Poe::verify(&witness.0.value, x, &self.value, proof)
+  }
+
+  /// Returns the equations `verify_membership_product` checks, or `None` if the proof is
+  /// malformed, for callers that batch them with other proofs' (see `proof::verify_batch`).
+  pub(crate) fn membership_equations_product(
+    &self,
+    x: &Integer,
+    MembershipProof { witness, proof }: &MembershipProof<G, T>,
+  ) -> Option<Vec<Equation<G>>> {
+    Some(vec![Poe::equation(
+      &witness.0.value,
+      x,
+      &self.value,
+      proof,
+    )?])
+  }
+
+  /// Proves that `x`, the prime hash product of the accumulated elements, divides `y`, i.e. that
+  /// every accumulated element is among those whose prime hashes make up `y` (each at most as often
+  /// as in `y`). The proof is an NI-PoKE2 of `y / x` for `self ^ (y / x) = g ^ y`, so it has
+  /// constant size and does not reveal `x`. Returns `None` if `x` does not divide `y`.
+  pub(crate) fn prove_divides_product(&self, x: &Integer, y: &Integer) -> Option<Poke2<G>> {
+    if !y.is_divisible(x) {
+      return None;
+    }
+    let cofactor = int(y / x);
+    let result = G::exp_unknown_order_elem(y);
+    Some(Poke2::prove(&self.value, &cofactor, &result))
+  }
+
+  /// Verifies a proof from `prove_divides_product` that the accumulated elements' prime hash
+  /// product divides `y`.
+  pub(crate) fn verify_divides_product(&self, y: &Integer, proof: &Poke2<G>) -> bool {
+    Poke2::verify(&self.value, &G::exp_unknown_order_elem(y), proof)
+  }
+
+  /// Aggregates batch membership proofs for disjoint sets of elements into a single batch proof
+  /// for their union, via repeated application of the Shamir trick. Unlike `prove_membership`,
+  /// this needs no witnesses beyond those in the proofs, so anyone can aggregate.
+  ///
+  /// # Arguments
+  ///
+  /// * `elem_proofs` - Tuples consisting of (elements, proof of their membership).
+  pub fn aggregate_membership_proofs(
+    &self,
+    elem_proofs: &[(&[T], &MembershipProof<G, T>)],
+  ) -> Result<MembershipProof<G, T>, AccError> {
+    let mut witness = self.value.clone();
+    let mut x = int(1);
+    for (index, (elems, proof)) in elem_proofs.iter().enumerate() {
+      let proof_x = prime_hash_product(elems);
+      if !self.verify_membership_product(&proof_x, proof) {
+        return Err(AccError::BadWitness { index });
+      }
+      witness = shamir_trick::<G>(&witness, &proof.witness.0.value, &x, &proof_x)
+        .ok_or(AccError::InputsNotCoprime { index })?;
+      x *= proof_x;
+    }
+    let proof = Poe::<G>::prove(&witness, &x, &self.value);
+    Ok(MembershipProof {
+      witness: Witness(Self {
+        phantom: PhantomData,
+        value: witness,
+      }),
+      proof,
+    })
   }
 
   /// Updates a `witness` for `tracked_elems` w.r.t the current accumulator, adding the elements in
@@ -236,14 +609,18 @@ impl<G: UnknownOrderGroup, T: Eq + Hash> Accumulator<G, T> {
     let x = prime_hash_product(tracked_elems);
     let x_hat = prime_hash_product(untracked_deletions);
 
-    for elem in tracked_elems {
+    for (index, elem) in tracked_elems.iter().enumerate() {
       if untracked_additions.contains(elem) || untracked_deletions.contains(elem) {
-        return Err(AccError::BadWitnessUpdate);
+        return Err(AccError::BadWitnessUpdate { index });
       }
     }
 
     let (gcd, a, b) = <(Integer, Integer, Integer)>::from(x.gcd_cofactors_ref(&x_hat));
-    assert!(gcd == int(1));
+    if gcd != int(1) {
+      return Err(AccError::BadWitnessUpdate {
+        index: first_dividing(tracked_elems, &x_hat),
+      });
+    }
 
     let w = witness.0.add(untracked_additions);
     let w_to_b = G::exp(&w.value, &b);
@@ -266,36 +643,119 @@ impl<G: UnknownOrderGroup, T: Eq + Hash> Accumulator<G, T> {
     acc_set: &[T],
     elems: &[T],
   ) -> Result<NonmembershipProof<G, T>, AccError> {
-    let x: Integer = elems.iter().map(hash_to_prime).product();
-    let s = acc_set.iter().map(hash_to_prime).product();
-    let (gcd, a, b) = <(Integer, Integer, Integer)>::from(x.gcd_cofactors_ref(&s));
+    let witness = self.compute_nonmembership_witness(acc_set, elems)?;
+    Ok(self.prove_nonmembership_with_witness(elems, &witness))
+  }
+
+  /// Computes a witness that the elements in `elems` are not in this accumulator, given its
+  /// `acc_set`.
+  pub fn compute_nonmembership_witness(
+    &self,
+    acc_set: &[T],
+    elems: &[T],
+  ) -> Result<NonmembershipWitness<G, T>, AccError> {
+    let s = prime_hash_product(acc_set);
+    Self::compute_nonmembership_witness_product(&s, &prime_hash_product(elems)).ok_or_else(|| {
+      AccError::InputsNotCoprime {
+        index: first_dividing(elems, &s),
+      }
+    })
+  }
+
+  /// Like `compute_nonmembership_witness`, but takes the prime hash products `s` of the
+  /// accumulated set and `x` of the elements.
+  pub(crate) fn compute_nonmembership_witness_product(
+    s: &Integer,
+    x: &Integer,
+  ) -> Option<NonmembershipWitness<G, T>> {
+    let (gcd, a, b) = <(Integer, Integer, Integer)>::from(x.gcd_cofactors_ref(s));
 
     if gcd != int(1) {
-      return Err(AccError::InputsNotCoprime);
+      return None;
     }
 
+    Some(NonmembershipWitness {
+      phantom: PhantomData,
+      d: G::exp_unknown_order_elem(&a),
+      b,
+    })
+  }
+
+  /// Computes the batch non-membership proof for `elems` from a `witness` to their nonmembership,
+  /// without needing the accumulated set. A wrong `witness` yields a proof that fails to verify.
+  pub fn prove_nonmembership_with_witness(
+    &self,
+    elems: &[T],
+    witness: &NonmembershipWitness<G, T>,
+  ) -> NonmembershipProof<G, T> {
+    self.prove_nonmembership_product(&prime_hash_product(elems), witness)
+  }
+
+  /// Like `prove_nonmembership_with_witness`, but takes the prime hash product of the elements.
+  pub(crate) fn prove_nonmembership_product(
+    &self,
+    x: &Integer,
+    NonmembershipWitness { d, b, .. }: &NonmembershipWitness<G, T>,
+  ) -> NonmembershipProof<G, T> {
     let g = G::unknown_order_elem();
-    let d = G::exp(&g, &a);
-    let v = G::exp(&self.value, &b);
+    let v = G::exp(&self.value, b);
     let gv_inv = G::op(&g, &G::inv(&v));
 
-    let poke2_proof = Poke2::prove(&self.value, &b, &v);
-    let poe_proof = Poe::prove(&d, &x, &gv_inv);
-    Ok(NonmembershipProof {
+    let poke2_proof = Poke2::prove(&self.value, b, &v);
+    let poe_proof = Poe::prove(d, x, &gv_inv);
+    NonmembershipProof {
       phantom: PhantomData,
-      d,
+      d: d.clone(),
       v,
       gv_inv,
       poke2_proof,
       poe_proof,
+    }
+  }
+
+  /// Updates a `witness` for the nonmembership of `elems` in this accumulator, returning a witness
+  /// for their nonmembership in the accumulator after adding `additions`. Unlike
+  /// `update_membership_witness`, this is called on the accumulator *before* the update.
+  ///
+  /// With `alpha * x + beta * y = 1` for the prime hash product `y` of the additions, the new
+  /// witness is `(d * acc^(b * alpha), b * beta)`, after which `b` is reduced modulo `x` to keep it
+  /// small.
+  pub fn update_nonmembership_witness(
+    &self,
+    witness: NonmembershipWitness<G, T>,
+    elems: &[T],
+    additions: &[T],
+  ) -> Result<NonmembershipWitness<G, T>, AccError> {
+    let x = prime_hash_product(elems);
+    let y = prime_hash_product(additions);
+    let (gcd, alpha, beta) = <(Integer, Integer, Integer)>::from(x.gcd_cofactors_ref(&y));
+    if gcd != int(1) {
+      return Err(AccError::BadWitnessUpdate {
+        index: first_dividing(elems, &y),
+      });
+    }
+
+    let (q, b) = <(Integer, Integer)>::from((beta * &witness.b).div_rem_floor_ref(&x));
+    // `d * acc^(b * alpha) * acc_new^q`, where `acc_new = acc^y`.
+    let exp = alpha * &witness.b + q * y;
+    Ok(NonmembershipWitness {
+      phantom: PhantomData,
+      d: G::op(&witness.d, &G::exp(&self.value, &exp)),
+      b,
     })
   }
 
   /// Verifies a non-membership proof against the current accumulator and elements `elems` whose
   /// non-inclusion is being proven.
-  pub fn verify_nonmembership(
+  pub fn verify_nonmembership(&self, elems: &[T], proof: &NonmembershipProof<G, T>) -> bool {
+    self.verify_nonmembership_product(&prime_hash_product(elems), proof)
+  }
+
+  /// Like `verify_nonmembership`, but takes the prime hash product of the elements, for callers
+  /// that hash them ahead of time.
+  pub(crate) fn verify_nonmembership_product(
     &self,
-    elems: &[T],
+    x: &Integer,
     NonmembershipProof {
       d,
       v,
@@ -305,18 +765,63 @@ impl<G: UnknownOrderGroup, T: Eq + Hash> Accumulator<G, T> {
       ..
     }: &NonmembershipProof<G, T>,
   ) -> bool {
-    let x = elems.iter().map(hash_to_prime).product();
-    Poke2::verify(&self.value, v, poke2_proof) && Poe::verify(d, &x, gv_inv, poe_proof)
+    // `gv_inv` must really be `g * v^-1`, or the two proofs say nothing about each other.
+    G::op(gv_inv, v) == G::unknown_order_elem()
+      && Poke2::verify(&self.value, v, poke2_proof)
+      && Poe::verify(d, x, gv_inv, poe_proof)
+  }
+
+  /// Returns the equations `verify_nonmembership_product` checks, or `None` if the proof is
+  /// malformed, for callers that batch them with other proofs' (see `proof::verify_batch`).
+  pub(crate) fn nonmembership_equations_product(
+    &self,
+    x: &Integer,
+    NonmembershipProof {
+      d,
+      v,
+      gv_inv,
+      poke2_proof,
+      poe_proof,
+      ..
+    }: &NonmembershipProof<G, T>,
+  ) -> Option<Vec<Equation<G>>> {
+    if G::op(gv_inv, v) != G::unknown_order_elem() {
+      return None;
+    }
+    Some(vec![
+      Poke2::equation(&self.value, v, poke2_proof)?,
+      Poe::equation(d, x, gv_inv, poe_proof)?,
+    ])
+  }
+}
+
+// Returns the prime hash product of `elems`, or the position of the first one whose prime hash
+// repeats an earlier one.
+fn distinct_prime_hash_product<T: PrimeHashable>(elems: &[T]) -> Result<Integer, usize> {
+  let primes = prime_hashes(elems);
+  let mut seen = HashSet::new();
+  if let Some(index) = primes.iter().position(|p| !seen.insert(p)) {
+    return Err(index);
   }
+  Ok(product(&primes))
 }
 
-impl<G: UnknownOrderGroup, T: Eq + Hash> From<&[T]> for Accumulator<G, T> {
+// Returns the position of the first of `elems` whose prime hash divides `x`. Only meant for
+// reporting errors once `x` is known not to be coprime to their product, so it defaults to 0.
+fn first_dividing<T: PrimeHashable>(elems: &[T], x: &Integer) -> usize {
+  elems
+    .iter()
+    .position(|elem| x.is_divisible(&hash_to_prime(elem)))
+    .unwrap_or(0)
+}
+
+impl<G: UnknownOrderGroup, T: Eq + PrimeHashable> From<&[T]> for Accumulator<G, T> {
   fn from(ts: &[T]) -> Self {
     Self::empty().add(ts)
   }
 }
 
-impl<G: UnknownOrderGroup, T: Clone + Hash> Witness<G, T> {
+impl<G: UnknownOrderGroup, T: Clone + PrimeHashable> Witness<G, T> {
   /// Given a witness for `witness_set`, returns a witness for `witness_subset`.
   ///
   /// The `witness_subset` must be a subset of the `witness_set`.
@@ -328,9 +833,9 @@ impl<G: UnknownOrderGroup, T: Clone + Hash> Witness<G, T> {
   where
     T: PartialEq,
   {
-    for witness in witness_subset {
+    for (index, witness) in witness_subset.iter().enumerate() {
       if !witness_set.contains(witness) {
-        return Err(AccError::BadWitness);
+        return Err(AccError::BadWitness { index });
       }
     }
 
@@ -348,10 +853,40 @@ impl<G: UnknownOrderGroup, T: Clone + Hash> Witness<G, T> {
     }))
   }
 
+  /// Given a witness for `elems` and an `other` witness for the disjoint `other_elems`, both w.r.t.
+  /// the same accumulator, returns a witness for the union of `elems` and `other_elems`.
+  ///
+  /// Fails with `InputsNotCoprime`, indexing `other_elems`, if the two sets overlap, and otherwise
+  /// with `BadWitness { index: 1 }` if the witnesses are not w.r.t. the same accumulator.
+  pub fn union(&self, elems: &[T], other: &Self, other_elems: &[T]) -> Result<Self, AccError> {
+    let x = prime_hash_product(elems);
+    self
+      .union_product(&x, other, &prime_hash_product(other_elems))
+      .ok_or_else(|| {
+        match other_elems
+          .iter()
+          .position(|elem| x.is_divisible(&hash_to_prime(elem)))
+        {
+          Some(index) => AccError::InputsNotCoprime { index },
+          None => AccError::BadWitness { index: 1 },
+        }
+      })
+  }
+
+  /// Like `union`, but takes the prime hash products of the elements, for callers that hash them
+  /// ahead of time. Returns `None` where `union` fails.
+  pub(crate) fn union_product(&self, x: &Integer, other: &Self, y: &Integer) -> Option<Self> {
+    let value = shamir_trick::<G>(&self.0.value, &other.0.value, x, y)?;
+    Some(Self(Accumulator {
+      phantom: PhantomData,
+      value,
+    }))
+  }
+
   /// Given a witness for many `elems`, computes a sub-witness for each individual element in
   /// O(N log N) time.
   pub fn compute_individual_witnesses(&self, elems: &[T]) -> Vec<(T, Self)> {
-    let hashes = elems.iter().map(hash_to_prime).collect::<Vec<_>>();
+    let hashes = prime_hashes(elems);
     elems
       .iter()
       .zip(self.root_factor(&hashes).iter())
@@ -359,29 +894,217 @@ impl<G: UnknownOrderGroup, T: Clone + Hash> Witness<G, T> {
       .collect()
   }
 
-  #[allow(non_snake_case)]
   fn root_factor(&self, elems: &[Integer]) -> Vec<Self> {
-    if elems.len() == 1 {
-      return vec![self.clone()];
-    }
-    let half_n = elems.len() / 2;
-    let g_l = elems[..half_n].iter().fold(self.clone(), |sum, x| {
-      Self(Accumulator {
-        phantom: PhantomData,
-        value: G::exp(&sum.0.value, x),
+    root_factor::<G>(&self.0.value, elems)
+      .into_iter()
+      .map(|value| {
+        Self(Accumulator {
+          phantom: PhantomData,
+          value,
+        })
       })
+      .collect()
+  }
+}
+
+/// Keeps membership witnesses for a set of tracked elements current as the accumulator changes.
+///
+/// Rather than updating each witness separately, the manager keeps a single witness for all tracked
+/// elements, which takes one `update_membership_witness` call per update batch. Individual
+/// witnesses are split off of it on demand (in O(N log N) time, see `compute_individual_witnesses`)
+/// and cached until the next change.
+pub struct WitnessManager<G: UnknownOrderGroup, T: Hash> {
+  acc: Accumulator<G, T>,
+  // Witness for all of `tracked` at once.
+  witness: Witness<G, T>,
+  tracked: HashSet<T>,
+  invalidated: HashSet<T>,
+  witnesses: Option<HashMap<T, Witness<G, T>>>,
+}
+
+impl<G: UnknownOrderGroup, T: Clone + Eq + PrimeHashable> WitnessManager<G, T> {
+  /// Creates a manager for `acc` that tracks no elements yet.
+  pub fn new(acc: Accumulator<G, T>) -> Self {
+    Self {
+      witness: Witness(acc.clone()),
+      acc,
+      tracked: HashSet::new(),
+      invalidated: HashSet::new(),
+      witnesses: None,
+    }
+  }
+
+  /// Returns the accumulator that all witnesses are current for.
+  pub fn accumulator(&self) -> &Accumulator<G, T> {
+    &self.acc
+  }
+
+  /// Returns whether `elem` is tracked.
+  pub fn is_tracked(&self, elem: &T) -> bool {
+    self.tracked.contains(elem)
+  }
+
+  /// Returns whether `elem` stopped being tracked because an update deleted it.
+  pub fn is_invalidated(&self, elem: &T) -> bool {
+    self.invalidated.contains(elem)
+  }
+
+  /// Returns the elements that stopped being tracked because an update deleted them.
+  pub fn invalidated(&self) -> Vec<T> {
+    self.invalidated.iter().cloned().collect()
+  }
+
+  /// Starts tracking the elements in `elem_witnesses`, which must be members of the current
+  /// accumulator and not tracked already. On error, nothing is registered.
+  ///
+  /// # Arguments
+  ///
+  /// * `elem_witnesses` - Tuples consisting of (element to track, element's witness).
+  pub fn register(&mut self, elem_witnesses: &[(T, Witness<G, T>)]) -> Result<(), AccError> {
+    let mut witness = self.witness.0.value.clone();
+    let mut x = prime_hash_product(&self.tracked.iter().collect::<Vec<_>>());
+    let mut new_elems = HashSet::new();
+    for (index, (elem, elem_witness)) in elem_witnesses.iter().enumerate() {
+      if self.tracked.contains(elem) || !new_elems.insert(elem) {
+        return Err(AccError::DuplicateElem { index });
+      }
+      let elem_x = hash_to_prime(elem);
+      if G::exp(&elem_witness.0.value, &elem_x) != self.acc.value {
+        return Err(AccError::BadWitness { index });
+      }
+      witness = shamir_trick::<G>(&witness, &elem_witness.0.value, &x, &elem_x)
+        .ok_or(AccError::InputsNotCoprime { index })?;
+      x *= elem_x;
+    }
+    self.witness = Witness(Accumulator {
+      phantom: PhantomData,
+      value: witness,
     });
-    let g_r = elems[half_n..].iter().fold(self.clone(), |sum, x| {
-      Self(Accumulator {
-        phantom: PhantomData,
-        value: G::exp(&sum.0.value, x),
-      })
+    for (elem, _) in elem_witnesses {
+      self.invalidated.remove(elem);
+      self.tracked.insert(elem.clone());
+    }
+    self.witnesses = None;
+    Ok(())
+  }
+
+  /// Stops tracking `elems`. Elements that are not tracked are ignored.
+  pub fn unregister(&mut self, elems: &[T]) {
+    let elems: Vec<T> = elems
+      .iter()
+      .filter(|elem| self.tracked.remove(elem))
+      .cloned()
+      .collect();
+    self.witness = Witness(self.witness.0.clone().add(&elems));
+    self.witnesses = None;
+  }
+
+  /// Applies an update batch that took the accumulator to `acc_new`. Tracked elements among the
+  /// `deletions` stop being tracked and are flagged as invalidated; they are also returned. On
+  /// error (e.g. if `acc_new` does not match the batch), the manager is left as it was.
+  ///
+  /// # Arguments
+  ///
+  /// * `acc_new` - The accumulator after the batch.
+  /// * `additions` - Elements added in the batch, none of which may be tracked.
+  /// * `deletions` - Elements deleted in the batch.
+  pub fn update(
+    &mut self,
+    acc_new: &Accumulator<G, T>,
+    additions: &[T],
+    deletions: &[T],
+  ) -> Result<Vec<T>, AccError> {
+    if let Some(index) = additions
+      .iter()
+      .position(|elem| self.tracked.contains(elem))
+    {
+      return Err(AccError::DuplicateElem { index });
+    }
+    let deleted: Vec<T> = deletions
+      .iter()
+      .filter(|elem| self.tracked.contains(elem))
+      .cloned()
+      .collect();
+    let tracked: Vec<T> = self
+      .tracked
+      .iter()
+      .filter(|elem| !deleted.contains(elem))
+      .cloned()
+      .collect();
+    // A witness for the remaining tracked elements, i.e. one that also covers `deleted`.
+    let witness = Witness(self.witness.0.clone().add(&deleted));
+    let witness = acc_new.update_membership_witness(witness, &tracked, additions, deletions)?;
+    if witness.0.clone().add(&tracked) != *acc_new {
+      return Err(AccError::BadProof);
+    }
+
+    self.acc = acc_new.clone();
+    self.witness = witness;
+    for elem in &deleted {
+      self.tracked.remove(elem);
+      self.invalidated.insert(elem.clone());
+    }
+    self.witnesses = None;
+    Ok(deleted)
+  }
+
+  /// Returns the current witness for `elem`, if it is tracked.
+  pub fn witness(&mut self, elem: &T) -> Option<Witness<G, T>> {
+    if !self.tracked.contains(elem) {
+      return None;
+    }
+    let (witness, tracked) = (&self.witness, &self.tracked);
+    let witnesses = self.witnesses.get_or_insert_with(|| {
+      let elems: Vec<T> = tracked.iter().cloned().collect();
+      witness
+        .compute_individual_witnesses(&elems)
+        .into_iter()
+        .collect()
+    });
+    witnesses.get(elem).cloned()
+  }
+
+  /// Computes a batch membership proof for `elems`, all of which must be tracked, w.r.t. the
+  /// current accumulator.
+  pub fn prove_membership(&self, elems: &[T]) -> Result<MembershipProof<G, T>, AccError> {
+    let mut seen = HashSet::new();
+    for (index, elem) in elems.iter().enumerate() {
+      if !self.tracked.contains(elem) {
+        return Err(AccError::UnknownElem { index });
+      }
+      if !seen.insert(elem) {
+        return Err(AccError::DuplicateElem { index });
+      }
+    }
+    let others: Vec<&T> = self
+      .tracked
+      .iter()
+      .filter(|elem| !seen.contains(elem))
+      .collect();
+    let witness = Witness(Accumulator {
+      phantom: PhantomData,
+      value: G::exp(&self.witness.0.value, &prime_hash_product(&others)),
     });
-    let mut L = g_r.root_factor(&Vec::from(&elems[..half_n]));
-    let mut R = g_l.root_factor(&Vec::from(&elems[half_n..]));
-    L.append(&mut R);
-    L
+    Ok(self.acc.prove_membership_with_witness(elems, witness))
+  }
+}
+
+/// Computes `value ^ (x / e)` for each `e` in `elems`, where `x` is the product of `elems`. The two
+/// recursive halves are independent, so the `parallel` feature runs them on separate threads.
+#[allow(non_snake_case)]
+fn root_factor<G: UnknownOrderGroup>(value: &G::Elem, elems: &[Integer]) -> Vec<G::Elem> {
+  if elems.len() <= 1 {
+    return vec![value.clone(); elems.len()];
   }
+  let (left, right) = elems.split_at(elems.len() / 2);
+  let exp_all = |xs: &[Integer]| xs.iter().fold(value.clone(), |acc, x| G::exp(&acc, x));
+  let (g_l, g_r) = join(|| exp_all(left), || exp_all(right));
+  let (mut L, mut R) = join(
+    || root_factor::<G>(&g_r, left),
+    || root_factor::<G>(&g_l, right),
+  );
+  L.append(&mut R);
+  L
 }
 
 #[cfg(test)]
@@ -389,7 +1112,7 @@ mod tests {
   use super::*;
   use crate::group::{ClassGroup, Rsa2048};
 
-  fn new_acc<G: UnknownOrderGroup, T: Hash + Eq>(data: &[T]) -> Accumulator<G, T> {
+  fn new_acc<G: UnknownOrderGroup, T: PrimeHashable + Eq>(data: &[T]) -> Accumulator<G, T> {
     Accumulator::<G, T>::empty().add(data)
   }
 
@@ -457,7 +1180,7 @@ mod tests {
     test_delete_bad_witness,
     test_delete_bad_witness_rsa2048,
     test_delete_bad_witness_class,
-    should_panic(expected = "BadWitness")
+    should_panic(expected = "BadWitness { index: 0 }")
   );
   fn test_delete_bad_witness<G: UnknownOrderGroup>() {
     let acc = Accumulator::<G, &'static str>::empty();
@@ -466,6 +1189,66 @@ mod tests {
     acc.delete(&[("a", a_witness), ("b", b_witness)]).unwrap();
   }
 
+  test_all_groups!(
+    test_delete_bad_second_witness,
+    test_delete_bad_second_witness_rsa2048,
+    test_delete_bad_second_witness_class,
+    should_panic(expected = "BadWitness { index: 1 }")
+  );
+  fn test_delete_bad_second_witness<G: UnknownOrderGroup>() {
+    let acc = new_acc::<G, &'static str>(&["a", "b"]);
+    let a_witness = Witness(new_acc::<G, &'static str>(&["b"]));
+    let b_witness = Witness(new_acc::<G, &'static str>(&["a", "c"]));
+    acc.delete(&[("a", a_witness), ("b", b_witness)]).unwrap();
+  }
+
+  test_all_groups!(
+    test_delete_twice,
+    test_delete_twice_rsa2048,
+    test_delete_twice_class,
+    should_panic(expected = "DuplicateElem { index: 1 }")
+  );
+  fn test_delete_twice<G: UnknownOrderGroup>() {
+    let acc = new_acc::<G, &'static str>(&["a", "b"]);
+    let a_witness = Witness(new_acc::<G, &'static str>(&["b"]));
+    acc
+      .delete(&[("a", a_witness.clone()), ("a", a_witness)])
+      .unwrap();
+  }
+
+  test_all_groups!(
+    test_prove_membership_with_witness,
+    test_prove_membership_with_witness_rsa2048,
+    test_prove_membership_with_witness_class,
+  );
+  fn test_prove_membership_with_witness<G: UnknownOrderGroup>() {
+    let acc = new_acc::<G, &'static str>(&["a", "b", "c"]);
+    let witness = Witness(new_acc::<G, &'static str>(&["b"]));
+    let proof = acc.prove_membership_with_witness(&["a", "c"], witness.clone());
+    assert!(acc.verify_membership_batch(&["a", "c"], &proof));
+    let bad_proof = acc.prove_membership_with_witness(&["a"], witness);
+    assert!(!acc.verify_membership(&"a", &bad_proof));
+  }
+
+  test_all_groups!(
+    test_aggregate_membership_proofs,
+    test_aggregate_membership_proofs_rsa2048,
+    test_aggregate_membership_proofs_class,
+  );
+  fn test_aggregate_membership_proofs<G: UnknownOrderGroup>() {
+    let acc = new_acc::<G, &'static str>(&["a", "b", "c", "d"]);
+    let ab_proof = acc.prove_membership_with_witness(&["a", "b"], Witness(new_acc(&["c", "d"])));
+    let c_proof = acc.prove_membership_with_witness(&["c"], Witness(new_acc(&["a", "b", "d"])));
+    let proof = acc
+      .aggregate_membership_proofs(&[(&["a", "b"], &ab_proof), (&["c"], &c_proof)])
+      .unwrap();
+    assert!(acc.verify_membership_batch(&["a", "b", "c"], &proof));
+    assert!(proof.witness == Witness(new_acc(&["d"])));
+    assert!(acc
+      .aggregate_membership_proofs(&[(&["a", "b"], &ab_proof), (&["b"], &c_proof)])
+      .is_err());
+  }
+
   test_all_groups!(
     test_update_membership_witness,
     test_update_membership_witness_rsa2048,
@@ -509,6 +1292,111 @@ mod tests {
     assert!(acc.verify_nonmembership(&non_members, &proof));
   }
 
+  #[test]
+  fn test_persist_proofs() {
+    let acc_set = ["a", "b"];
+    let acc = new_acc::<Rsa2048, &'static str>(&acc_set);
+    let (acc, membership_proof) = acc.add_with_proof(&["c"]);
+    let nonmembership_proof = acc
+      .prove_nonmembership(&["a", "b", "c"], &["d"])
+      .expect("valid proof expected");
+
+    let mut bytes = vec![];
+    membership_proof.encode(&mut bytes);
+    nonmembership_proof.encode(&mut bytes);
+    let mut rest = &bytes[..];
+    assert_eq!(MembershipProof::decode(&mut rest), Some(membership_proof));
+    assert_eq!(
+      NonmembershipProof::decode(&mut rest),
+      Some(nonmembership_proof)
+    );
+    assert!(rest.is_empty());
+    assert!(MembershipProof::<Rsa2048, &'static str>::decode(&mut &bytes[..10]).is_none());
+  }
+
+  test_all_groups!(
+    test_forged_nonmembership,
+    test_forged_nonmembership_rsa2048,
+    test_forged_nonmembership_class,
+  );
+  fn test_forged_nonmembership<G: UnknownOrderGroup>() {
+    // Consistent proofs for `d^x = gv_inv` and `acc^1 = v`, where `gv_inv` is not `g * v^-1`.
+    let acc = new_acc::<G, &'static str>(&["a", "b"]);
+    let x = prime_hash_product(&["a"]);
+    let d = G::unknown_order_elem();
+    let gv_inv = G::exp(&d, &x);
+    let forged = NonmembershipProof::<G, &'static str> {
+      phantom: PhantomData,
+      poke2_proof: Poke2::prove(&acc.value, &int(1), &acc.value),
+      poe_proof: Poe::prove(&d, &x, &gv_inv),
+      d,
+      v: acc.value.clone(),
+      gv_inv,
+    };
+    assert!(!acc.verify_nonmembership(&["a"], &forged));
+  }
+
+  test_all_groups!(
+    test_update_nonmembership_witness,
+    test_update_nonmembership_witness_rsa2048,
+    test_update_nonmembership_witness_class,
+  );
+  fn test_update_nonmembership_witness<G: UnknownOrderGroup>() {
+    let acc = new_acc::<G, &'static str>(&["a", "b"]);
+    let non_members = ["c", "d"];
+    let witness = acc
+      .compute_nonmembership_witness(&["a", "b"], &non_members)
+      .unwrap();
+    let witness = acc
+      .update_nonmembership_witness(witness, &non_members, &["e", "f", "g"])
+      .unwrap();
+    let acc = acc.add(&["e", "f", "g"]);
+    let witness = acc
+      .update_nonmembership_witness(witness, &non_members, &["h"])
+      .unwrap();
+    let acc = acc.add(&["h"]);
+    let proof = acc.prove_nonmembership_with_witness(&non_members, &witness);
+    assert!(acc.verify_nonmembership(&non_members, &proof));
+    assert!(!acc.verify_nonmembership(&["c", "h"], &proof));
+    assert!(acc
+      .update_nonmembership_witness(witness, &non_members, &["d"])
+      .is_err());
+  }
+
+  test_all_groups!(
+    test_add_unique,
+    test_add_unique_rsa2048,
+    test_add_unique_class,
+  );
+  fn test_add_unique<G: UnknownOrderGroup>() {
+    let acc = new_acc::<G, &'static str>(&["a", "b"]);
+    let (new_acc, proof) = acc.clone().add_unique(&["a", "b"], &["c", "d"]).unwrap();
+    assert!(new_acc == acc.clone().add(&["c", "d"]));
+    assert!(acc.verify_add_unique(&new_acc, &["c", "d"], &proof));
+    assert!(!acc.verify_add_unique(&new_acc, &["c", "e"], &proof));
+    assert!(!new_acc.verify_add_unique(&new_acc, &["c", "d"], &proof));
+
+    // Duplicates, whether already accumulated or within the new elements, are rejected.
+    assert!(acc.clone().add_unique(&["a", "b"], &["b", "c"]).is_err());
+    assert!(acc.clone().add_unique(&["a", "b"], &["c", "c"]).is_err());
+    let (dup_acc, dup_proof) = acc.clone().add_unique(&["a", "b"], &["c"]).unwrap();
+    assert!(!acc.verify_add_unique(&dup_acc.add(&["c"]), &["c", "c"], &dup_proof));
+
+    let witness = acc
+      .compute_nonmembership_witness(&["a", "b"], &["c", "d"])
+      .unwrap();
+    let (_, proof) = acc
+      .clone()
+      .add_unique_with_witness(&["c", "d"], &witness)
+      .unwrap();
+    assert!(acc.verify_add_unique(&new_acc, &["c", "d"], &proof));
+    let (bad_acc, bad_proof) = acc
+      .clone()
+      .add_unique_with_witness(&["a", "c"], &witness)
+      .unwrap();
+    assert!(!acc.verify_add_unique(&bad_acc, &["a", "c"], &bad_proof));
+  }
+
   test_all_groups!(
     test_compute_sub_witness,
     test_compute_sub_witness_rsa2048,
@@ -536,6 +1424,84 @@ mod tests {
       .unwrap();
   }
 
+  test_all_groups!(
+    test_witness_manager,
+    test_witness_manager_rsa2048,
+    test_witness_manager_class,
+  );
+  fn test_witness_manager<G: UnknownOrderGroup>() {
+    let elems = ["a", "b", "c", "d"];
+    let acc = new_acc::<G, &'static str>(&elems);
+    let witnesses = Witness(Accumulator::empty()).compute_individual_witnesses(&elems);
+    let mut manager = WitnessManager::new(acc.clone());
+    manager.register(&witnesses[..2]).unwrap();
+
+    // Add "e" and delete the untracked "c".
+    let acc = acc.add(&["e"]);
+    let c_witness = Witness(new_acc(&["a", "b", "d", "e"]));
+    let acc = acc.delete(&[("c", c_witness)]).unwrap();
+    assert!(manager.update(&acc, &["e"], &["c"]).unwrap().is_empty());
+    for elem in &["a", "b"] {
+      assert!(manager.witness(elem).unwrap().0.add(&[*elem]) == acc);
+    }
+    let proof = manager.prove_membership(&["b", "a"]).unwrap();
+    assert!(acc.verify_membership_batch(&["a", "b"], &proof));
+
+    // Delete the tracked "a".
+    let a_witness = manager.witness(&"a").unwrap();
+    let acc = acc.delete(&[("a", a_witness)]).unwrap();
+    assert_eq!(manager.update(&acc, &[], &["a"]).unwrap(), vec!["a"]);
+    assert!(manager.is_invalidated(&"a"));
+    assert!(!manager.is_tracked(&"a"));
+    assert!(manager.witness(&"a").is_none());
+    assert!(manager.prove_membership(&["a"]).is_err());
+    let proof = manager.prove_membership(&["b"]).unwrap();
+    assert!(acc.verify_membership(&"b", &proof));
+  }
+
+  #[test]
+  fn test_witness_manager_rejects_bad_input() {
+    let elems = ["a", "b", "c"];
+    let acc = new_acc::<Rsa2048, &'static str>(&elems);
+    let witnesses = Witness(Accumulator::empty()).compute_individual_witnesses(&elems);
+    let mut manager = WitnessManager::new(acc.clone());
+    assert!(manager.register(&[("a", witnesses[1].1.clone())]).is_err());
+    assert!(manager
+      .register(&[witnesses[0].clone(), witnesses[0].clone()])
+      .is_err());
+    manager.register(&witnesses[..2]).unwrap();
+    assert!(manager.register(&witnesses[..1]).is_err());
+    assert!(manager.prove_membership(&["c"]).is_err());
+
+    // An update whose accumulator does not match its additions.
+    let acc_new = acc.add(&["d"]);
+    assert!(manager.update(&acc_new, &["e"], &[]).is_err());
+    assert!(manager.update(&acc_new, &["a"], &[]).is_err());
+    assert!(manager.accumulator() == &new_acc(&elems));
+    manager.update(&acc_new, &["d"], &[]).unwrap();
+
+    manager.unregister(&["a", "c"]);
+    assert!(!manager.is_tracked(&"a"));
+    assert!(!manager.is_invalidated(&"a"));
+    let proof = manager.prove_membership(&["b"]).unwrap();
+    assert!(acc_new.verify_membership(&"b", &proof));
+  }
+
+  test_all_groups!(
+    test_witness_union,
+    test_witness_union_rsa2048,
+    test_witness_union_class,
+  );
+  fn test_witness_union<G: UnknownOrderGroup>() {
+    let ab_witness = Witness(new_acc::<G, &'static str>(&["c", "d"]));
+    let cd_witness = Witness(new_acc::<G, &'static str>(&["a", "b"]));
+    let witness = ab_witness
+      .union(&["a", "b"], &cd_witness, &["c", "d"])
+      .unwrap();
+    assert!(witness == Witness(Accumulator::empty()));
+    assert!(ab_witness.union(&["a", "b"], &cd_witness, &["c"]).is_err());
+  }
+
   fn test_compute_individual_witnesses<G: UnknownOrderGroup>() {
     let acc = new_acc::<G, &'static str>(&["a", "b", "c"]);
     let witness_multiple = Witness(new_acc::<G, &'static str>(&["a"]));
@@ -0,0 +1,25 @@
+//! Arbitrary-precision integers used throughout the library.
+//!
+//! By default (the `gmp` feature), `Integer` is GMP's integer type from the `rug` crate. The
+//! `pure-rust` feature swaps in a wrapper around `num-bigint` with the same API (or rather, the
+//! subset of it that we use) and removes the dependency on `libgmp` altogether. The low-level
+//! `mpn_`/`mpz_` routines behind the fixed-width types in `uint` are replaced the same way.
+//!
+//! Both backends produce identical results, down to the bytes that `Integer` feeds to a `Hasher`,
+//! so accumulators, proofs and hashes are interchangeable between them. Expect the pure-Rust
+//! backend to be several times slower.
+#[cfg(not(any(feature = "gmp", feature = "pure-rust")))]
+compile_error!("Enable either the `gmp` or the `pure-rust` feature.");
+
+// Both pure-Rust modules are always built for tests, which check them against GMP.
+#[cfg(any(test, feature = "pure-rust"))]
+#[cfg_attr(not(feature = "pure-rust"), allow(dead_code))]
+pub(crate) mod mpn;
+#[cfg(any(test, feature = "pure-rust"))]
+#[cfg_attr(not(feature = "pure-rust"), allow(dead_code))]
+mod pure;
+
+#[cfg(feature = "pure-rust")]
+pub use pure::{Assign, Digit, Integer, Order, Pow};
+#[cfg(not(feature = "pure-rust"))]
+pub use rug::{integer::Order, ops::Pow, Assign, Integer};
@@ -0,0 +1,476 @@
+//! Pure-Rust stand-ins for the handful of GMP functions that `uint` calls, with the same
+//! signatures as their `gmp_mpfr_sys::gmp` counterparts so that `uint` can switch between the two
+//! with a single `use`.
+//!
+//! The `mpn_` functions follow GMP's contracts on operand sizes and overlap, and the `mpz_`
+//! functions operate on `mpz_t`s that point into fixed-size buffers. Unlike GMP, outputs are never
+//! reallocated: writing more than `alloc` limbs panics instead.
+#![allow(non_camel_case_types, clippy::missing_safety_doc)]
+#![allow(
+  clippy::cast_possible_truncation,
+  clippy::cast_sign_loss,
+  clippy::cast_possible_wrap
+)]
+
+use super::pure::{Integer, Order};
+use num_bigint::BigUint;
+use std::cmp::Ordering;
+use std::ptr::NonNull;
+use std::slice;
+
+/// Number of bits in a limb.
+pub const LIMB_BITS: i32 = 64;
+
+/// Layout-compatible with GMP's `mpz_t`.
+#[derive(Clone, Copy, Debug)]
+pub struct mpz_t {
+  /// Number of limbs allocated at `d`.
+  pub alloc: i32,
+  /// Number of limbs in use; negative for negative integers.
+  pub size: i32,
+  /// Least-significant limb first.
+  pub d: NonNull<u64>,
+}
+
+/// Sets `rp = s1p + s2p` over `n` limbs and returns the carry. `rp` may equal either input.
+pub unsafe fn mpn_add_n(rp: *mut u64, s1p: *const u64, s2p: *const u64, n: i64) -> u64 {
+  let mut carry = false;
+  for i in 0..n as usize {
+    let (sum, c1) = (*s1p.add(i)).overflowing_add(*s2p.add(i));
+    let (sum, c2) = sum.overflowing_add(u64::from(carry));
+    *rp.add(i) = sum;
+    carry = c1 || c2;
+  }
+  u64::from(carry)
+}
+
+/// Sets `rp = s1p - s2p` over `n` limbs and returns the borrow. `rp` may equal either input.
+pub unsafe fn mpn_sub_n(rp: *mut u64, s1p: *const u64, s2p: *const u64, n: i64) -> u64 {
+  let mut borrow = false;
+  for i in 0..n as usize {
+    let (diff, b1) = (*s1p.add(i)).overflowing_sub(*s2p.add(i));
+    let (diff, b2) = diff.overflowing_sub(u64::from(borrow));
+    *rp.add(i) = diff;
+    borrow = b1 || b2;
+  }
+  u64::from(borrow)
+}
+
+/// Adds `s1p * s2limb` to the `n` limbs at `rp` and returns the carry limb.
+pub unsafe fn mpn_addmul_1(rp: *mut u64, s1p: *const u64, n: i64, s2limb: u64) -> u64 {
+  let mut carry = 0_u128;
+  for i in 0..n as usize {
+    let t = u128::from(*rp.add(i)) + u128::from(*s1p.add(i)) * u128::from(s2limb) + carry;
+    *rp.add(i) = t as u64;
+    carry = t >> 64;
+  }
+  carry as u64
+}
+
+/// Sets the `2n` limbs at `rp` to `s1p * s2p`. `rp` must not overlap either input.
+pub unsafe fn mpn_mul_n(rp: *mut u64, s1p: *const u64, s2p: *const u64, n: i64) {
+  let n = n as usize;
+  for i in 0..2 * n {
+    *rp.add(i) = 0;
+  }
+  for i in 0..n {
+    *rp.add(i + n) = mpn_addmul_1(rp.add(i), s1p, n as i64, *s2p.add(i));
+  }
+}
+
+/// Sets the `2n` limbs at `rp` to `s1p * s1p`. `rp` must not overlap the input.
+pub unsafe fn mpn_sqr(rp: *mut u64, s1p: *const u64, n: i64) {
+  mpn_mul_n(rp, s1p, s1p, n);
+}
+
+/// Compares two `n`-limb numbers, returning a negative, zero or positive value.
+pub unsafe fn mpn_cmp(s1p: *const u64, s2p: *const u64, n: i64) -> i32 {
+  let (a, b) = (
+    slice::from_raw_parts(s1p, n as usize),
+    slice::from_raw_parts(s2p, n as usize),
+  );
+  match a.iter().rev().cmp(b.iter().rev()) {
+    Ordering::Less => -1,
+    Ordering::Equal => 0,
+    Ordering::Greater => 1,
+  }
+}
+
+/// Shifts the `n` limbs at `sp` left by `count` bits (`1 <= count <= 64`) into `rp`, returning the
+/// bits shifted out in the low bits of the result. `rp` may equal `sp`.
+pub unsafe fn mpn_lshift(rp: *mut u64, sp: *const u64, n: i64, count: u32) -> u64 {
+  let n = n as usize;
+  let shift = 64 - count;
+  let out = (u128::from(*sp.add(n - 1)) >> shift) as u64;
+  for i in (1..n).rev() {
+    let pair = (u128::from(*sp.add(i)) << 64) | u128::from(*sp.add(i - 1));
+    *rp.add(i) = (pair >> shift) as u64;
+  }
+  *rp = ((u128::from(*sp) << 64) >> shift) as u64;
+  out
+}
+
+/// Shifts the `n` limbs at `sp` right by `count` bits (`1 <= count <= 64`) into `rp`, returning
+/// the bits shifted out in the high bits of the result. `rp` may equal `sp`.
+pub unsafe fn mpn_rshift(rp: *mut u64, sp: *const u64, n: i64, count: u32) -> u64 {
+  let n = n as usize;
+  let out = (u128::from(*sp) << (64 - count)) as u64;
+  for i in 0..n {
+    let hi = if i + 1 < n { *sp.add(i + 1) } else { 0 };
+    let pair = (u128::from(hi) << 64) | u128::from(*sp.add(i));
+    *rp.add(i) = (pair >> count) as u64;
+  }
+  out
+}
+
+/// Divides the `nn` limbs at `np` by the `dn` limbs at `dp`, writing `nn - dn + 1` quotient limbs
+/// to `qp` and `dn` remainder limbs to `rp`. Requires `qxn == 0`, `nn >= dn` and a nonzero top
+/// limb in `dp`. `rp` may equal `np`.
+pub unsafe fn mpn_tdiv_qr(
+  qp: *mut u64,
+  rp: *mut u64,
+  qxn: i64,
+  np: *const u64,
+  nn: i64,
+  dp: *const u64,
+  dn: i64,
+) {
+  assert!(qxn == 0 && nn >= dn && dn > 0);
+  let n = slice::from_raw_parts(np, nn as usize).to_vec();
+  let d = slice::from_raw_parts(dp, dn as usize).to_vec();
+  let (q, r) = div_rem(&n, &d);
+  for (i, limb) in q.into_iter().enumerate() {
+    *qp.add(i) = limb;
+  }
+  for (i, limb) in r.into_iter().enumerate() {
+    *rp.add(i) = limb;
+  }
+}
+
+/// Returns nonzero if the `n` limbs at `s1p` form a perfect square.
+pub unsafe fn mpn_perfect_square_p(s1p: *const u64, n: i64) -> i32 {
+  i32::from(to_integer(s1p, n).is_perfect_square())
+}
+
+/// Writes the digits (not characters) of the `s1n` limbs at `s1p` in base `base` to `str`, most
+/// significant first, and returns the number of digits written.
+pub unsafe fn mpn_get_str(str: *mut u8, base: i32, s1p: *mut u64, s1n: i64) -> usize {
+  let digits = to_biguint(s1p, s1n).to_radix_be(base as u32);
+  let digits = match digits.iter().position(|&d| d != 0) {
+    Some(start) => &digits[start..],
+    None => &[],
+  };
+  for (i, &digit) in digits.iter().enumerate() {
+    *str.add(i) = digit;
+  }
+  digits.len()
+}
+
+/// Reads `strsize` digits (not characters) in base `base` from `str`, most significant first, into
+/// `rp`, and returns the number of limbs written.
+pub unsafe fn mpn_set_str(rp: *mut u64, str: *const u8, strsize: usize, base: i32) -> i64 {
+  let digits = slice::from_raw_parts(str, strsize);
+  let x = BigUint::from_radix_be(digits, base as u32).expect("Invalid digit.");
+  let limbs = x.to_u64_digits();
+  for (i, &limb) in limbs.iter().enumerate() {
+    *rp.add(i) = limb;
+  }
+  limbs.len() as i64
+}
+
+/// Sets `rop` to the inverse of `op1` modulo `op2` and returns nonzero, or returns zero if there
+/// is no inverse.
+pub unsafe fn mpz_invert(rop: *mut mpz_t, op1: *const mpz_t, op2: *const mpz_t) -> i32 {
+  match from_mpz(op1).invert_ref(&from_mpz(op2)) {
+    Some(inv) => {
+      write_mpz(rop, &inv);
+      1
+    }
+    None => 0,
+  }
+}
+
+/// Sets `rop` to `base ^ exp mod modu`.
+pub unsafe fn mpz_powm(rop: *mut mpz_t, base: *const mpz_t, exp: *const mpz_t, modu: *const mpz_t) {
+  let x = from_mpz(base)
+    .pow_mod_ref(&from_mpz(exp), &from_mpz(modu))
+    .expect("Base is not invertible.");
+  write_mpz(rop, &x);
+}
+
+/// Returns the Jacobi symbol `(a / b)`. `b` must be odd and positive.
+pub unsafe fn mpz_jacobi(a: *const mpz_t, b: *const mpz_t) -> i32 {
+  from_mpz(a).jacobi(&from_mpz(b))
+}
+
+/// Returns nonzero if `n = c mod d`.
+pub unsafe fn mpz_congruent_p(n: *const mpz_t, c: *const mpz_t, d: *const mpz_t) -> i32 {
+  i32::from(from_mpz(n).is_congruent(&from_mpz(c), &from_mpz(d)))
+}
+
+/// Returns nonzero if `n` is divisible by `d`. Avoids allocating, since primality testing calls
+/// this once per small prime.
+pub unsafe fn mpz_divisible_ui_p(n: *const mpz_t, d: u64) -> i32 {
+  let n = &*n;
+  let limbs = slice::from_raw_parts(n.d.as_ptr(), n.size.unsigned_abs() as usize);
+  if d == 0 {
+    return i32::from(limbs.is_empty());
+  }
+  let rem = limbs.iter().rev().fold(0_u128, |rem, &limb| {
+    ((rem << 64) | u128::from(limb)) % u128::from(d)
+  });
+  i32::from(rem == 0)
+}
+
+/// Sets `rop` to `op` with all factors of `f` removed, and returns the number removed.
+pub unsafe fn mpz_remove(rop: *mut mpz_t, op: *const mpz_t, f: *const mpz_t) -> u64 {
+  let (mut x, f) = (from_mpz(op), from_mpz(f));
+  let mut count = 0;
+  if x != 0 {
+    loop {
+      let (q, r) = x.clone().div_rem(f.clone());
+      if r != 0 {
+        break;
+      }
+      x = q;
+      count += 1;
+    }
+  }
+  write_mpz(rop, &x);
+  count
+}
+
+unsafe fn to_integer(p: *const u64, n: i64) -> Integer {
+  Integer::from_digits(slice::from_raw_parts(p, n as usize), Order::Lsf)
+}
+
+unsafe fn to_biguint(p: *const u64, n: i64) -> BigUint {
+  let bytes = slice::from_raw_parts(p, n as usize)
+    .iter()
+    .flat_map(|limb| limb.to_le_bytes().to_vec())
+    .collect::<Vec<_>>();
+  BigUint::from_bytes_le(&bytes)
+}
+
+unsafe fn from_mpz(z: *const mpz_t) -> Integer {
+  let z = &*z;
+  let x = to_integer(z.d.as_ptr(), i64::from(z.size.abs()));
+  if z.size < 0 {
+    -x
+  } else {
+    x
+  }
+}
+
+unsafe fn write_mpz(z: *mut mpz_t, x: &Integer) {
+  let z = &mut *z;
+  let limbs = x.to_digits::<u64>(Order::Lsf);
+  assert!(
+    limbs.len() <= z.alloc as usize,
+    "mpz_t output overflows its buffer."
+  );
+  let out = slice::from_raw_parts_mut(z.d.as_ptr(), limbs.len());
+  out.copy_from_slice(&limbs);
+  z.size = if *x < 0 {
+    -(limbs.len() as i32)
+  } else {
+    limbs.len() as i32
+  };
+}
+
+/// Schoolbook long division (Knuth's Algorithm D) of `n` by `d`, whose top limb is nonzero.
+/// Returns `n.len() - d.len() + 1` quotient limbs and `d.len()` remainder limbs.
+fn div_rem(n: &[u64], d: &[u64]) -> (Vec<u64>, Vec<u64>) {
+  let (nn, dn) = (n.len(), d.len());
+  assert!(d[dn - 1] != 0, "Division by zero.");
+  let mut q = vec![0; nn - dn + 1];
+
+  if dn == 1 {
+    let d = u128::from(d[0]);
+    let mut rem = 0_u128;
+    for (q_i, &n_i) in q.iter_mut().zip(n).rev() {
+      let cur = (rem << 64) | u128::from(n_i);
+      *q_i = (cur / d) as u64;
+      rem = cur % d;
+    }
+    return (q, vec![rem as u64]);
+  }
+
+  // Normalize so the divisor's top bit is set, which keeps each quotient-limb estimate within 2
+  // of the truth.
+  let shift = d[dn - 1].leading_zeros();
+  let d = shl_limbs(d, shift, dn);
+  let mut u = shl_limbs(n, shift, nn + 1);
+  let (d_hi, d_lo) = (u128::from(d[dn - 1]), u128::from(d[dn - 2]));
+  let base = 1_u128 << 64;
+
+  for j in (0..=nn - dn).rev() {
+    let top = (u128::from(u[j + dn]) << 64) | u128::from(u[j + dn - 1]);
+    let (mut qhat, mut rhat) = (top / d_hi, top % d_hi);
+    while qhat >= base || qhat * d_lo > ((rhat << 64) | u128::from(u[j + dn - 2])) {
+      qhat -= 1;
+      rhat += d_hi;
+      if rhat >= base {
+        break;
+      }
+    }
+
+    // u[j..=j + dn] -= qhat * d
+    let (mut carry, mut borrow) = (0_u128, false);
+    for (i, &d_i) in d.iter().enumerate() {
+      let p = qhat * u128::from(d_i) + carry;
+      carry = p >> 64;
+      let (t, b1) = u[i + j].overflowing_sub(p as u64);
+      let (t, b2) = t.overflowing_sub(u64::from(borrow));
+      u[i + j] = t;
+      borrow = b1 || b2;
+    }
+    let (t, b1) = u[j + dn].overflowing_sub(carry as u64);
+    let (t, b2) = t.overflowing_sub(u64::from(borrow));
+    u[j + dn] = t;
+
+    // The estimate was one too large; add `d` back.
+    if b1 || b2 {
+      qhat -= 1;
+      let mut carry = 0_u128;
+      for (i, &d_i) in d.iter().enumerate() {
+        let s = u128::from(u[i + j]) + u128::from(d_i) + carry;
+        u[i + j] = s as u64;
+        carry = s >> 64;
+      }
+      u[j + dn] = u[j + dn].wrapping_add(carry as u64);
+    }
+    q[j] = qhat as u64;
+  }
+
+  let mut r = u[..dn].to_vec();
+  if shift > 0 {
+    for i in 0..dn {
+      let hi = if i + 1 < dn { r[i + 1] } else { 0 };
+      r[i] = (r[i] >> shift) | (hi << (64 - shift));
+    }
+  }
+  (q, r)
+}
+
+/// Returns `x << shift` (`shift < 64`) in `len` limbs.
+fn shl_limbs(x: &[u64], shift: u32, len: usize) -> Vec<u64> {
+  let mut out = vec![0; len];
+  for (i, &limb) in x.iter().enumerate() {
+    let wide = u128::from(limb) << shift;
+    out[i] |= wide as u64;
+    if i + 1 < len {
+      out[i + 1] |= (wide >> 64) as u64;
+    }
+  }
+  out
+}
+
+#[cfg(all(test, feature = "gmp"))]
+mod tests {
+  use super::*;
+  use gmp_mpfr_sys::gmp;
+
+  /// Deterministic pseudo-random limbs (xorshift64).
+  fn limbs(seed: u64, n: usize) -> Vec<u64> {
+    let mut x = seed | 1;
+    (0..n)
+      .map(|_| {
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        x
+      })
+      .collect()
+  }
+
+  #[test]
+  fn test_arithmetic_matches_gmp() {
+    for seed in 1..20 {
+      let (a, b) = (limbs(seed, 8), limbs(seed + 100, 8));
+      let (mut r, mut r_) = (vec![0; 16], vec![0; 16]);
+      unsafe {
+        assert_eq!(
+          mpn_add_n(r.as_mut_ptr(), a.as_ptr(), b.as_ptr(), 8),
+          gmp::mpn_add_n(r_.as_mut_ptr(), a.as_ptr(), b.as_ptr(), 8)
+        );
+        assert_eq!(r, r_);
+        assert_eq!(
+          mpn_sub_n(r.as_mut_ptr(), a.as_ptr(), b.as_ptr(), 8),
+          gmp::mpn_sub_n(r_.as_mut_ptr(), a.as_ptr(), b.as_ptr(), 8)
+        );
+        assert_eq!(r, r_);
+        mpn_mul_n(r.as_mut_ptr(), a.as_ptr(), b.as_ptr(), 8);
+        gmp::mpn_mul_n(r_.as_mut_ptr(), a.as_ptr(), b.as_ptr(), 8);
+        assert_eq!(r, r_);
+        assert_eq!(
+          mpn_addmul_1(r.as_mut_ptr(), a.as_ptr(), 8, b[0]),
+          gmp::mpn_addmul_1(r_.as_mut_ptr(), a.as_ptr(), 8, b[0])
+        );
+        assert_eq!(r, r_);
+        assert_eq!(
+          mpn_cmp(a.as_ptr(), b.as_ptr(), 8),
+          gmp::mpn_cmp(a.as_ptr(), b.as_ptr(), 8).signum()
+        );
+        let count = (seed % 63 + 1) as u32;
+        assert_eq!(
+          mpn_lshift(r.as_mut_ptr(), a.as_ptr(), 8, count),
+          gmp::mpn_lshift(r_.as_mut_ptr(), a.as_ptr(), 8, count)
+        );
+        assert_eq!(r, r_);
+        assert_eq!(
+          mpn_rshift(r.as_mut_ptr(), a.as_ptr(), 8, count),
+          gmp::mpn_rshift(r_.as_mut_ptr(), a.as_ptr(), 8, count)
+        );
+        assert_eq!(r, r_);
+      }
+    }
+  }
+
+  #[test]
+  fn test_division_matches_gmp() {
+    for seed in 1..50 {
+      for &(nn, dn) in &[(8, 1), (8, 3), (8, 8), (5, 4)] {
+        let n = limbs(seed, nn);
+        let mut d = limbs(seed * 7, dn);
+        // Small top limbs exercise normalization, and `d = n` the exact-quotient case.
+        d[dn - 1] >>= seed % 64;
+        d[dn - 1] |= 1;
+        let (mut q, mut r, mut q_, mut r_) = (vec![0; nn], vec![0; dn], vec![0; nn], vec![0; dn]);
+        unsafe {
+          mpn_tdiv_qr(
+            q.as_mut_ptr(),
+            r.as_mut_ptr(),
+            0,
+            n.as_ptr(),
+            nn as i64,
+            d.as_ptr(),
+            dn as i64,
+          );
+          gmp::mpn_tdiv_qr(
+            q_.as_mut_ptr(),
+            r_.as_mut_ptr(),
+            0,
+            n.as_ptr(),
+            nn as i64,
+            d.as_ptr(),
+            dn as i64,
+          );
+        }
+        assert_eq!((q, r), (q_, r_));
+      }
+    }
+  }
+
+  #[test]
+  fn test_get_str_matches_gmp() {
+    let x = limbs(3, 4);
+    let (mut s, mut s_) = (vec![0; 257], vec![0; 257]);
+    let (mut y, mut y_) = (x.clone(), x);
+    unsafe {
+      let len = mpn_get_str(s.as_mut_ptr(), 2, y.as_mut_ptr(), 4);
+      let len_ = gmp::mpn_get_str(s_.as_mut_ptr(), 2, y_.as_mut_ptr(), 4);
+      assert_eq!(s[..len], s_[..len_]);
+    }
+  }
+}
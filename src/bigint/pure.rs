@@ -0,0 +1,895 @@
+//! Pure-Rust `Integer`, a thin wrapper around `num_bigint::BigInt` that mirrors the parts of
+//! `rug::Integer`'s API used by this library.
+//!
+//! Where GMP pins down a result that `num-bigint` leaves open (e.g. the cofactors returned by
+//! `gcd_cofactors_ref`, or the bytes fed to `Hash`), we follow GMP exactly. Methods that `rug`
+//! exposes as incomplete computations (the `*_ref` methods) compute eagerly here; those returning
+//! several values wrap them in a type convertible to a tuple, so that the usual
+//! `<(Integer, Integer)>::from(x.div_rem_floor_ref(y))` idiom works with either backend.
+use num_bigint::{BigInt, BigUint, ParseBigIntError, Sign};
+use num_integer::Integer as _;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::{Product, Sum};
+use std::ops::{
+  Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, RemAssign, Shl, ShlAssign, Shr,
+  ShrAssign, Sub, SubAssign,
+};
+use std::str::FromStr;
+
+/// An arbitrary-precision integer.
+#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Integer(BigInt);
+
+/// Digit and byte orders for `Integer::from_digits` and friends. Digits are ordered by
+/// significance (`Lsf` or `Msf`), and the bytes within each digit are little-endian (`Le`),
+/// big-endian (`Be`) or native.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Order {
+  /// Least-significant digit first, native byte order.
+  Lsf,
+  /// Least-significant digit first, little-endian bytes.
+  LsfLe,
+  /// Least-significant digit first, big-endian bytes.
+  LsfBe,
+  /// Most-significant digit first, native byte order.
+  Msf,
+  /// Most-significant digit first, little-endian bytes.
+  MsfLe,
+  /// Most-significant digit first, big-endian bytes.
+  MsfBe,
+}
+
+impl Order {
+  fn is_msf(self) -> bool {
+    match self {
+      Order::Msf | Order::MsfLe | Order::MsfBe => true,
+      Order::Lsf | Order::LsfLe | Order::LsfBe => false,
+    }
+  }
+
+  /// Converts between a digit's value and its in-memory representation under this order. (The
+  /// conversion is its own inverse.)
+  fn to_native<T: Digit>(self, digit: T) -> T {
+    let swap = match self {
+      Order::Lsf | Order::Msf => false,
+      Order::LsfLe | Order::MsfLe => cfg!(target_endian = "big"),
+      Order::LsfBe | Order::MsfBe => cfg!(target_endian = "little"),
+    };
+    if swap {
+      digit.swap_bytes()
+    } else {
+      digit
+    }
+  }
+}
+
+/// Unsigned primitives usable as digits in `Integer::from_digits` and friends.
+pub trait Digit: Copy + Default {
+  /// Width of the digit in bytes.
+  const BYTES: usize;
+  /// Reads a digit from `BYTES` little-endian bytes.
+  fn from_le_slice(bytes: &[u8]) -> Self;
+  /// Writes the digit as `BYTES` little-endian bytes.
+  fn write_le_slice(self, bytes: &mut [u8]);
+  /// Reverses the byte order of the digit.
+  fn swap_bytes(self) -> Self;
+}
+
+macro_rules! digits {
+  ($($t:ty),*) => {
+    $(
+      impl Digit for $t {
+        const BYTES: usize = std::mem::size_of::<$t>();
+        fn from_le_slice(bytes: &[u8]) -> Self {
+          let mut buf = [0; std::mem::size_of::<$t>()];
+          buf.copy_from_slice(bytes);
+          Self::from_le_bytes(buf)
+        }
+        fn write_le_slice(self, bytes: &mut [u8]) {
+          bytes.copy_from_slice(&self.to_le_bytes());
+        }
+        fn swap_bytes(self) -> Self {
+          <$t>::swap_bytes(self)
+        }
+      }
+    )*
+  };
+}
+
+digits!(u8, u16, u32, u64, u128);
+
+/// The result of a division returning a quotient and remainder. Convert to `(Integer, Integer)`.
+pub struct DivRemIncomplete(Integer, Integer);
+
+impl From<DivRemIncomplete> for (Integer, Integer) {
+  fn from(x: DivRemIncomplete) -> Self {
+    (x.0, x.1)
+  }
+}
+
+/// The result of `Integer::gcd_cofactors_ref`. Convert to `(Integer, Integer, Integer)`.
+pub struct GcdIncomplete(Integer, Integer, Integer);
+
+impl From<GcdIncomplete> for (Integer, Integer, Integer) {
+  fn from(x: GcdIncomplete) -> Self {
+    (x.0, x.1, x.2)
+  }
+}
+
+/// Trait for assigning to an existing value, as in `rug::Assign`.
+pub trait Assign<Src = Self> {
+  /// Assigns `src` to `self`.
+  fn assign(&mut self, src: Src);
+}
+
+/// Trait for exponentiation, as in `rug::ops::Pow`.
+pub trait Pow<Rhs> {
+  /// The result of the exponentiation.
+  type Output;
+  /// Returns `self ^ rhs`.
+  fn pow(self, rhs: Rhs) -> Self::Output;
+}
+
+impl Integer {
+  /// Returns zero.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Parses a decimal integer, with an optional leading sign.
+  pub fn parse<S: AsRef<str>>(src: S) -> Result<Self, ParseBigIntError> {
+    Self::from_str(src.as_ref())
+  }
+
+  /// Returns the integer whose absolute value has the given `digits`, in the given `order`.
+  pub fn from_digits<T: Digit>(digits: &[T], order: Order) -> Self {
+    let mut bytes = vec![0; digits.len() * T::BYTES];
+    for (i, chunk) in bytes.chunks_mut(T::BYTES).enumerate() {
+      let digit = if order.is_msf() {
+        digits[digits.len() - 1 - i]
+      } else {
+        digits[i]
+      };
+      order.to_native(digit).write_le_slice(chunk);
+    }
+    Self(BigInt::from_bytes_le(Sign::Plus, &bytes))
+  }
+
+  /// Returns the digits of the absolute value of `self`, with no leading zeros.
+  pub fn to_digits<T: Digit>(&self, order: Order) -> Vec<T> {
+    let (_, bytes) = self.0.to_bytes_le();
+    let len = if self.0.is_zero() {
+      0
+    } else {
+      bytes.len().div_ceil(T::BYTES)
+    };
+    let mut digits = vec![T::default(); len];
+    self.write_digits(&mut digits, order);
+    digits
+  }
+
+  /// Writes the digits of the absolute value of `self` into `digits`, truncating or zero-padding
+  /// as needed.
+  pub fn write_digits<T: Digit>(&self, digits: &mut [T], order: Order) {
+    let (_, mut bytes) = self.0.to_bytes_le();
+    bytes.resize(digits.len() * T::BYTES, 0);
+    let len = digits.len();
+    for (i, chunk) in bytes.chunks(T::BYTES).enumerate() {
+      let digit = order.to_native(T::from_le_slice(chunk));
+      if order.is_msf() {
+        digits[len - 1 - i] = digit;
+      } else {
+        digits[i] = digit;
+      }
+    }
+  }
+
+  /// Returns the number of bits in the absolute value of `self`, or zero if `self` is zero.
+  #[allow(clippy::cast_possible_truncation)]
+  pub fn significant_bits(&self) -> u32 {
+    self.0.bits() as u32
+  }
+
+  /// Returns bit `index` of the two's complement representation of `self`.
+  pub fn get_bit(&self, index: u32) -> bool {
+    self.0.bit(u64::from(index))
+  }
+
+  /// Returns the low `n` bits of the two's complement representation of `self`.
+  pub fn keep_bits_ref(&self, n: u32) -> Self {
+    Self(self.0.mod_floor(&(BigInt::one() << n)))
+  }
+
+  /// Returns `true` if `self` is odd.
+  pub fn is_odd(&self) -> bool {
+    self.0.is_odd()
+  }
+
+  /// Returns `true` if `self` is even.
+  pub fn is_even(&self) -> bool {
+    self.0.is_even()
+  }
+
+  /// Returns `-1`, `0` or `1` according to the sign of `self`.
+  pub fn signum(self) -> Self {
+    Self(self.0.signum())
+  }
+
+  /// Returns the absolute value of `self`.
+  pub fn abs(self) -> Self {
+    Self(self.0.abs())
+  }
+
+  /// Returns the absolute value of `self`.
+  pub fn abs_ref(&self) -> Self {
+    Self(self.0.abs())
+  }
+
+  /// Returns the absolute value of `self`. Unlike `rug`, this copies.
+  pub fn as_abs(&self) -> Self {
+    self.abs_ref()
+  }
+
+  /// Returns `self * self`.
+  pub fn square(self) -> Self {
+    self.square_ref()
+  }
+
+  /// Returns `self * self`.
+  pub fn square_ref(&self) -> Self {
+    Self(&self.0 * &self.0)
+  }
+
+  /// Returns the nonnegative greatest common divisor of `self` and `other`.
+  pub fn gcd(self, other: &Self) -> Self {
+    self.gcd_ref(other)
+  }
+
+  /// Returns the nonnegative greatest common divisor of `self` and `other`.
+  pub fn gcd_ref(&self, other: &Self) -> Self {
+    Self(self.0.gcd(&other.0))
+  }
+
+  /// Returns `(g, s, t)` with `g = gcd(self, other) = s * self + t * other`, normalized like GMP's
+  /// `mpz_gcdext`: `|s| < |other| / 2g` and `|t| < |self| / 2g`, outside of a few edge cases.
+  pub fn gcd_cofactors_ref(&self, other: &Self) -> GcdIncomplete {
+    let (g, s, t) = self.gcd_cofactors_(other);
+    GcdIncomplete(g, s, t)
+  }
+
+  fn gcd_cofactors_(&self, other: &Self) -> (Self, Self, Self) {
+    let (a, b) = (&self.0, &other.0);
+    let sgn = |x: &BigInt| x.signum();
+    if a.abs() == b.abs() {
+      return (Self(a.abs()), Self(BigInt::zero()), Self(sgn(b)));
+    }
+    if b.is_zero() {
+      return (Self(a.abs()), Self(sgn(a)), Self(BigInt::zero()));
+    }
+    if a.is_zero() {
+      return (Self(b.abs()), Self(BigInt::zero()), Self(sgn(b)));
+    }
+
+    let egcd = a.extended_gcd(b);
+    let (g, s) = if egcd.gcd.is_negative() {
+      (-egcd.gcd, -egcd.x)
+    } else {
+      (egcd.gcd, egcd.x)
+    };
+    let two_g = &g * 2;
+    let s = if b.abs() == two_g {
+      sgn(a)
+    } else if a.abs() == two_g {
+      // Then `t = sgn(b)` and `s` follows from `s * a + t * b = g`.
+      (&g - b.abs()) / a
+    } else {
+      // `s` is determined mod `|b| / g`; take the representative closest to zero.
+      let period = b.abs() / &g;
+      let s = s.mod_floor(&period);
+      if &s * 2 > period {
+        s - period
+      } else {
+        s
+      }
+    };
+    let t = (&g - &s * a) / b;
+    (Self(g), Self(s), Self(t))
+  }
+
+  /// Returns the quotient and remainder of truncating division.
+  pub fn div_rem(self, divisor: Self) -> (Self, Self) {
+    let (q, r) = self.0.div_rem(&divisor.0);
+    (Self(q), Self(r))
+  }
+
+  /// Returns the quotient and remainder of floor division.
+  pub fn div_rem_floor(self, divisor: Self) -> (Self, Self) {
+    let (q, r) = self.0.div_mod_floor(&divisor.0);
+    (Self(q), Self(r))
+  }
+
+  /// Returns the quotient and remainder of floor division.
+  pub fn div_rem_floor_ref(&self, divisor: &Self) -> DivRemIncomplete {
+    let (q, r) = self.0.div_mod_floor(&divisor.0);
+    DivRemIncomplete(Self(q), Self(r))
+  }
+
+  /// Returns the quotient and remainder of Euclidean division, whose remainder is nonnegative.
+  pub fn div_rem_euc(self, divisor: Self) -> (Self, Self) {
+    self.div_rem_euc_(&divisor)
+  }
+
+  /// Returns the quotient and remainder of Euclidean division, whose remainder is nonnegative.
+  pub fn div_rem_euc_ref(&self, divisor: &Self) -> DivRemIncomplete {
+    let (q, r) = self.div_rem_euc_(divisor);
+    DivRemIncomplete(q, r)
+  }
+
+  fn div_rem_euc_(&self, divisor: &Self) -> (Self, Self) {
+    let (mut q, mut r) = self.0.div_rem(&divisor.0);
+    if r.is_negative() {
+      if divisor.0.is_positive() {
+        q -= 1;
+        r += &divisor.0;
+      } else {
+        q += 1;
+        r -= &divisor.0;
+      }
+    }
+    (Self(q), Self(r))
+  }
+
+  /// Returns the inverse of `self` in `[0, |modulo|)`, or `Err(self)` if there is none.
+  pub fn invert(self, modulo: &Self) -> Result<Self, Self> {
+    match self.invert_ref(modulo) {
+      Some(inv) => Ok(inv),
+      None => Err(self),
+    }
+  }
+
+  /// Returns the inverse of `self` in `[0, |modulo|)`, or `None` if there is none.
+  pub fn invert_ref(&self, modulo: &Self) -> Option<Self> {
+    let m = modulo.0.abs();
+    if m.is_one() {
+      return Some(Self::new());
+    }
+    self.0.modinv(&m).map(Self)
+  }
+
+  /// Returns `self ^ exponent mod |modulo|` in `[0, |modulo|)`, or `Err(self)` if `exponent` is
+  /// negative and `self` has no inverse.
+  pub fn pow_mod(self, exponent: &Self, modulo: &Self) -> Result<Self, Self> {
+    match self.pow_mod_ref(exponent, modulo) {
+      Some(x) => Ok(x),
+      None => Err(self),
+    }
+  }
+
+  /// Returns `self ^ exponent mod |modulo|` in `[0, |modulo|)`, or `None` if `exponent` is
+  /// negative and `self` has no inverse.
+  pub fn pow_mod_ref(&self, exponent: &Self, modulo: &Self) -> Option<Self> {
+    let m = modulo.0.abs();
+    let base = if exponent.0.is_negative() {
+      self.invert_ref(modulo)?.0
+    } else {
+      self.0.mod_floor(&m)
+    };
+    Some(Self(base.modpow(&exponent.0.abs(), &m)))
+  }
+
+  /// Returns the Jacobi symbol `(self / n)`. Panics if `n` is not odd and positive.
+  pub fn jacobi(&self, n: &Self) -> i32 {
+    assert!(n.0.is_positive() && n.0.is_odd());
+    let mut a = self.0.mod_floor(&n.0);
+    let mut n = n.0.clone();
+    let mut result = 1;
+    while !a.is_zero() {
+      let twos = a.trailing_zeros().unwrap_or(0);
+      a >>= twos;
+      let n_mod_8 = (&n % 8_u32).to_u32().unwrap();
+      if twos % 2 == 1 && (n_mod_8 == 3 || n_mod_8 == 5) {
+        result = -result;
+      }
+      std::mem::swap(&mut a, &mut n);
+      if (&a % 4_u32) == BigInt::from(3) && (&n % 4_u32) == BigInt::from(3) {
+        result = -result;
+      }
+      a = a.mod_floor(&n);
+    }
+    if n.is_one() {
+      result
+    } else {
+      0
+    }
+  }
+
+  /// Returns `true` if `self` is a perfect square.
+  pub fn is_perfect_square(&self) -> bool {
+    !self.0.is_negative() && {
+      let root = self.0.sqrt();
+      &root * &root == self.0
+    }
+  }
+
+  /// Returns `true` if `self = c mod |divisor|`.
+  pub fn is_congruent(&self, c: &Self, divisor: &Self) -> bool {
+    if divisor.0.is_zero() {
+      self == c
+    } else {
+      (&self.0 - &c.0).is_multiple_of(&divisor.0)
+    }
+  }
+
+  /// Returns `true` if `self` is divisible by `divisor`.
+  pub fn is_divisible(&self, divisor: &Self) -> bool {
+    self.is_congruent(&Self::new(), divisor)
+  }
+
+  /// Returns `self` as a `u64`, if it fits.
+  pub fn to_u64(&self) -> Option<u64> {
+    self.0.to_u64()
+  }
+
+  /// Returns `self` as an `i64`, if it fits.
+  pub fn to_i64(&self) -> Option<i64> {
+    self.0.to_i64()
+  }
+
+  /// Returns `self` as a `u32`, if it fits.
+  pub fn to_u32(&self) -> Option<u32> {
+    self.0.to_u32()
+  }
+
+  /// Returns `self` as an `i32`, if it fits.
+  pub fn to_i32(&self) -> Option<i32> {
+    self.0.to_i32()
+  }
+
+  /// Returns `self` as a `usize`, if it fits.
+  pub fn to_usize(&self) -> Option<usize> {
+    self.0.to_usize()
+  }
+}
+
+/// Hashes exactly like `rug::Integer`: the signed limb count as a `c_int`, then the limbs.
+impl Hash for Integer {
+  #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+  fn hash<H: Hasher>(&self, state: &mut H) {
+    let (sign, limbs) = self.0.to_u64_digits();
+    let size = limbs.len() as i32;
+    let size = if sign == Sign::Minus { -size } else { size };
+    size.hash(state);
+    limbs[..].hash(state);
+  }
+}
+
+impl fmt::Display for Integer {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    fmt::Display::fmt(&self.0, f)
+  }
+}
+
+impl fmt::Debug for Integer {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    fmt::Display::fmt(&self.0, f)
+  }
+}
+
+impl FromStr for Integer {
+  type Err = ParseBigIntError;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let s = s.strip_prefix('+').unwrap_or(s);
+    BigInt::from_str(s).map(Self)
+  }
+}
+
+impl From<&Integer> for Integer {
+  fn from(x: &Integer) -> Self {
+    x.clone()
+  }
+}
+
+impl From<BigInt> for Integer {
+  fn from(x: BigInt) -> Self {
+    Self(x)
+  }
+}
+
+impl From<BigUint> for Integer {
+  fn from(x: BigUint) -> Self {
+    Self(BigInt::from(x))
+  }
+}
+
+impl From<Integer> for BigInt {
+  fn from(x: Integer) -> Self {
+    x.0
+  }
+}
+
+impl Assign for Integer {
+  fn assign(&mut self, src: Self) {
+    *self = src;
+  }
+}
+
+impl Assign<&Integer> for Integer {
+  fn assign(&mut self, src: &Self) {
+    self.0.clone_from(&src.0);
+  }
+}
+
+impl Pow<u32> for Integer {
+  type Output = Self;
+  fn pow(self, exp: u32) -> Self {
+    Self(num_traits::Pow::pow(self.0, exp))
+  }
+}
+
+impl Pow<u32> for &Integer {
+  type Output = Integer;
+  fn pow(self, exp: u32) -> Integer {
+    Integer(num_traits::Pow::pow(&self.0, exp))
+  }
+}
+
+impl Neg for Integer {
+  type Output = Self;
+  fn neg(self) -> Self {
+    Self(-self.0)
+  }
+}
+
+impl Neg for &Integer {
+  type Output = Integer;
+  fn neg(self) -> Integer {
+    Integer(-&self.0)
+  }
+}
+
+impl Product for Integer {
+  fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+    Self(iter.map(|x| x.0).product())
+  }
+}
+
+impl<'a> Product<&'a Integer> for Integer {
+  fn product<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+    Self(iter.map(|x| &x.0).product())
+  }
+}
+
+impl Sum for Integer {
+  fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+    Self(iter.map(|x| x.0).sum())
+  }
+}
+
+impl<'a> Sum<&'a Integer> for Integer {
+  fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+    Self(iter.map(|x| &x.0).sum())
+  }
+}
+
+macro_rules! arith {
+  ($($op_trait:ident $op:ident $assign_trait:ident $assign:ident),*) => {
+    $(
+      impl $op_trait for Integer {
+        type Output = Self;
+        fn $op(self, rhs: Self) -> Self {
+          Self(self.0.$op(rhs.0))
+        }
+      }
+
+      impl $op_trait<&Integer> for Integer {
+        type Output = Self;
+        fn $op(self, rhs: &Self) -> Self {
+          Self(self.0.$op(&rhs.0))
+        }
+      }
+
+      impl $op_trait<Integer> for &Integer {
+        type Output = Integer;
+        fn $op(self, rhs: Integer) -> Integer {
+          Integer((&self.0).$op(rhs.0))
+        }
+      }
+
+      impl $op_trait<&Integer> for &Integer {
+        type Output = Integer;
+        fn $op(self, rhs: &Integer) -> Integer {
+          Integer((&self.0).$op(&rhs.0))
+        }
+      }
+
+      impl $assign_trait for Integer {
+        fn $assign(&mut self, rhs: Self) {
+          self.0.$assign(rhs.0);
+        }
+      }
+
+      impl $assign_trait<&Integer> for Integer {
+        fn $assign(&mut self, rhs: &Self) {
+          self.0.$assign(&rhs.0);
+        }
+      }
+
+      arith_primitive!($op_trait $op $assign_trait $assign; i32 i64 u32 u64 usize);
+    )*
+  };
+}
+
+macro_rules! arith_primitive {
+  ($op_trait:ident $op:ident $assign_trait:ident $assign:ident; $($t:ty)*) => {
+    $(
+      impl $op_trait<$t> for Integer {
+        type Output = Self;
+        fn $op(self, rhs: $t) -> Self {
+          Self(self.0.$op(rhs))
+        }
+      }
+
+      impl $op_trait<$t> for &Integer {
+        type Output = Integer;
+        fn $op(self, rhs: $t) -> Integer {
+          Integer((&self.0).$op(rhs))
+        }
+      }
+
+      impl $op_trait<Integer> for $t {
+        type Output = Integer;
+        fn $op(self, rhs: Integer) -> Integer {
+          Integer(self.$op(rhs.0))
+        }
+      }
+
+      impl $op_trait<&Integer> for $t {
+        type Output = Integer;
+        fn $op(self, rhs: &Integer) -> Integer {
+          Integer(self.$op(&rhs.0))
+        }
+      }
+
+      impl $assign_trait<$t> for Integer {
+        fn $assign(&mut self, rhs: $t) {
+          self.0.$assign(rhs);
+        }
+      }
+    )*
+  };
+}
+
+arith!(
+  Add add AddAssign add_assign,
+  Sub sub SubAssign sub_assign,
+  Mul mul MulAssign mul_assign,
+  Div div DivAssign div_assign,
+  Rem rem RemAssign rem_assign
+);
+
+// Right shifts round toward negative infinity, as in `rug`. Negative shift amounts shift the
+// other way.
+macro_rules! shifts {
+  ($($t:ty => |$n:ident| $left:expr, $right:expr);*) => {
+    $(
+      impl Shl<$t> for Integer {
+        type Output = Self;
+        fn shl(mut self, n: $t) -> Self {
+          self <<= n;
+          self
+        }
+      }
+
+      impl Shl<$t> for &Integer {
+        type Output = Integer;
+        fn shl(self, n: $t) -> Integer {
+          self.clone() << n
+        }
+      }
+
+      impl Shr<$t> for Integer {
+        type Output = Self;
+        fn shr(mut self, n: $t) -> Self {
+          self >>= n;
+          self
+        }
+      }
+
+      impl Shr<$t> for &Integer {
+        type Output = Integer;
+        fn shr(self, n: $t) -> Integer {
+          self.clone() >> n
+        }
+      }
+
+      impl ShlAssign<$t> for Integer {
+        fn shl_assign(&mut self, $n: $t) {
+          let (left, right): (bool, u64) = ($left, $right);
+          if left {
+            self.0 <<= right;
+          } else {
+            self.0 >>= right;
+          }
+        }
+      }
+
+      impl ShrAssign<$t> for Integer {
+        fn shr_assign(&mut self, $n: $t) {
+          let (left, right): (bool, u64) = ($left, $right);
+          if left {
+            self.0 >>= right;
+          } else {
+            self.0 <<= right;
+          }
+        }
+      }
+    )*
+  };
+}
+
+shifts!(
+  i32 => |n| n >= 0, u64::from(n.unsigned_abs());
+  u32 => |n| true, u64::from(n);
+  usize => |n| true, n as u64
+);
+
+macro_rules! primitives {
+  ($($t:ty),*) => {
+    $(
+      impl From<$t> for Integer {
+        fn from(x: $t) -> Self {
+          Self(BigInt::from(x))
+        }
+      }
+
+      impl Assign<$t> for Integer {
+        fn assign(&mut self, src: $t) {
+          self.0 = BigInt::from(src);
+        }
+      }
+
+      impl PartialEq<$t> for Integer {
+        fn eq(&self, other: &$t) -> bool {
+          self.0 == BigInt::from(*other)
+        }
+      }
+
+      impl PartialEq<Integer> for $t {
+        fn eq(&self, other: &Integer) -> bool {
+          BigInt::from(*self) == other.0
+        }
+      }
+
+      impl PartialOrd<$t> for Integer {
+        fn partial_cmp(&self, other: &$t) -> Option<Ordering> {
+          self.0.partial_cmp(&BigInt::from(*other))
+        }
+      }
+
+      impl PartialOrd<Integer> for $t {
+        fn partial_cmp(&self, other: &Integer) -> Option<Ordering> {
+          BigInt::from(*self).partial_cmp(&other.0)
+        }
+      }
+    )*
+  };
+}
+
+primitives!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+#[cfg(all(test, feature = "gmp"))]
+mod tests {
+  use super::*;
+  use std::collections::hash_map::DefaultHasher;
+
+  fn values() -> Vec<Integer> {
+    let big = "8379318854759393542919624658740924312470716298768497305640897230547324230\
+               2174980623485943211853497834215734012983645908234651";
+    let mut values = ["0", "1", "2", "3", "7", "12", "18446744073709551616", big]
+      .iter()
+      .map(|s| Integer::from_str(s).unwrap())
+      .collect::<Vec<_>>();
+    values.push(values[7].clone() * &values[6] + 12_345);
+    let negated = values.iter().map(|x| -x).collect::<Vec<_>>();
+    values.extend(negated);
+    values
+  }
+
+  fn gmp(x: &Integer) -> rug::Integer {
+    rug::Integer::from_str(&x.to_string()).unwrap()
+  }
+
+  fn same(x: &Integer, y: &rug::Integer) -> bool {
+    x.to_string() == y.to_string()
+  }
+
+  fn hash_of<T: Hash>(t: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    t.hash(&mut hasher);
+    hasher.finish()
+  }
+
+  #[test]
+  #[allow(deprecated)]
+  fn test_matches_gmp() {
+    for a in &values() {
+      let a_ = gmp(a);
+      assert_eq!(hash_of(a), hash_of(&a_));
+      assert_eq!(a.significant_bits(), a_.significant_bits());
+      assert_eq!(
+        a.to_digits::<u64>(Order::Lsf),
+        a_.to_digits::<u64>(rug::integer::Order::Lsf)
+      );
+      assert_eq!(
+        a.to_digits::<u8>(Order::Msf),
+        a_.to_digits::<u8>(rug::integer::Order::Msf)
+      );
+      assert!(same(&(a >> 3), &rug::Integer::from(&a_ >> 3)));
+      assert!(same(
+        &a.keep_bits_ref(70),
+        &rug::Integer::from(a_.keep_bits_ref(70))
+      ));
+      for i in 0..80 {
+        assert_eq!(a.get_bit(i), a_.get_bit(i));
+      }
+
+      for b in &values() {
+        let b_ = gmp(b);
+        assert!(same(&(a + b), &rug::Integer::from(&a_ + &b_)));
+        assert!(same(&(a - b), &rug::Integer::from(&a_ - &b_)));
+        assert!(same(&(a * b), &rug::Integer::from(&a_ * &b_)));
+        let (g, s, t) = <(Integer, Integer, Integer)>::from(a.gcd_cofactors_ref(b));
+        let (g_, s_, t_) =
+          <(rug::Integer, rug::Integer, rug::Integer)>::from(a_.gcd_cofactors_ref(&b_));
+        assert!(
+          same(&g, &g_) && same(&s, &s_) && same(&t, &t_),
+          "{} {}",
+          a,
+          b
+        );
+        if *b == 0 {
+          continue;
+        }
+        assert!(same(&(a / b), &rug::Integer::from(&a_ / &b_)));
+        assert!(same(&(a % b), &rug::Integer::from(&a_ % &b_)));
+        let (q, r) = <(Integer, Integer)>::from(a.div_rem_floor_ref(b));
+        let (q_, r_) = <(rug::Integer, rug::Integer)>::from(a_.div_rem_floor_ref(&b_));
+        assert!(same(&q, &q_) && same(&r, &r_));
+        let (q, r) = <(Integer, Integer)>::from(a.div_rem_euc_ref(b));
+        let (q_, r_) = <(rug::Integer, rug::Integer)>::from(a_.div_rem_euc_ref(&b_));
+        assert!(same(&q, &q_) && same(&r, &r_));
+        let inv = a.invert_ref(b);
+        let inv_ = a_.invert_ref(&b_).map(rug::Integer::from);
+        assert_eq!(inv.map(|x| x.to_string()), inv_.map(|x| x.to_string()));
+        if *b > 1 {
+          let e = Integer::from(65_537);
+          let x = a.pow_mod_ref(&e, b).unwrap();
+          let x_ = rug::Integer::from(a_.pow_mod_ref(&gmp(&e), &b_).unwrap());
+          assert!(same(&x, &x_));
+        }
+        if *b > 0 && b.is_odd() {
+          assert_eq!(a.jacobi(b), a_.jacobi(&b_));
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn test_digit_orders() {
+    let bytes = [1_u8, 2, 3, 4, 5, 6, 7, 8, 9];
+    let x = Integer::from_digits(&bytes, Order::Msf);
+    let x_ = rug::Integer::from_digits(&bytes, rug::integer::Order::Msf);
+    assert!(same(&x, &x_));
+    let mut words = [0_u32; 4];
+    let mut words_ = [0_u32; 4];
+    x.write_digits(&mut words, Order::LsfBe);
+    x_.write_digits(&mut words_, rug::integer::Order::LsfBe);
+    assert_eq!(words, words_);
+    x.write_digits(&mut words, Order::MsfLe);
+    x_.write_digits(&mut words_, rug::integer::Order::MsfLe);
+    assert_eq!(words, words_);
+  }
+}
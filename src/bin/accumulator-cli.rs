@@ -0,0 +1,297 @@
+//! Command-line tool for creating, updating and proving against accumulators of strings.
+//!
+//! ```text
+//! accumulator-cli create <rsa2048|class> <state>
+//! accumulator-cli show <state>
+//! accumulator-cli add <state> <elems>
+//! accumulator-cli witness <state> <elems> <witnesses>
+//! accumulator-cli delete <state> <witnesses>
+//! accumulator-cli prove-membership <state> <elems> <proof>
+//! accumulator-cli prove-nonmembership <state> <elems> <proof>
+//! accumulator-cli verify <state> <elems> <proof>
+//! accumulator-cli hash-to-prime <elems>
+//! ```
+//!
+//! Element files are UTF-8 text with one element per line, taken verbatim. Blank lines are skipped.
+//! Every element is accumulated as a `String`. `verify` exits with status 1 if the proof is
+//! invalid, and every command exits with status 2 on error.
+//!
+//! State, witness and proof files are binary, in the `Persist` encoding of the `store` module:
+//! `u64`s are 8 bytes little-endian, strings are a `u64` length followed by their bytes, integers
+//! are a sign byte followed by their magnitude as a byte string (least significant byte first) and
+//! group elements are an integer (RSA) or three integers `a, b, c` (class group). Lists are a `u64`
+//! length followed by their items. Each file starts with the magic bytes `ACLI`, then the group
+//! name (`rsa2048` or `class`) and the file kind as strings, then a payload depending on the kind:
+//!
+//! * `state` - The accumulator value, then the list of accumulated elements.
+//! * `witnesses` - A list of pairs of an element and its witness, which is an accumulator value.
+//! * `membership` - A batch membership proof: the witness, then the NI-PoE quotient.
+//! * `nonmembership` - A batch nonmembership proof: the elements `d`, `v` and `g * v^-1`, the
+//!   NI-PoKE2 proof `z, Q, r` and the NI-PoE quotient.
+use accumulator::group::{ClassGroup, Rsa2048, UnknownOrderGroup};
+use accumulator::hash::hash_to_prime;
+use accumulator::store::Persist;
+use accumulator::{Accumulator, MembershipProof, NonmembershipProof, Witness};
+use std::collections::HashSet;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::process;
+
+const MAGIC: &[u8] = b"ACLI";
+
+const USAGE: &str = "usage:
+  accumulator-cli create <rsa2048|class> <state>
+  accumulator-cli show <state>
+  accumulator-cli add <state> <elems>
+  accumulator-cli witness <state> <elems> <witnesses>
+  accumulator-cli delete <state> <witnesses>
+  accumulator-cli prove-membership <state> <elems> <proof>
+  accumulator-cli prove-nonmembership <state> <elems> <proof>
+  accumulator-cli verify <state> <elems> <proof>
+  accumulator-cli hash-to-prime <elems>";
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+fn main() {
+  let args: Vec<String> = env::args().skip(1).collect();
+  match run(&args.iter().map(String::as_str).collect::<Vec<_>>()) {
+    Ok(true) => (),
+    Ok(false) => process::exit(1),
+    Err(err) => {
+      eprintln!("error: {}", err);
+      process::exit(2);
+    }
+  }
+}
+
+// Runs a command, returning whether it succeeded (only `verify` can fail without an error).
+fn run(args: &[&str]) -> Result<bool> {
+  match *args {
+    ["create", group, state] => {
+      let mut out = vec![];
+      match group {
+        "rsa2048" => Accumulator::<Rsa2048, String>::empty().encode(&mut out),
+        "class" => Accumulator::<ClassGroup, String>::empty().encode(&mut out),
+        _ => return Err(format!("unknown group `{}`", group).into()),
+      }
+      encode_list::<String>(&[], &mut out);
+      write_file(state, group, "state", &out)?;
+      Ok(true)
+    }
+    ["hash-to-prime", elems] => {
+      for elem in read_elems(elems)? {
+        println!("{}", hash_to_prime(&elem));
+      }
+      Ok(true)
+    }
+    [command, state, ..] if command != "create" && command != "hash-to-prime" => {
+      let (group, state) = read_file(state, "state")?;
+      match group.as_str() {
+        "rsa2048" => run_with_state::<Rsa2048>(args, &group, &state),
+        "class" => run_with_state::<ClassGroup>(args, &group, &state),
+        _ => Err(format!("unknown group `{}`", group).into()),
+      }
+    }
+    _ => Err(USAGE.into()),
+  }
+}
+
+// Runs a command on the state file `args[1]`, whose group is `group` and payload is `state`.
+fn run_with_state<G: UnknownOrderGroup>(args: &[&str], group: &str, state: &[u8]) -> Result<bool>
+where
+  G::Elem: Persist,
+{
+  let (acc, set): (Accumulator<G, String>, Vec<String>) = decode_all(state, |bytes| {
+    Some((Accumulator::decode(bytes)?, decode_list(bytes)?))
+  })?;
+  match *args {
+    ["show", _] => {
+      let mut value = vec![];
+      acc.encode(&mut value);
+      println!("group: {}", group);
+      println!("elements: {}", set.len());
+      println!("value: {}", hex(&value));
+      Ok(true)
+    }
+    ["add", state, elems] => {
+      let elems = read_elems(elems)?;
+      let mut seen: HashSet<&String> = set.iter().collect();
+      if let Some(elem) = elems.iter().find(|elem| !seen.insert(elem)) {
+        return Err(format!("element `{}` is already accumulated", elem).into());
+      }
+      let acc = acc.add(&elems);
+      write_state(state, group, &acc, &[set, elems].concat())?;
+      Ok(true)
+    }
+    ["witness", _, elems, witnesses] => {
+      let elems = read_elems(elems)?;
+      let others = others(&set, &elems)?;
+      let witness = Witness(Accumulator::<G, String>::empty().add(&others));
+      let mut out = vec![];
+      encode_list(&witness.compute_individual_witnesses(&elems), &mut out);
+      write_file(witnesses, group, "witnesses", &out)?;
+      Ok(true)
+    }
+    ["delete", state, witnesses] => {
+      let elem_witnesses: Vec<(String, Witness<G, String>)> =
+        decode_all(&read_file_of(witnesses, group, "witnesses")?, decode_list)?;
+      let elems: Vec<String> = elem_witnesses
+        .iter()
+        .map(|(elem, _)| elem.clone())
+        .collect();
+      let others = others(&set, &elems)?;
+      let acc = acc.delete(&elem_witnesses)?;
+      write_state(state, group, &acc, &others)?;
+      Ok(true)
+    }
+    ["prove-membership", _, elems, proof] => {
+      let elems = read_elems(elems)?;
+      let others = others(&set, &elems)?;
+      let witness = Witness(Accumulator::empty().add(&others));
+      let mut out = vec![];
+      acc
+        .prove_membership_with_witness(&elems, witness)
+        .encode(&mut out);
+      write_file(proof, group, "membership", &out)?;
+      Ok(true)
+    }
+    ["prove-nonmembership", _, elems, proof] => {
+      let elems = read_elems(elems)?;
+      let mut out = vec![];
+      acc.prove_nonmembership(&set, &elems)?.encode(&mut out);
+      write_file(proof, group, "nonmembership", &out)?;
+      Ok(true)
+    }
+    ["verify", _, elems, proof] => {
+      let elems = read_elems(elems)?;
+      let (proof_group, kind, proof) = read_header(proof)?;
+      if proof_group != group {
+        return Err(format!("proof is for group `{}`, not `{}`", proof_group, group).into());
+      }
+      let valid = match kind.as_str() {
+        "membership" => {
+          acc.verify_membership_batch(&elems, &decode_all(&proof, MembershipProof::decode)?)
+        }
+        "nonmembership" => {
+          acc.verify_nonmembership(&elems, &decode_all(&proof, NonmembershipProof::decode)?)
+        }
+        _ => return Err(format!("`{}` is not a proof", kind).into()),
+      };
+      println!("{}", if valid { "valid" } else { "invalid" });
+      Ok(valid)
+    }
+    _ => Err(USAGE.into()),
+  }
+}
+
+// Returns the elements of `set` other than `elems`, failing if any of `elems` is not in `set`.
+fn others(set: &[String], elems: &[String]) -> Result<Vec<String>> {
+  let elems_set: HashSet<&String> = elems.iter().collect();
+  let set_set: HashSet<&String> = set.iter().collect();
+  if let Some(elem) = elems.iter().find(|elem| !set_set.contains(elem)) {
+    return Err(format!("element `{}` is not accumulated", elem).into());
+  }
+  Ok(
+    set
+      .iter()
+      .filter(|elem| !elems_set.contains(elem))
+      .cloned()
+      .collect(),
+  )
+}
+
+fn read_elems(path: &str) -> Result<Vec<String>> {
+  Ok(
+    fs::read_to_string(path)?
+      .lines()
+      .filter(|line| !line.is_empty())
+      .map(String::from)
+      .collect(),
+  )
+}
+
+fn write_state<G: UnknownOrderGroup>(
+  path: &str,
+  group: &str,
+  acc: &Accumulator<G, String>,
+  set: &[String],
+) -> Result<()>
+where
+  G::Elem: Persist,
+{
+  let mut out = vec![];
+  acc.encode(&mut out);
+  encode_list(set, &mut out);
+  write_file(path, group, "state", &out)
+}
+
+fn write_file(path: &str, group: &str, kind: &str, payload: &[u8]) -> Result<()> {
+  let mut out = MAGIC.to_vec();
+  group.to_string().encode(&mut out);
+  kind.to_string().encode(&mut out);
+  out.extend_from_slice(payload);
+  fs::write(path, out)?;
+  Ok(())
+}
+
+// Reads a file of the given `kind`, returning its group and payload.
+fn read_file(path: &str, kind: &str) -> Result<(String, Vec<u8>)> {
+  let (group, file_kind, payload) = read_header(path)?;
+  if file_kind != kind {
+    return Err(format!("{} is a {} file, not a {} file", path, file_kind, kind).into());
+  }
+  Ok((group, payload))
+}
+
+// Like `read_file`, but also checks that the file is for the given `group`.
+fn read_file_of(path: &str, group: &str, kind: &str) -> Result<Vec<u8>> {
+  let (file_group, payload) = read_file(path, kind)?;
+  if file_group != group {
+    return Err(format!("{} is for group `{}`, not `{}`", path, file_group, group).into());
+  }
+  Ok(payload)
+}
+
+// Reads a file, returning its group, kind and payload.
+fn read_header(path: &str) -> Result<(String, String, Vec<u8>)> {
+  let bytes = fs::read(path)?;
+  if !bytes.starts_with(MAGIC) {
+    return Err(format!("{} is not an accumulator-cli file", path).into());
+  }
+  let mut rest = &bytes[MAGIC.len()..];
+  let group = String::decode(&mut rest).ok_or_else(|| format!("{} is malformed", path))?;
+  let kind = String::decode(&mut rest).ok_or_else(|| format!("{} is malformed", path))?;
+  Ok((group, kind, rest.to_vec()))
+}
+
+// Decodes `bytes` with `decode`, failing unless it consumes all of them.
+fn decode_all<T, F>(mut bytes: &[u8], decode: F) -> Result<T>
+where
+  F: FnOnce(&mut &[u8]) -> Option<T>,
+{
+  match decode(&mut bytes) {
+    Some(val) if bytes.is_empty() => Ok(val),
+    _ => Err("malformed payload".into()),
+  }
+}
+
+fn encode_list<T: Persist>(items: &[T], out: &mut Vec<u8>) {
+  (items.len() as u64).encode(out);
+  for item in items {
+    item.encode(out);
+  }
+}
+
+fn decode_list<T: Persist>(bytes: &mut &[u8]) -> Option<Vec<T>> {
+  let len = u64::decode(bytes)?;
+  // Every item takes at least a byte, so longer lengths can only come from bad input.
+  if len > bytes.len() as u64 {
+    return None;
+  }
+  (0..len).map(|_| T::decode(bytes)).collect()
+}
+
+fn hex(bytes: &[u8]) -> String {
+  bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
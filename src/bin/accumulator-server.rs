@@ -0,0 +1,31 @@
+//! Serves an accumulator of strings over JSON-RPC. See the `server` module for the protocol.
+//!
+//! ```text
+//! accumulator-server <rsa2048|class> <addr>
+//! ```
+use accumulator::group::{ClassGroup, Rsa2048};
+use accumulator::server::Server;
+use std::env;
+use std::process;
+
+fn main() {
+  let args: Vec<String> = env::args().skip(1).collect();
+  let result = match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+    ["rsa2048", addr] => Server::<Rsa2048>::bind(addr).and_then(|server| {
+      eprintln!("listening on {}", server.local_addr()?);
+      server.serve()
+    }),
+    ["class", addr] => Server::<ClassGroup>::bind(addr).and_then(|server| {
+      eprintln!("listening on {}", server.local_addr()?);
+      server.serve()
+    }),
+    _ => {
+      eprintln!("usage: accumulator-server <rsa2048|class> <addr>");
+      process::exit(2);
+    }
+  };
+  if let Err(err) = result {
+    eprintln!("error: {}", err);
+    process::exit(1);
+  }
+}
@@ -0,0 +1,784 @@
+//! C bindings for accumulators over `Rsa2048` and `ClassGroup`, whose elements are byte strings.
+//!
+//! Accumulators and proofs are opaque handles, created by this module and released with the
+//! matching `*_free` function. Each handle belongs to the group it was created for, and mixing
+//! handles of different groups fails with `GroupMismatch`. Functions report failure through their
+//! `AccStatus` and write results to out-parameters, which are left untouched on failure.
+//!
+//! Handles serialize to the `Persist` encoding of the `store` module, so proofs produced by Rust
+//! provers can be passed to C verifiers as bytes. Serialized data does not record its group, which
+//! must be passed to the matching `*_deserialize` function.
+//!
+//! The header `include/accumulator.h` is generated from this module by `cbindgen` (see
+//! `cbindgen.toml`), and `tests/ffi` holds a C program that exercises it against the static
+//! library.
+//!
+//! # Safety
+//!
+//! Every pointer argument must be null or valid for the access its type implies, and handles must
+//! not be used after they are freed. Element lists are passed as an array of `n` `AccSlice`s, which
+//! may be null if `n` is zero; the bytes of each slice may likewise be null if its length is zero.
+//! Null handles and out-parameters fail with `NullPointer`. A panic inside a call does not unwind
+//! into C, but fails the call with `Failed` (or, for `acc_new`, returns null).
+use crate::accumulator::{AccError, Accumulator, MembershipProof, NonmembershipProof, Witness};
+use crate::group::{ClassGroup, Rsa2048, UnknownOrderGroup};
+use crate::store::Persist;
+use std::collections::HashSet;
+use std::panic::{self, AssertUnwindSafe};
+use std::{ptr, slice};
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+/// The groups an accumulator can be created over.
+pub enum AccGroup {
+  /// The RSA-2048 group.
+  Rsa2048,
+  /// The class group.
+  Class,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+/// The result of a call.
+pub enum AccStatus {
+  /// The call succeeded.
+  Ok,
+  /// A required pointer was null.
+  NullPointer,
+  /// Handles of different groups were mixed.
+  GroupMismatch,
+  /// Serialized data was malformed.
+  Malformed,
+  /// A witness or proof did not verify against the accumulator.
+  BadWitness,
+  /// An element appeared twice, or was already accumulated.
+  DuplicateElem,
+  /// An element was not accumulated.
+  UnknownElem,
+  /// Some other accumulator operation failed.
+  Failed,
+}
+
+impl From<AccError> for AccStatus {
+  fn from(err: AccError) -> Self {
+    match err {
+      AccError::BadWitness { .. } => AccStatus::BadWitness,
+      AccError::InputsNotCoprime { .. } | AccError::DuplicateElem { .. } => {
+        AccStatus::DuplicateElem
+      }
+      AccError::UnknownElem { .. } => AccStatus::UnknownElem,
+      _ => AccStatus::Failed,
+    }
+  }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+/// A borrowed byte string.
+pub struct AccSlice {
+  /// The bytes, which may be null if `len` is zero.
+  pub data: *const u8,
+  /// The number of bytes.
+  pub len: usize,
+}
+
+#[repr(C)]
+#[derive(Debug)]
+/// A byte string owned by this library, released with `acc_buffer_free`.
+pub struct AccBuffer {
+  /// The bytes.
+  pub data: *mut u8,
+  /// The number of bytes.
+  pub len: usize,
+}
+
+type Elem = Vec<u8>;
+
+/// An accumulator of byte strings.
+// Handles are always boxed, so the size difference between groups (here and in the proof
+// handles below) costs nothing.
+#[allow(clippy::large_enum_variant)]
+pub enum AccAccumulator {
+  #[doc(hidden)]
+  Rsa2048(Accumulator<Rsa2048, Elem>),
+  #[doc(hidden)]
+  Class(Accumulator<ClassGroup, Elem>),
+}
+
+/// A batch membership proof.
+#[allow(clippy::large_enum_variant)]
+pub enum AccMembershipProof {
+  #[doc(hidden)]
+  Rsa2048(MembershipProof<Rsa2048, Elem>),
+  #[doc(hidden)]
+  Class(MembershipProof<ClassGroup, Elem>),
+}
+
+/// A batch nonmembership proof.
+#[allow(clippy::large_enum_variant)]
+pub enum AccNonmembershipProof {
+  #[doc(hidden)]
+  Rsa2048(NonmembershipProof<Rsa2048, Elem>),
+  #[doc(hidden)]
+  Class(NonmembershipProof<ClassGroup, Elem>),
+}
+
+// Borrows a handle, returning `NullPointer` from the enclosing closure if it is null.
+macro_rules! handle {
+  ($ptr:expr) => {
+    match $ptr.as_ref() {
+      Some(handle) => handle,
+      None => return AccStatus::NullPointer,
+    }
+  };
+}
+
+// Like `handle`, but borrows mutably.
+macro_rules! handle_mut {
+  ($ptr:expr) => {
+    match $ptr.as_mut() {
+      Some(handle) => handle,
+      None => return AccStatus::NullPointer,
+    }
+  };
+}
+
+// Runs `f`, returning `fallback` if it panics, since unwinding into C is undefined behavior.
+// Handles are only modified once a call can no longer fail, so they stay usable after a panic.
+fn catch_panic<R>(fallback: R, f: impl FnOnce() -> R) -> R {
+  panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(fallback)
+}
+
+// Copies `n` elements from `elems`, or fails if `elems` (or the bytes of one) is null.
+unsafe fn read_elems(elems: *const AccSlice, n: usize) -> Result<Vec<Elem>, AccStatus> {
+  if n == 0 {
+    return Ok(vec![]);
+  }
+  if elems.is_null() {
+    return Err(AccStatus::NullPointer);
+  }
+  slice::from_raw_parts(elems, n)
+    .iter()
+    .map(|elem| read_bytes(elem.data, elem.len).map(<[u8]>::to_vec))
+    .collect()
+}
+
+unsafe fn read_bytes<'a>(data: *const u8, len: usize) -> Result<&'a [u8], AccStatus> {
+  match (data.is_null(), len) {
+    (true, 0) => Ok(&[]),
+    (true, _) => Err(AccStatus::NullPointer),
+    (false, _) => Ok(slice::from_raw_parts(data, len)),
+  }
+}
+
+// Moves `val` into a new handle at `out`.
+unsafe fn write_handle<T>(out: *mut *mut T, val: T) -> AccStatus {
+  if out.is_null() {
+    return AccStatus::NullPointer;
+  }
+  *out = Box::into_raw(Box::new(val));
+  AccStatus::Ok
+}
+
+unsafe fn serialize<T: Persist>(val: &T, out: *mut AccBuffer) -> AccStatus {
+  if out.is_null() {
+    return AccStatus::NullPointer;
+  }
+  let mut bytes = vec![];
+  val.encode(&mut bytes);
+  let len = bytes.len();
+  *out = AccBuffer {
+    data: Box::into_raw(bytes.into_boxed_slice()) as *mut u8,
+    len,
+  };
+  AccStatus::Ok
+}
+
+unsafe fn deserialize<T: Persist>(data: *const u8, len: usize) -> Result<T, AccStatus> {
+  let mut bytes = read_bytes(data, len)?;
+  match T::decode(&mut bytes) {
+    Some(val) if bytes.is_empty() => Ok(val),
+    _ => Err(AccStatus::Malformed),
+  }
+}
+
+// Computes a witness for `elems` from the accumulated `set`, failing if `elems` is not a subset.
+fn witness<G: UnknownOrderGroup>(
+  set: &[Elem],
+  elems: &[Elem],
+) -> Result<Witness<G, Elem>, AccStatus> {
+  let mut others: HashSet<&Elem> = set.iter().collect();
+  for elem in elems {
+    if !others.remove(elem) {
+      return Err(AccStatus::UnknownElem);
+    }
+  }
+  let others: Vec<Elem> = others.into_iter().cloned().collect();
+  Ok(Witness(Accumulator::empty().add(&others)))
+}
+
+// Deletes `elems` from `acc` given a batch membership `proof` for them.
+fn delete<G: UnknownOrderGroup>(
+  acc: &mut Accumulator<G, Elem>,
+  elems: &[Elem],
+  proof: &MembershipProof<G, Elem>,
+) -> AccStatus {
+  let elem_witnesses = proof.witness.compute_individual_witnesses(elems);
+  match acc.clone().delete(&elem_witnesses) {
+    Ok(new_acc) => {
+      *acc = new_acc;
+      AccStatus::Ok
+    }
+    Err(err) => err.into(),
+  }
+}
+
+/// Returns a new, empty accumulator over `group`, or null if creating it panicked.
+#[no_mangle]
+pub extern "C" fn acc_new(group: AccGroup) -> *mut AccAccumulator {
+  catch_panic(ptr::null_mut(), || {
+    Box::into_raw(Box::new(match group {
+      AccGroup::Rsa2048 => AccAccumulator::Rsa2048(Accumulator::empty()),
+      AccGroup::Class => AccAccumulator::Class(Accumulator::empty()),
+    }))
+  })
+}
+
+/// Writes the group of `acc` to `group`.
+///
+/// # Safety
+///
+/// See the module docs (repeated at the top of `accumulator.h`).
+#[no_mangle]
+pub unsafe extern "C" fn acc_group(acc: *const AccAccumulator, group: *mut AccGroup) -> AccStatus {
+  catch_panic(AccStatus::Failed, || {
+    let (acc, group) = (handle!(acc), handle_mut!(group));
+    *group = match acc {
+      AccAccumulator::Rsa2048(_) => AccGroup::Rsa2048,
+      AccAccumulator::Class(_) => AccGroup::Class,
+    };
+    AccStatus::Ok
+  })
+}
+
+/// Releases an accumulator. Does nothing if `acc` is null.
+///
+/// # Safety
+///
+/// See the module docs (repeated at the top of `accumulator.h`).
+#[no_mangle]
+pub unsafe extern "C" fn acc_free(acc: *mut AccAccumulator) {
+  catch_panic((), || {
+    if !acc.is_null() {
+      drop(Box::from_raw(acc));
+    }
+  })
+}
+
+/// Adds `elems` to `acc`. If `proof` is not null, a membership proof for `elems` in the new
+/// accumulator is written to it.
+///
+/// # Safety
+///
+/// See the module docs (repeated at the top of `accumulator.h`).
+#[no_mangle]
+pub unsafe extern "C" fn acc_add(
+  acc: *mut AccAccumulator,
+  elems: *const AccSlice,
+  n: usize,
+  proof: *mut *mut AccMembershipProof,
+) -> AccStatus {
+  catch_panic(AccStatus::Failed, || {
+    let acc = handle_mut!(acc);
+    let elems = match read_elems(elems, n) {
+      Ok(elems) => elems,
+      Err(status) => return status,
+    };
+    let new_proof = match acc {
+      AccAccumulator::Rsa2048(acc) => {
+        let (new_acc, proof) = acc.clone().add_with_proof(&elems);
+        *acc = new_acc;
+        AccMembershipProof::Rsa2048(proof)
+      }
+      AccAccumulator::Class(acc) => {
+        let (new_acc, proof) = acc.clone().add_with_proof(&elems);
+        *acc = new_acc;
+        AccMembershipProof::Class(proof)
+      }
+    };
+    if proof.is_null() {
+      return AccStatus::Ok;
+    }
+    write_handle(proof, new_proof)
+  })
+}
+
+/// Deletes `elems` from `acc`, given a membership proof for them (such as one from `acc_add` or
+/// `acc_prove_membership`). Fails with `BadWitness` if the proof's witness does not match.
+///
+/// # Safety
+///
+/// See the module docs (repeated at the top of `accumulator.h`).
+#[no_mangle]
+pub unsafe extern "C" fn acc_delete(
+  acc: *mut AccAccumulator,
+  elems: *const AccSlice,
+  n: usize,
+  proof: *const AccMembershipProof,
+) -> AccStatus {
+  catch_panic(AccStatus::Failed, || {
+    let (acc, proof) = (handle_mut!(acc), handle!(proof));
+    let elems = match read_elems(elems, n) {
+      Ok(elems) => elems,
+      Err(status) => return status,
+    };
+    match (acc, proof) {
+      (AccAccumulator::Rsa2048(acc), AccMembershipProof::Rsa2048(proof)) => {
+        delete(acc, &elems, proof)
+      }
+      (AccAccumulator::Class(acc), AccMembershipProof::Class(proof)) => delete(acc, &elems, proof),
+      _ => AccStatus::GroupMismatch,
+    }
+  })
+}
+
+/// Proves that `elems` are in `acc`, whose accumulated elements are `set`. Fails with
+/// `UnknownElem` if some element is not in `set`.
+///
+/// # Safety
+///
+/// See the module docs (repeated at the top of `accumulator.h`).
+#[no_mangle]
+pub unsafe extern "C" fn acc_prove_membership(
+  acc: *const AccAccumulator,
+  set: *const AccSlice,
+  set_len: usize,
+  elems: *const AccSlice,
+  n: usize,
+  proof: *mut *mut AccMembershipProof,
+) -> AccStatus {
+  catch_panic(AccStatus::Failed, || {
+    let acc = handle!(acc);
+    let (set, elems) = match (read_elems(set, set_len), read_elems(elems, n)) {
+      (Ok(set), Ok(elems)) => (set, elems),
+      (Err(status), _) | (_, Err(status)) => return status,
+    };
+    let new_proof = match acc {
+      AccAccumulator::Rsa2048(acc) => match witness(&set, &elems) {
+        Ok(witness) => {
+          AccMembershipProof::Rsa2048(acc.prove_membership_with_witness(&elems, witness))
+        }
+        Err(status) => return status,
+      },
+      AccAccumulator::Class(acc) => match witness(&set, &elems) {
+        Ok(witness) => {
+          AccMembershipProof::Class(acc.prove_membership_with_witness(&elems, witness))
+        }
+        Err(status) => return status,
+      },
+    };
+    write_handle(proof, new_proof)
+  })
+}
+
+/// Proves that `elems` are not in `acc`, whose accumulated elements are `set`. Fails with
+/// `DuplicateElem` if some element is in `set`.
+///
+/// # Safety
+///
+/// See the module docs (repeated at the top of `accumulator.h`).
+#[no_mangle]
+pub unsafe extern "C" fn acc_prove_nonmembership(
+  acc: *const AccAccumulator,
+  set: *const AccSlice,
+  set_len: usize,
+  elems: *const AccSlice,
+  n: usize,
+  proof: *mut *mut AccNonmembershipProof,
+) -> AccStatus {
+  catch_panic(AccStatus::Failed, || {
+    let acc = handle!(acc);
+    let (set, elems) = match (read_elems(set, set_len), read_elems(elems, n)) {
+      (Ok(set), Ok(elems)) => (set, elems),
+      (Err(status), _) | (_, Err(status)) => return status,
+    };
+    let new_proof = match acc {
+      AccAccumulator::Rsa2048(acc) => acc
+        .prove_nonmembership(&set, &elems)
+        .map(AccNonmembershipProof::Rsa2048),
+      AccAccumulator::Class(acc) => acc
+        .prove_nonmembership(&set, &elems)
+        .map(AccNonmembershipProof::Class),
+    };
+    match new_proof {
+      Ok(new_proof) => write_handle(proof, new_proof),
+      Err(err) => err.into(),
+    }
+  })
+}
+
+/// Verifies a membership proof for `elems` in `acc`, writing the result to `valid`.
+///
+/// # Safety
+///
+/// See the module docs (repeated at the top of `accumulator.h`).
+#[no_mangle]
+pub unsafe extern "C" fn acc_verify_membership(
+  acc: *const AccAccumulator,
+  elems: *const AccSlice,
+  n: usize,
+  proof: *const AccMembershipProof,
+  valid: *mut bool,
+) -> AccStatus {
+  catch_panic(AccStatus::Failed, || {
+    let (acc, proof, valid) = (handle!(acc), handle!(proof), handle_mut!(valid));
+    let elems = match read_elems(elems, n) {
+      Ok(elems) => elems,
+      Err(status) => return status,
+    };
+    *valid = match (acc, proof) {
+      (AccAccumulator::Rsa2048(acc), AccMembershipProof::Rsa2048(proof)) => {
+        acc.verify_membership_batch(&elems, proof)
+      }
+      (AccAccumulator::Class(acc), AccMembershipProof::Class(proof)) => {
+        acc.verify_membership_batch(&elems, proof)
+      }
+      _ => return AccStatus::GroupMismatch,
+    };
+    AccStatus::Ok
+  })
+}
+
+/// Verifies a nonmembership proof for `elems` in `acc`, writing the result to `valid`.
+///
+/// # Safety
+///
+/// See the module docs (repeated at the top of `accumulator.h`).
+#[no_mangle]
+pub unsafe extern "C" fn acc_verify_nonmembership(
+  acc: *const AccAccumulator,
+  elems: *const AccSlice,
+  n: usize,
+  proof: *const AccNonmembershipProof,
+  valid: *mut bool,
+) -> AccStatus {
+  catch_panic(AccStatus::Failed, || {
+    let (acc, proof, valid) = (handle!(acc), handle!(proof), handle_mut!(valid));
+    let elems = match read_elems(elems, n) {
+      Ok(elems) => elems,
+      Err(status) => return status,
+    };
+    *valid = match (acc, proof) {
+      (AccAccumulator::Rsa2048(acc), AccNonmembershipProof::Rsa2048(proof)) => {
+        acc.verify_nonmembership(&elems, proof)
+      }
+      (AccAccumulator::Class(acc), AccNonmembershipProof::Class(proof)) => {
+        acc.verify_nonmembership(&elems, proof)
+      }
+      _ => return AccStatus::GroupMismatch,
+    };
+    AccStatus::Ok
+  })
+}
+
+/// Releases a membership proof. Does nothing if `proof` is null.
+///
+/// # Safety
+///
+/// See the module docs (repeated at the top of `accumulator.h`).
+#[no_mangle]
+pub unsafe extern "C" fn acc_membership_proof_free(proof: *mut AccMembershipProof) {
+  catch_panic((), || {
+    if !proof.is_null() {
+      drop(Box::from_raw(proof));
+    }
+  })
+}
+
+/// Releases a nonmembership proof. Does nothing if `proof` is null.
+///
+/// # Safety
+///
+/// See the module docs (repeated at the top of `accumulator.h`).
+#[no_mangle]
+pub unsafe extern "C" fn acc_nonmembership_proof_free(proof: *mut AccNonmembershipProof) {
+  catch_panic((), || {
+    if !proof.is_null() {
+      drop(Box::from_raw(proof));
+    }
+  })
+}
+
+/// Serializes `acc` into a new buffer at `out`.
+///
+/// # Safety
+///
+/// See the module docs (repeated at the top of `accumulator.h`).
+#[no_mangle]
+pub unsafe extern "C" fn acc_serialize(
+  acc: *const AccAccumulator,
+  out: *mut AccBuffer,
+) -> AccStatus {
+  catch_panic(AccStatus::Failed, || match handle!(acc) {
+    AccAccumulator::Rsa2048(acc) => serialize(acc, out),
+    AccAccumulator::Class(acc) => serialize(acc, out),
+  })
+}
+
+/// Deserializes an accumulator over `group` from `len` bytes at `data` into a new handle at `out`.
+///
+/// # Safety
+///
+/// See the module docs (repeated at the top of `accumulator.h`).
+#[no_mangle]
+pub unsafe extern "C" fn acc_deserialize(
+  group: AccGroup,
+  data: *const u8,
+  len: usize,
+  out: *mut *mut AccAccumulator,
+) -> AccStatus {
+  catch_panic(AccStatus::Failed, || {
+    let acc = match group {
+      AccGroup::Rsa2048 => deserialize(data, len).map(AccAccumulator::Rsa2048),
+      AccGroup::Class => deserialize(data, len).map(AccAccumulator::Class),
+    };
+    match acc {
+      Ok(acc) => write_handle(out, acc),
+      Err(status) => status,
+    }
+  })
+}
+
+/// Serializes a membership proof into a new buffer at `out`.
+///
+/// # Safety
+///
+/// See the module docs (repeated at the top of `accumulator.h`).
+#[no_mangle]
+pub unsafe extern "C" fn acc_membership_proof_serialize(
+  proof: *const AccMembershipProof,
+  out: *mut AccBuffer,
+) -> AccStatus {
+  catch_panic(AccStatus::Failed, || match handle!(proof) {
+    AccMembershipProof::Rsa2048(proof) => serialize(proof, out),
+    AccMembershipProof::Class(proof) => serialize(proof, out),
+  })
+}
+
+/// Deserializes a membership proof over `group` from `len` bytes at `data` into a new handle at
+/// `out`.
+///
+/// # Safety
+///
+/// See the module docs (repeated at the top of `accumulator.h`).
+#[no_mangle]
+pub unsafe extern "C" fn acc_membership_proof_deserialize(
+  group: AccGroup,
+  data: *const u8,
+  len: usize,
+  out: *mut *mut AccMembershipProof,
+) -> AccStatus {
+  catch_panic(AccStatus::Failed, || {
+    let proof = match group {
+      AccGroup::Rsa2048 => deserialize(data, len).map(AccMembershipProof::Rsa2048),
+      AccGroup::Class => deserialize(data, len).map(AccMembershipProof::Class),
+    };
+    match proof {
+      Ok(proof) => write_handle(out, proof),
+      Err(status) => status,
+    }
+  })
+}
+
+/// Serializes a nonmembership proof into a new buffer at `out`.
+///
+/// # Safety
+///
+/// See the module docs (repeated at the top of `accumulator.h`).
+#[no_mangle]
+pub unsafe extern "C" fn acc_nonmembership_proof_serialize(
+  proof: *const AccNonmembershipProof,
+  out: *mut AccBuffer,
+) -> AccStatus {
+  catch_panic(AccStatus::Failed, || match handle!(proof) {
+    AccNonmembershipProof::Rsa2048(proof) => serialize(proof, out),
+    AccNonmembershipProof::Class(proof) => serialize(proof, out),
+  })
+}
+
+/// Deserializes a nonmembership proof over `group` from `len` bytes at `data` into a new handle at
+/// `out`.
+///
+/// # Safety
+///
+/// See the module docs (repeated at the top of `accumulator.h`).
+#[no_mangle]
+pub unsafe extern "C" fn acc_nonmembership_proof_deserialize(
+  group: AccGroup,
+  data: *const u8,
+  len: usize,
+  out: *mut *mut AccNonmembershipProof,
+) -> AccStatus {
+  catch_panic(AccStatus::Failed, || {
+    let proof = match group {
+      AccGroup::Rsa2048 => deserialize(data, len).map(AccNonmembershipProof::Rsa2048),
+      AccGroup::Class => deserialize(data, len).map(AccNonmembershipProof::Class),
+    };
+    match proof {
+      Ok(proof) => write_handle(out, proof),
+      Err(status) => status,
+    }
+  })
+}
+
+/// Releases a buffer returned by this library. Does nothing if its data is null.
+///
+/// # Safety
+///
+/// See the module docs (repeated at the top of `accumulator.h`).
+#[no_mangle]
+pub unsafe extern "C" fn acc_buffer_free(buffer: AccBuffer) {
+  catch_panic((), || {
+    if !buffer.data.is_null() {
+      drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+        buffer.data,
+        buffer.len,
+      )));
+    }
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn slices(elems: &[&'static str]) -> Vec<AccSlice> {
+    elems
+      .iter()
+      .map(|elem| AccSlice {
+        data: elem.as_ptr(),
+        len: elem.len(),
+      })
+      .collect()
+  }
+
+  #[test]
+  fn test_ffi() {
+    let (set, elems, others) = (slices(&["a", "b", "c"]), slices(&["b"]), slices(&["d"]));
+    let mut proof = ptr::null_mut();
+    let mut valid = false;
+    unsafe {
+      let acc = acc_new(AccGroup::Class);
+      let mut group = AccGroup::Rsa2048;
+      assert_eq!(acc_group(acc, &mut group), AccStatus::Ok);
+      assert_eq!(group, AccGroup::Class);
+      assert_eq!(acc_group(ptr::null(), &mut group), AccStatus::NullPointer);
+      assert_eq!(
+        acc_add(acc, set.as_ptr(), 3, ptr::null_mut()),
+        AccStatus::Ok
+      );
+      assert_eq!(
+        acc_prove_membership(acc, set.as_ptr(), 3, others.as_ptr(), 1, &mut proof),
+        AccStatus::UnknownElem
+      );
+      assert_eq!(
+        acc_prove_membership(acc, set.as_ptr(), 3, elems.as_ptr(), 1, &mut proof),
+        AccStatus::Ok
+      );
+
+      // Round-trip the proof and the accumulator through bytes.
+      let mut buffer = AccBuffer {
+        data: ptr::null_mut(),
+        len: 0,
+      };
+      assert_eq!(
+        acc_membership_proof_serialize(proof, &mut buffer),
+        AccStatus::Ok
+      );
+      let mut copy = ptr::null_mut();
+      assert_eq!(
+        acc_membership_proof_deserialize(AccGroup::Rsa2048, buffer.data, buffer.len, &mut copy),
+        AccStatus::Malformed
+      );
+      assert_eq!(
+        acc_membership_proof_deserialize(AccGroup::Class, buffer.data, buffer.len, &mut copy),
+        AccStatus::Ok
+      );
+      acc_buffer_free(buffer);
+      let mut buffer = AccBuffer {
+        data: ptr::null_mut(),
+        len: 0,
+      };
+      assert_eq!(acc_serialize(acc, &mut buffer), AccStatus::Ok);
+      let mut acc_copy = ptr::null_mut();
+      assert_eq!(
+        acc_deserialize(AccGroup::Class, buffer.data, buffer.len, &mut acc_copy),
+        AccStatus::Ok
+      );
+      acc_buffer_free(buffer);
+
+      assert_eq!(
+        acc_verify_membership(acc_copy, elems.as_ptr(), 1, copy, &mut valid),
+        AccStatus::Ok
+      );
+      assert!(valid);
+      let rsa_acc = acc_new(AccGroup::Rsa2048);
+      assert_eq!(
+        acc_verify_membership(rsa_acc, elems.as_ptr(), 1, copy, &mut valid),
+        AccStatus::GroupMismatch
+      );
+
+      assert_eq!(acc_delete(acc, elems.as_ptr(), 1, copy), AccStatus::Ok);
+      assert_eq!(
+        acc_delete(acc, elems.as_ptr(), 1, copy),
+        AccStatus::BadWitness
+      );
+      let mut nonmembership_proof = ptr::null_mut();
+      let rest = slices(&["a", "c"]);
+      assert_eq!(
+        acc_prove_nonmembership(
+          acc,
+          rest.as_ptr(),
+          2,
+          elems.as_ptr(),
+          1,
+          &mut nonmembership_proof
+        ),
+        AccStatus::Ok
+      );
+      assert_eq!(
+        acc_verify_nonmembership(acc, elems.as_ptr(), 1, nonmembership_proof, &mut valid),
+        AccStatus::Ok
+      );
+      assert!(valid);
+      assert_eq!(
+        acc_verify_nonmembership(acc_copy, elems.as_ptr(), 1, nonmembership_proof, &mut valid),
+        AccStatus::Ok
+      );
+      assert!(!valid);
+      assert_eq!(
+        acc_add(ptr::null_mut(), elems.as_ptr(), 1, ptr::null_mut()),
+        AccStatus::NullPointer
+      );
+
+      acc_nonmembership_proof_free(nonmembership_proof);
+      acc_membership_proof_free(copy);
+      acc_membership_proof_free(proof);
+      acc_free(rsa_acc);
+      acc_free(acc_copy);
+      acc_free(acc);
+    }
+  }
+
+  #[test]
+  fn test_catch_panic() {
+    assert_eq!(
+      catch_panic(AccStatus::Failed, || panic!("panic in a call")),
+      AccStatus::Failed
+    );
+    assert_eq!(
+      catch_panic(AccStatus::Failed, || AccStatus::Ok),
+      AccStatus::Ok
+    );
+  }
+}
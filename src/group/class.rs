@@ -2,10 +2,11 @@
 //!
 //! Using a class group instead of an RSA group for accumulators or vector commitments eliminates
 //! the need for a trusted setup, albeit at the expense of slower operations.
-use super::{ElemFrom, Group, UnknownOrderGroup};
+use super::{ElemError, ElemFrom, FixedBaseTable, Group, TryElemFrom, UnknownOrderGroup};
+use crate::bigint::{Assign, Integer};
+use crate::store::Persist;
 use crate::util;
 use crate::util::{int, TypeRep};
-use rug::{Assign, Integer};
 use std::hash::{Hash, Hasher};
 use std::str::FromStr;
 
@@ -13,6 +14,11 @@ use std::str::FromStr;
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 /// Class group implementation, with future optimizations available via the `--features` flag.
 /// Discriminant generated via OpenSSL.
+///
+/// Every `ClassElem` is an element of the group: `ElemFrom` panics on forms that cannot be reduced
+/// to one, and `TryElemFrom` and decoding return an error instead. The group operations therefore
+/// cannot fail. `try_op` and `try_square` are fallible counterparts for forms whose membership is
+/// in doubt.
 pub enum ClassGroup {}
 
 // 2048-bit prime, negated, congruent to `3 mod 4`. Generated using OpenSSL.
@@ -31,6 +37,8 @@ const DISCRIMINANT2048_DECIMAL: &str =
 lazy_static! {
   pub static ref CLASS_GROUP_DISCRIMINANT: Integer =
     Integer::from_str(DISCRIMINANT2048_DECIMAL).unwrap();
+  static ref UNKNOWN_ORDER_TABLE: FixedBaseTable<ClassGroup> =
+    FixedBaseTable::new(ClassGroup::unknown_order_elem());
 }
 
 #[allow(clippy::module_name_repetitions)]
@@ -81,8 +89,9 @@ impl ClassGroup {
   /// This method is only public for benchmarking. You should not need to use it.
   pub fn square(x: &ClassElem) -> ClassElem {
     // Solve `bk = c mod a` for `k`, represented by `mu`, `v` and any integer `n` s.t.
-    // `k = mu + v * n`.
-    let (mu, _) = util::solve_linear_congruence(&x.b, &x.c, &x.a).unwrap();
+    // `k = mu + v * n`. There is always a solution for elements of the group.
+    let (mu, _) = util::solve_linear_congruence(&x.b, &x.c, &x.a)
+      .expect("congruence is solvable for elements of the group");
 
     // A = a^2
     // B = b - 2a * mu
@@ -96,6 +105,21 @@ impl ClassGroup {
     Self::elem((a, b, c))
   }
 
+  /// Like `square`, but fails with the reason `x` is not an element of the group instead of
+  /// computing with it.
+  pub fn try_square(x: &ClassElem) -> Result<ClassElem, ElemError> {
+    Self::check(&x.a, &x.b, &x.c)?;
+    Ok(Self::square(x))
+  }
+
+  /// Like `op`, but fails with the reason `x` or `y` is not an element of the group instead of
+  /// computing with it.
+  pub fn try_op(x: &ClassElem, y: &ClassElem) -> Result<ClassElem, ElemError> {
+    Self::check(&x.a, &x.b, &x.c)?;
+    Self::check(&y.a, &y.b, &y.c)?;
+    Ok(Self::op(x, y))
+  }
+
   fn discriminant(a: &Integer, b: &Integer, c: &Integer) -> Integer {
     int(b.square_ref()) - int(4) * a * c
   }
@@ -111,6 +135,20 @@ impl ClassGroup {
   fn is_normal(a: &Integer, b: &Integer, _c: &Integer) -> bool {
     -int(a) < int(b) && b <= a
   }
+
+  // Checks that `(a, b, c)` is a class element: a reduced, primitive form of our discriminant.
+  fn check(a: &Integer, b: &Integer, c: &Integer) -> Result<(), ElemError> {
+    if !Self::validate(a, b, c) {
+      return Err(ElemError::WrongDiscriminant);
+    }
+    if !Self::is_reduced(a, b, c) {
+      return Err(ElemError::NotReduced);
+    }
+    if int(a.gcd_ref(b)).gcd(c) != 1 {
+      return Err(ElemError::NotPrimitive);
+    }
+    Ok(())
+  }
 }
 
 impl TypeRep for ClassGroup {
@@ -145,11 +183,13 @@ impl Group for ClassGroup {
     // a = tu
     // b = hu + sc
     // m = st
-    // Solve linear congruence `(tu)k = hu + sc mod st` or `ak = b mod m` for solutions `k`.
+    // Solve linear congruence `(tu)k = hu + sc mod st` or `ak = b mod m` for solutions `k`. Both
+    // this and the next congruence always have solutions for elements of the group.
     let a = int(&t * &u);
     let b = int(&h * &u) + (&s * &x.c);
     let mut m = int(&s * &t);
-    let (mu, v) = util::solve_linear_congruence(&a, &b, &m).unwrap();
+    let (mu, v) = util::solve_linear_congruence(&a, &b, &m)
+      .expect("congruence is solvable for elements of the group");
 
     // a = tv
     // b = h - t * mu
@@ -158,7 +198,8 @@ impl Group for ClassGroup {
     let a = int(&t * &v);
     let b = &h - int(&t * &mu);
     m.assign(&s);
-    let (lambda, _) = util::solve_linear_congruence(&a, &b, &m).unwrap();
+    let (lambda, _) = util::solve_linear_congruence(&a, &b, &m)
+      .expect("congruence is solvable for elements of the group");
 
     // k = mu + v * lambda
     // l = (k * t - h) / s
@@ -186,6 +227,10 @@ impl Group for ClassGroup {
     ClassElem { a, b, c }
   }
 
+  fn is_valid_(_: &Integer, x: &ClassElem) -> bool {
+    Self::check(&x.a, &x.b, &x.c).is_ok()
+  }
+
   // Constructs the inverse directly instead of using `Self::Elem()`.
   fn inv_(_: &Integer, x: &ClassElem) -> ClassElem {
     ClassElem {
@@ -226,6 +271,10 @@ impl UnknownOrderGroup for ClassGroup {
     let c = int(1 - d) / int(8);
     ClassElem { a, b, c }
   }
+
+  fn exp_unknown_order_elem_(_: &Integer, n: &Integer) -> ClassElem {
+    UNKNOWN_ORDER_TABLE.exp(n)
+  }
 }
 
 impl Hash for ClassElem {
@@ -244,7 +293,26 @@ impl PartialEq for ClassElem {
   }
 }
 
-/// Panics if `(a, b, c)` cannot be reduced to a valid class element.
+impl Persist for ClassElem {
+  fn encode(&self, out: &mut Vec<u8>) {
+    self.a.encode(out);
+    self.b.encode(out);
+    self.c.encode(out);
+  }
+
+  /// Only accepts reduced elements of the group.
+  fn decode(bytes: &mut &[u8]) -> Option<Self> {
+    let (a, b, c) = (
+      Integer::decode(bytes)?,
+      Integer::decode(bytes)?,
+      Integer::decode(bytes)?,
+    );
+    ClassGroup::try_elem((a, b, c)).ok()
+  }
+}
+
+/// Panics if `(a, b, c)` cannot be reduced to a valid class element, i.e. if it has the wrong
+/// discriminant or is not positive definite. Use `TryElemFrom` for forms that may be invalid.
 impl<A, B, C> ElemFrom<(A, B, C)> for ClassGroup
 where
   Integer: From<A>,
@@ -252,18 +320,37 @@ where
   Integer: From<C>,
 {
   fn elem(abc: (A, B, C)) -> ClassElem {
-    let (a, b, c) = Self::reduce(int(abc.0), int(abc.1), int(abc.2));
-
-    // Ideally, this should return an error and the return type of `ElemFrom` should be
-    // `Result<Self::Elem, Self:err>`, but this would require a lot of ugly `unwrap`s in the
-    // accumulator library. Besides, users should not need to create new class group elements, so
-    // an invalid `ElemFrom` here should signal a severe internal error.
-    assert!(Self::validate(&a, &b, &c));
+    let (a, b, c) = (int(abc.0), int(abc.1), int(abc.2));
+
+    // `ElemFrom` cannot return an error, and users should not need to create new class group
+    // elements, so an invalid form here signals a severe internal error. `TryElemFrom` is the
+    // fallible counterpart. Reduction preserves the discriminant, and only terminates for positive
+    // definite forms, so check both first.
+    assert!(
+      a > 0 && Self::validate(&a, &b, &c),
+      "form is not in the class group"
+    );
 
+    let (a, b, c) = Self::reduce(a, b, c);
     ClassElem { a, b, c }
   }
 }
 
+/// Accepts only forms `(a, b, c)` that are already class elements, i.e. reduced, primitive and of
+/// the group's discriminant.
+impl<A, B, C> TryElemFrom<(A, B, C)> for ClassGroup
+where
+  Integer: From<A>,
+  Integer: From<B>,
+  Integer: From<C>,
+{
+  fn try_elem(abc: (A, B, C)) -> Result<ClassElem, ElemError> {
+    let (a, b, c) = (int(abc.0), int(abc.1), int(abc.2));
+    Self::check(&a, &b, &c)?;
+    Ok(ClassElem { a, b, c })
+  }
+}
+
 // Caveat: Tests that use "ground truth" use outputs from Chia's sample implementation in python:
 // https://github.com/Chia-Network/vdf-competition/blob/master/inkfish/classgroup.py.
 #[cfg(test)]
@@ -286,6 +373,43 @@ mod tests {
     let _ = ClassGroup::elem((1, 2, 3));
   }
 
+  #[should_panic]
+  #[test]
+  fn test_negative_definite_elem() {
+    // The right discriminant, but negative definite.
+    let c = int(1 - &*CLASS_GROUP_DISCRIMINANT) / int(-8);
+    assert!(ClassGroup::validate(&int(-2), &int(1), &c));
+    let _ = ClassGroup::elem((-2, 1, c));
+  }
+
+  #[test]
+  fn test_try_op() {
+    let g = ClassGroup::unknown_order_elem();
+    assert!(ClassGroup::try_op(&g, &g) == Ok(ClassGroup::op(&g, &g)));
+    assert!(ClassGroup::try_square(&g) == Ok(ClassGroup::op(&g, &g)));
+    let bad = construct_raw_elem_from_strings("1", "2", "3");
+    assert!(ClassGroup::try_op(&bad, &g) == Err(ElemError::WrongDiscriminant));
+    assert!(ClassGroup::try_op(&g, &bad) == Err(ElemError::WrongDiscriminant));
+    assert!(ClassGroup::try_square(&bad) == Err(ElemError::WrongDiscriminant));
+  }
+
+  #[test]
+  fn test_try_elem() {
+    let g = ClassGroup::unknown_order_elem();
+    let (a, b, c) = (g.a.clone(), g.b.clone(), g.c.clone());
+    assert!(ClassGroup::try_elem((a.clone(), b.clone(), c.clone())) == Ok(g.clone()));
+    assert!(ClassGroup::is_valid(&g));
+    assert!(ClassGroup::try_elem((1, 2, 3)) == Err(ElemError::WrongDiscriminant));
+    assert!(!ClassGroup::is_valid(&construct_raw_elem_from_strings(
+      "1", "2", "3"
+    )));
+
+    // `(a, b + 2a, a + b + c)` is equivalent to `(a, b, c)`, but not normal.
+    let unreduced = (a.clone(), int(&b + int(2 * &a)), int(&a + &b) + &c);
+    assert!(ClassGroup::try_elem(unreduced.clone()) == Err(ElemError::NotReduced));
+    assert!(ClassGroup::elem(unreduced) == g);
+  }
+
   #[test]
   fn test_elem_from() {
     let a1 = Integer::from_str("16").unwrap();
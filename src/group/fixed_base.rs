@@ -0,0 +1,267 @@
+//! Fixed-base exponentiation via lazily-built precomputation tables.
+//!
+//! Nearly every exponentiation of `UnknownOrderGroup::unknown_order_elem` in this library has a
+//! large exponent (e.g. a product of hashed primes), so it pays to precompute powers of that base.
+//! A `FixedBaseTable` stores `base ^ (2 ^ (stride * i))` for increasing `i`, growing on demand up
+//! to a configurable cap, and exponentiates with Yao's windowed method: an exponent with `k`
+//! windows of `w` bits costs roughly `k + 2 ^ (w + 1)` group operations instead of the `~1.5 *
+//! bits` needed by square-and-multiply.
+//!
+//! Table sizes are governed by a global `FixedBaseConfig`. Tables read the config when they are
+//! first used, so call `set_fixed_base_config` early (e.g. at node startup) if you need to shrink
+//! them.
+use super::Group;
+use crate::bigint::Integer;
+use crate::bigint::Order;
+use crate::util::int;
+use std::sync::RwLock;
+
+/// Widest window (in bits) we will ever use for a single exponentiation. Bounds the temporary
+/// bucket storage to `2 ^ MAX_WINDOW_BITS` elements.
+const MAX_WINDOW_BITS: usize = 16;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+/// Size parameters for fixed-base tables. A table holds at most `max_bits / stride + 1` group
+/// elements.
+pub struct FixedBaseConfig {
+  /// Number of exponent bits between consecutive precomputed powers. Larger strides shrink the
+  /// table proportionally, at the cost of slightly slower exponentiation.
+  pub stride: u32,
+  /// Number of exponent bits the table will grow to cover. Exponent bits past this point fall back
+  /// to square-and-multiply. Setting this to `0` disables precomputation.
+  pub max_bits: u32,
+}
+
+impl Default for FixedBaseConfig {
+  /// About 4096 elements per group (1 MB for `Rsa2048`).
+  fn default() -> Self {
+    Self {
+      stride: 4,
+      max_bits: 1 << 14,
+    }
+  }
+}
+
+lazy_static! {
+  static ref CONFIG: RwLock<FixedBaseConfig> = RwLock::new(FixedBaseConfig::default());
+}
+
+/// Sets the size of fixed-base tables built from now on. Tables that have already been used keep
+/// their original configuration.
+///
+/// Panics if `config.stride` is zero.
+pub fn set_fixed_base_config(config: FixedBaseConfig) {
+  assert!(config.stride > 0, "Fixed-base stride must be positive.");
+  *CONFIG.write().unwrap() = config;
+}
+
+/// Returns the configuration that newly-built fixed-base tables will use.
+pub fn fixed_base_config() -> FixedBaseConfig {
+  *CONFIG.read().unwrap()
+}
+
+/// Precomputed powers of a fixed group element. See the module documentation for details.
+pub struct FixedBaseTable<G: Group> {
+  stride: usize,
+  max_windows: usize,
+  // `powers[i] = base ^ (2 ^ (stride * i))`. Never empty.
+  powers: RwLock<Vec<G::Elem>>,
+}
+
+impl<G: Group> FixedBaseTable<G> {
+  /// Returns a table for `base` sized by the current global `FixedBaseConfig`.
+  pub fn new(base: G::Elem) -> Self {
+    Self::with_config(base, fixed_base_config())
+  }
+
+  /// Returns a table for `base` with an explicit configuration.
+  ///
+  /// Panics if `config.stride` is zero.
+  pub fn with_config(base: G::Elem, config: FixedBaseConfig) -> Self {
+    assert!(config.stride > 0, "Fixed-base stride must be positive.");
+    Self {
+      stride: config.stride as usize,
+      max_windows: (config.max_bits / config.stride) as usize,
+      powers: RwLock::new(vec![base]),
+    }
+  }
+
+  /// Returns `base ^ n`. Equivalent to `G::exp(base, n)`, but faster.
+  pub fn exp(&self, n: &Integer) -> G::Elem {
+    let abs = int(n.abs_ref());
+    let bits = abs.significant_bits() as usize;
+    let windows = bits.div_ceil(self.stride).min(self.max_windows);
+    self.grow(windows);
+
+    let powers = self.powers.read().unwrap();
+    let covered = windows * self.stride;
+    let val = if bits > covered {
+      // `n = lo + 2 ^ covered * hi`, and `powers[windows] = base ^ (2 ^ covered)`.
+      #[allow(clippy::cast_possible_truncation)]
+      let (lo, hi) = (
+        int(abs.keep_bits_ref(covered as u32)),
+        abs >> covered as u32,
+      );
+      G::op(
+        &self.exp_covered(&powers, &lo),
+        &G::exp(&powers[windows], &hi),
+      )
+    } else {
+      self.exp_covered(&powers, &abs)
+    };
+
+    if *n < int(0) {
+      G::inv(&val)
+    } else {
+      val
+    }
+  }
+
+  /// Extends the table so that `powers[windows]` exists.
+  fn grow(&self, windows: usize) {
+    if self.powers.read().unwrap().len() > windows {
+      return;
+    }
+    let mut powers = self.powers.write().unwrap();
+    let step = int(1) << self.stride as u32;
+    while powers.len() <= windows {
+      let next = G::exp(&powers[powers.len() - 1], &step);
+      powers.push(next);
+    }
+  }
+
+  /// Yao's method for a nonnegative `n` whose bits are all covered by `powers`.
+  fn exp_covered(&self, powers: &[G::Elem], n: &Integer) -> G::Elem {
+    let bits = n.significant_bits() as usize;
+    if bits == 0 {
+      return G::id();
+    }
+
+    // Windows must be a multiple of `stride` wide so that each one starts at a table entry.
+    let m = best_window_multiple(self.stride, bits);
+    let width = self.stride * m;
+    let limbs = n.to_digits::<u64>(Order::Lsf);
+
+    // `buckets[d - 1]` is the product of the table entries whose window has digit `d`.
+    let mut buckets: Vec<Option<G::Elem>> = vec![None; (1 << width) - 1];
+    for (j, start) in (0..bits).step_by(width).enumerate() {
+      let digit = window_at(&limbs, start, width);
+      if digit != 0 {
+        let bucket = &mut buckets[digit - 1];
+        *bucket = Some(op_or_clone::<G>(bucket.take(), &powers[j * m]));
+      }
+    }
+
+    // Computes the product of `bucket_d ^ d` with running products, highest digit first.
+    let mut running: Option<G::Elem> = None;
+    let mut result: Option<G::Elem> = None;
+    for bucket in buckets.iter().rev() {
+      if let Some(bucket) = bucket {
+        running = Some(op_or_clone::<G>(running, bucket));
+      }
+      if let Some(running) = &running {
+        result = Some(op_or_clone::<G>(result, running));
+      }
+    }
+    result.unwrap_or_else(G::id)
+  }
+}
+
+fn op_or_clone<G: Group>(acc: Option<G::Elem>, x: &G::Elem) -> G::Elem {
+  match acc {
+    Some(acc) => G::op(&acc, x),
+    None => x.clone(),
+  }
+}
+
+/// Picks the window width (as a multiple of `stride`) minimizing the op count for `bits`.
+fn best_window_multiple(stride: usize, bits: usize) -> usize {
+  let cost = |width: usize| bits.div_ceil(width) + (2 << width);
+  (1..)
+    .take_while(|m| m * stride <= MAX_WINDOW_BITS)
+    .min_by_key(|m| cost(m * stride))
+    .unwrap_or(1)
+}
+
+/// Returns the `width` bits of the little-endian `limbs` starting at bit `start`.
+#[allow(clippy::cast_possible_truncation)]
+fn window_at(limbs: &[u64], start: usize, width: usize) -> usize {
+  let (i, offset) = (start / 64, start % 64);
+  let lo = limbs.get(i).copied().unwrap_or(0) >> offset;
+  let hi = if offset + width > 64 {
+    limbs.get(i + 1).copied().unwrap_or(0) << (64 - offset)
+  } else {
+    0
+  };
+  ((lo | hi) & ((1 << width) - 1)) as usize
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::group::{ClassGroup, Rsa2048, UnknownOrderGroup};
+  use crate::hash::blake2b;
+
+  fn test_exponents() -> Vec<Integer> {
+    let big = blake2b("fixed base") * blake2b("table") * blake2b("exponent");
+    vec![
+      int(0),
+      int(1),
+      int(2),
+      int(255),
+      int(-7),
+      blake2b("fixed base"),
+      -big.clone(),
+      big,
+    ]
+  }
+
+  #[test]
+  fn test_exp_matches_group_exp() {
+    let g = Rsa2048::unknown_order_elem();
+    let configs = [
+      FixedBaseConfig::default(),
+      FixedBaseConfig {
+        stride: 1,
+        max_bits: 512,
+      },
+      // Small cap and odd stride exercise the fallback path and unaligned windows.
+      FixedBaseConfig {
+        stride: 3,
+        max_bits: 100,
+      },
+      FixedBaseConfig {
+        stride: 5,
+        max_bits: 0,
+      },
+    ];
+    for config in &configs {
+      let table = FixedBaseTable::<Rsa2048>::with_config(g.clone(), *config);
+      for n in test_exponents() {
+        assert_eq!(table.exp(&n), Rsa2048::exp(&g, &n));
+      }
+    }
+  }
+
+  #[test]
+  fn test_exp_unknown_order_elem() {
+    let n = blake2b("class group exponent");
+    assert_eq!(
+      Rsa2048::exp_unknown_order_elem(&n),
+      Rsa2048::exp(&Rsa2048::unknown_order_elem(), &n)
+    );
+    assert_eq!(
+      ClassGroup::exp_unknown_order_elem(&n),
+      ClassGroup::exp(&ClassGroup::unknown_order_elem(), &n)
+    );
+  }
+
+  #[test]
+  fn test_window_at() {
+    let limbs = [u64::MAX - 1, 0b101];
+    assert_eq!(window_at(&limbs, 0, 4), 0b1110);
+    assert_eq!(window_at(&limbs, 62, 4), 0b0111);
+    assert_eq!(window_at(&limbs, 64, 3), 0b101);
+    assert_eq!(window_at(&limbs, 128, 8), 0);
+  }
+}
@@ -4,14 +4,17 @@
 //!
 //! The preferred elliptic group implementation is the `Ristretto` group, which is a cyclic subset
 //! of the `Ed25519` group.
+use crate::bigint::Integer;
 use crate::util::{int, TypeRep};
-use rug::Integer;
-use std::fmt::Debug;
+use std::error;
+use std::fmt::{self, Debug};
 use std::hash::Hash;
 use std::marker::Sized;
 
 mod class;
 pub use class::{ClassElem, ClassGroup};
+mod fixed_base;
+pub use fixed_base::{fixed_base_config, set_fixed_base_config, FixedBaseConfig, FixedBaseTable};
 mod ristretto;
 pub use ristretto::{Ristretto, RistrettoElem};
 mod rsa;
@@ -72,9 +75,35 @@ pub trait Group: Clone + Debug + Eq + Hash + TypeRep + Send + Sync {
     val
   }
 
+  /// A group-specific wrapper for `exp_sec`. The default is a Montgomery ladder, which performs
+  /// the same sequence of group operations for every exponent of a given bit length. It is only as
+  /// side-channel resistant as `op_` itself, so groups should override it where they can do better.
+  fn exp_sec_(_rep: &Self::Rep, a: &Self::Elem, n: &Integer) -> Self::Elem {
+    let a = if *n < int(0) { Self::inv(a) } else { a.clone() };
+    let n = n.as_abs();
+    let (mut r0, mut r1) = (Self::id(), a);
+    for i in (0..n.significant_bits()).rev() {
+      if n.get_bit(i) {
+        r0 = Self::op(&r0, &r1);
+        r1 = Self::op(&r1, &r1);
+      } else {
+        r1 = Self::op(&r0, &r1);
+        r0 = Self::op(&r0, &r0);
+      }
+    }
+    r0
+  }
+
   /// A group-specific wrapper for `inv`.
   fn inv_(rep: &Self::Rep, a: &Self::Elem) -> Self::Elem;
 
+  /// A group-specific wrapper for `is_valid`. The default accepts every element, which is right
+  /// for groups whose element type can only hold elements of the group. Groups whose element type
+  /// has other values (e.g. non-units for RSA groups) must override it.
+  fn is_valid_(_rep: &Self::Rep, _a: &Self::Elem) -> bool {
+    true
+  }
+
   // -------------------
   // END OF REQUIRED FNS
   // -------------------
@@ -94,10 +123,24 @@ pub trait Group: Clone + Debug + Eq + Hash + TypeRep + Send + Sync {
     Self::exp_(Self::rep(), a, n)
   }
 
+  /// Like `exp`, but for secret exponents: the work done depends only on the sign and bit length
+  /// of `n`, not on its bits (see `exp_sec_`). Slower than `exp`, so use it only when `n` must stay
+  /// private, e.g. the witness in `Poke2::prove`.
+  fn exp_sec(a: &Self::Elem, n: &Integer) -> Self::Elem {
+    Self::exp_sec_(Self::rep(), a, n)
+  }
+
   /// Returns the group inverse of `a`.
   fn inv(a: &Self::Elem) -> Self::Elem {
     Self::inv_(Self::rep(), a)
   }
+
+  /// Returns whether `a` is an element of the group. Group operations on valid elements always
+  /// yield valid elements, so only elements that come from outside the process (e.g. in proofs
+  /// received from peers) need checking. Proof verifiers do this for their inputs.
+  fn is_valid(a: &Self::Elem) -> bool {
+    Self::is_valid_(Self::rep(), a)
+  }
 }
 
 /// A group containing elements of unknown order.
@@ -112,6 +155,17 @@ pub trait UnknownOrderGroup: Group {
 
   /// A group-specific wrapper for `unknown_order_elem`.
   fn unknown_order_elem_(rep: &Self::Rep) -> Self::Elem;
+
+  /// Raises `unknown_order_elem` to the power `n`.
+  fn exp_unknown_order_elem(n: &Integer) -> Self::Elem {
+    Self::exp_unknown_order_elem_(Self::rep(), n)
+  }
+
+  /// A group-specific wrapper for `exp_unknown_order_elem`. Defaults to `exp`, but groups should
+  /// override this with a lookup into a `FixedBaseTable` where memory allows.
+  fn exp_unknown_order_elem_(_rep: &Self::Rep, n: &Integer) -> Self::Elem {
+    Self::exp(&Self::unknown_order_elem(), n)
+  }
 }
 
 /// Like `From<T>`, but implemented on the `Group` instead of the element type.
@@ -120,6 +174,44 @@ pub trait ElemFrom<T>: Group {
   fn elem(val: T) -> Self::Elem;
 }
 
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+/// The reasons a value may not represent a group element.
+pub enum ElemError {
+  /// An integer outside the range of representatives, e.g. `[0, N)` for an RSA modulus `N`.
+  OutOfRange,
+  /// An integer with no inverse modulo the group's modulus, e.g. zero.
+  NotUnit,
+  /// A form whose discriminant is not the group's.
+  WrongDiscriminant,
+  /// A form that is not reduced.
+  NotReduced,
+  /// A form whose coefficients share a factor.
+  NotPrimitive,
+}
+
+impl fmt::Display for ElemError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let msg = match self {
+      ElemError::OutOfRange => "integer is out of range for the group",
+      ElemError::NotUnit => "integer is not a unit modulo the group's modulus",
+      ElemError::WrongDiscriminant => "form does not have the group's discriminant",
+      ElemError::NotReduced => "form is not reduced",
+      ElemError::NotPrimitive => "form is not primitive",
+    };
+    f.write_str(msg)
+  }
+}
+
+impl error::Error for ElemError {}
+
+/// Like `TryFrom<T>`, but implemented on the `Group` instead of the element type. Unlike
+/// `ElemFrom`, which maps any value to some element, this rejects values that do not represent an
+/// element of the group. Use it for values that come from outside the process.
+pub trait TryElemFrom<T>: Group {
+  /// Returns a group element from an initial value, or why the value does not represent one.
+  fn try_elem(val: T) -> Result<Self::Elem, ElemError>;
+}
+
 /// Computes the product of `alpha_i ^ (p(x) / x_i)`, where `i` is an index into the `alphas` and
 /// `x` arrays, and `p(x)` is the product of all `x_i`. See BBF (page 11).
 pub fn multi_exp<G: Group>(alphas: &[G::Elem], x: &[Integer]) -> G::Elem {
@@ -139,6 +231,38 @@ pub fn multi_exp<G: Group>(alphas: &[G::Elem], x: &[Integer]) -> G::Elem {
   G::op(&G::exp(&l, &x_star_r), &G::exp(&r, &x_star_l))
 }
 
+/// Computes the product of `base_i ^ exp_i` over all `terms` by simultaneous exponentiation
+/// (Straus), which shares one chain of squarings between all of the bases. For `k` terms with
+/// `b`-bit exponents this takes about `b` squarings rather than the `k * b` of exponentiating each
+/// base on its own. Negative exponents apply to the inverse of their base.
+pub fn exp_product<G: Group>(terms: &[(G::Elem, Integer)]) -> G::Elem {
+  let terms: Vec<(G::Elem, Integer)> = terms
+    .iter()
+    .map(|(base, exp)| {
+      if *exp < int(0) {
+        (G::inv(base), int(-exp))
+      } else {
+        (base.clone(), exp.clone())
+      }
+    })
+    .collect();
+  let bits = terms
+    .iter()
+    .map(|(_, exp)| exp.significant_bits())
+    .max()
+    .unwrap_or(0);
+  let mut val = G::id();
+  for i in (0..bits).rev() {
+    val = G::op(&val, &val);
+    for (base, exp) in &terms {
+      if exp.get_bit(i) {
+        val = G::op(&val, base);
+      }
+    }
+  }
+  val
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -160,4 +284,29 @@ mod tests {
     let res_2 = multi_exp::<Rsa2048>(&[alpha_1, alpha_2, alpha_3], &[x_1, x_2, x_3]);
     assert!(res_2 == Rsa2048::elem(1_687_500));
   }
+
+  #[test]
+  fn test_exp_product() {
+    // 2^10 * 3^-2 * 5^0 * 7^1
+    let terms = [
+      (Rsa2048::elem(2), int(10)),
+      (Rsa2048::elem(3), int(-2)),
+      (Rsa2048::elem(5), int(0)),
+      (Rsa2048::elem(7), int(1)),
+    ];
+    let expected = terms.iter().fold(Rsa2048::id(), |val, (base, exp)| {
+      Rsa2048::op(&val, &Rsa2048::exp(base, exp))
+    });
+    assert!(exp_product::<Rsa2048>(&terms) == expected);
+    assert!(exp_product::<Rsa2048>(&terms[..1]) == Rsa2048::elem(1024));
+    assert!(exp_product::<Rsa2048>(&[]) == Rsa2048::id());
+  }
+
+  #[test]
+  fn test_exp_sec_default() {
+    let g = ClassGroup::unknown_order_elem();
+    for n in &[int(0), int(1), int(2), int(1_000_003), -int(77)] {
+      assert_eq!(ClassGroup::exp_sec(&g, n), ClassGroup::exp(&g, n));
+    }
+  }
 }
@@ -1,12 +1,12 @@
 //! Ristretto group implementation (based on the `curve25519-dalek` crate).
 use super::Group;
+use crate::bigint::Integer;
+use crate::bigint::Order;
+use crate::bigint::Pow;
 use crate::util::{int, TypeRep};
 use curve25519_dalek::ristretto::RistrettoPoint;
 use curve25519_dalek::scalar::Scalar;
 use curve25519_dalek::traits::Identity;
-use rug::integer::Order;
-use rug::ops::Pow;
-use rug::Integer;
 use std::hash::{Hash, Hasher};
 
 #[allow(clippy::module_name_repetitions)]
@@ -67,6 +67,11 @@ impl Group for Ristretto {
     RistrettoElem(-x.0)
   }
 
+  // Points are only ever constructed by group operations here, or validated on decompression.
+  fn is_valid_(_: &(), _: &RistrettoElem) -> bool {
+    true
+  }
+
   fn exp_(_: &(), x: &RistrettoElem, n: &Integer) -> RistrettoElem {
     let mut remaining = n.clone();
     let mut result = Self::id();
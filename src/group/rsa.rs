@@ -1,7 +1,11 @@
-//! RSA (2048) group using GMP integers in the `rug` crate.
-use super::{ElemFrom, Group, UnknownOrderGroup};
+//! RSA (2048) group using fixed-width Montgomery arithmetic over GMP `mpn_` functions.
+use super::{ElemError, ElemFrom, FixedBaseTable, Group, TryElemFrom, UnknownOrderGroup};
+use crate::bigint::Integer;
+use crate::store::Persist;
+use crate::uint::U2048;
 use crate::util::{int, TypeRep};
-use rug::Integer;
+use std::fmt::{self, Debug, Formatter};
+use std::hash::{Hash, Hasher};
 use std::str::FromStr;
 
 #[allow(clippy::module_name_repetitions)]
@@ -22,15 +26,127 @@ const RSA2048_MODULUS_DECIMAL: &str =
   0924616515723350778707749817125772467962926386356373289912154831438167899885040445364023527381951\
   378636564391212010397122822120720357";
 
+/// Largest sliding window used by `exp`. Bounds the table of odd powers to `2^(MAX_WINDOW_BITS - 1)`
+/// elements, which lives on the stack.
+const MAX_WINDOW_BITS: u32 = 7;
+
+/// Window size used by `exp_sec`, which always reads a table of `2^SEC_WINDOW_BITS` elements.
+const SEC_WINDOW_BITS: u32 = 5;
+
+/// Montgomery constants for `RSA2048_MODULUS` with `R = 2^2048`.
+struct Montgomery {
+  modulus: U2048,
+  half_modulus: U2048,
+  // `-modulus^-1 mod 2^64`.
+  m_inv: u64,
+  // `R mod N`, i.e. 1 in Montgomery form.
+  one: U2048,
+  // `R^2 mod N`, for converting into Montgomery form.
+  r2: U2048,
+  // `R^3 mod N`, for converting inverses back into Montgomery form.
+  r3: U2048,
+}
+
 lazy_static! {
   pub static ref RSA2048_MODULUS: Integer = Integer::from_str(RSA2048_MODULUS_DECIMAL).unwrap();
   pub static ref HALF_MODULUS: Integer = RSA2048_MODULUS.clone() / 2;
+  static ref MONTGOMERY: Montgomery = {
+    let n = &*RSA2048_MODULUS;
+    let r: Integer = int(1) << 2048;
+    let modulus = U2048::from(n);
+    Montgomery {
+      half_modulus: U2048::from(&*HALF_MODULUS),
+      m_inv: modulus.mont_inv_limb(),
+      one: U2048::from(&int(&r % n)),
+      r2: U2048::from(&(int(r.square_ref()) % n)),
+      r3: U2048::from(&(r.pow_mod(&int(3), n).unwrap())),
+      modulus,
+    }
+  };
+  static ref UNKNOWN_ORDER_TABLE: FixedBaseTable<Rsa2048> =
+    FixedBaseTable::new(Rsa2048::unknown_order_elem());
 }
 
 #[allow(clippy::module_name_repetitions)]
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
-/// An RSA 2048 group element, directly wrapping a GMP integer from the `rug` crate.
-pub struct Rsa2048Elem(Integer);
+#[derive(Clone, PartialEq, Eq)]
+/// An RSA 2048 group element, stored in Montgomery form as a fixed-width integer so that group
+/// operations never touch the heap. Since `x` and `-x` are the same element, only the smaller of
+/// their two Montgomery representatives is stored.
+pub struct Rsa2048Elem(U2048);
+
+impl Rsa2048Elem {
+  /// Wraps a Montgomery-form value, picking the canonical representative of `x` and `-x`.
+  fn from_mont(x: U2048) -> Self {
+    let mont = &*MONTGOMERY;
+    if x > mont.half_modulus {
+      Self(mont.modulus - x)
+    } else {
+      Self(x)
+    }
+  }
+
+  /// Returns the canonical representative of this element in `[0, N / 2]`, outside of Montgomery
+  /// form. This is what `Debug` prints and what `Hash` hashes.
+  fn to_integer(&self) -> Integer {
+    let mont = &*MONTGOMERY;
+    let x = self.0.mont_mul(&U2048::one(), &mont.modulus, mont.m_inv);
+    if x > mont.half_modulus {
+      Integer::from(mont.modulus - x)
+    } else {
+      Integer::from(x)
+    }
+  }
+}
+
+impl Debug for Rsa2048Elem {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    f.debug_tuple("Rsa2048Elem")
+      .field(&self.to_integer())
+      .finish()
+  }
+}
+
+// Hashes the plain integer representative, so that proof challenges do not depend on the internal
+// representation.
+impl Hash for Rsa2048Elem {
+  fn hash<H: Hasher>(&self, state: &mut H) {
+    self.to_integer().hash(state);
+  }
+}
+
+/// Encoded as the canonical representative in `[0, N / 2]`, outside of Montgomery form. Decoding
+/// rejects anything else, as well as non-units.
+impl Persist for Rsa2048Elem {
+  fn encode(&self, out: &mut Vec<u8>) {
+    self.to_integer().encode(out);
+  }
+
+  fn decode(bytes: &mut &[u8]) -> Option<Self> {
+    let x = Integer::decode(bytes)?;
+    if x > *HALF_MODULUS {
+      return None;
+    }
+    Rsa2048::try_elem(x).ok()
+  }
+}
+
+impl Rsa2048 {
+  /// Like `inv`, but fails with `ElemError::NotUnit` for non-units such as `elem(0)`, which have
+  /// no inverse.
+  pub fn try_inv(x: &Rsa2048Elem) -> Result<Rsa2048Elem, ElemError> {
+    let mont = &*MONTGOMERY;
+    // For `x = aR`, we have `x^-1 = a^-1 R^-1`, and multiplying by `R^3` in Montgomery form gives
+    // back `a^-1 R`. Inversion is rare enough that we let GMP allocate here.
+    let x = Integer::from(x.0);
+    let x_inv = x.invert_ref(Self::rep()).ok_or(ElemError::NotUnit)?;
+    let x_inv = U2048::from(&int(x_inv));
+    Ok(Rsa2048Elem::from_mont(x_inv.mont_mul(
+      &mont.r3,
+      &mont.modulus,
+      mont.m_inv,
+    )))
+  }
+}
 
 impl TypeRep for Rsa2048 {
   type Rep = Integer;
@@ -41,36 +157,152 @@ impl TypeRep for Rsa2048 {
 
 impl Group for Rsa2048 {
   type Elem = Rsa2048Elem;
-  fn op_(modulus: &Integer, a: &Rsa2048Elem, b: &Rsa2048Elem) -> Rsa2048Elem {
-    Self::elem(int(&a.0 * &b.0) % modulus)
+  fn op_(_: &Integer, a: &Rsa2048Elem, b: &Rsa2048Elem) -> Rsa2048Elem {
+    let mont = &*MONTGOMERY;
+    Rsa2048Elem::from_mont(a.0.mont_mul(&b.0, &mont.modulus, mont.m_inv))
   }
 
   fn id_(_: &Integer) -> Rsa2048Elem {
-    Self::elem(1)
+    Rsa2048Elem::from_mont(MONTGOMERY.one)
+  }
+
+  /// Panics if `x` is not a unit. Short of factoring the modulus, the only non-unit anyone can
+  /// produce is zero, which decoding and the proof verifiers reject. Use `Rsa2048::try_inv` where
+  /// `x` may be zero.
+  fn inv_(_: &Integer, x: &Rsa2048Elem) -> Rsa2048Elem {
+    Self::try_inv(x).expect("non-units have no inverse")
   }
 
-  fn inv_(modulus: &Integer, x: &Rsa2048Elem) -> Rsa2048Elem {
-    Self::elem(x.0.invert_ref(modulus).unwrap())
+  fn is_valid_(modulus: &Integer, x: &Rsa2048Elem) -> bool {
+    let x = x.to_integer();
+    x != 0 && int(x.gcd_ref(modulus)) == 1
   }
 
-  fn exp_(modulus: &Integer, x: &Rsa2048Elem, n: &Integer) -> Rsa2048Elem {
-    // A side-channel resistant impl is 40% slower; we'll consider it in the future if we need to.
-    Self::elem(x.0.pow_mod_ref(n, modulus).unwrap())
+  fn exp_(_: &Integer, x: &Rsa2048Elem, n: &Integer) -> Rsa2048Elem {
+    // Variable-time. Secret exponents should go through `exp_sec` instead.
+    let base = if *n < 0 { Self::inv(x) } else { x.clone() };
+    Rsa2048Elem::from_mont(mont_pow(&base.0, n))
+  }
+
+  fn exp_sec_(_: &Integer, x: &Rsa2048Elem, n: &Integer) -> Rsa2048Elem {
+    let base = if *n < 0 { Self::inv(x) } else { x.clone() };
+    Rsa2048Elem::from_mont(mont_pow_sec(&base.0, n))
   }
 }
 
+/// Sliding-window exponentiation in Montgomery form by `|n|`.
+fn mont_pow(base: &U2048, n: &Integer) -> U2048 {
+  let mont = &*MONTGOMERY;
+  let (m, m_inv) = (&mont.modulus, mont.m_inv);
+  let n = n.as_abs();
+  let bits = n.significant_bits();
+  if bits == 0 {
+    return mont.one;
+  }
+
+  // Odd powers `base^1, base^3, ..., base^(2^w - 1)`.
+  let w = window_bits(bits);
+  let mut odd_powers = [U2048::zero(); 1 << (MAX_WINDOW_BITS - 1)];
+  odd_powers[0] = *base;
+  let base_sqr = base.mont_sqr(m, m_inv);
+  for i in 1..1 << (w - 1) {
+    odd_powers[i] = odd_powers[i - 1].mont_mul(&base_sqr, m, m_inv);
+  }
+
+  // Scans from the top bit. Each window starts and ends on a set bit, so its value is odd.
+  let mut acc: Option<U2048> = None;
+  let mut top = bits;
+  while top > 0 {
+    if !n.get_bit(top - 1) {
+      acc = acc.map(|acc| acc.mont_sqr(m, m_inv));
+      top -= 1;
+      continue;
+    }
+    let mut bottom = top.saturating_sub(w);
+    while !n.get_bit(bottom) {
+      bottom += 1;
+    }
+    let mut window = 0;
+    for i in (bottom..top).rev() {
+      window = (window << 1) | usize::from(n.get_bit(i));
+    }
+    let odd_power = &odd_powers[window >> 1];
+    acc = Some(match acc {
+      Some(mut acc) => {
+        for _ in bottom..top {
+          acc = acc.mont_sqr(m, m_inv);
+        }
+        acc.mont_mul(odd_power, m, m_inv)
+      }
+      None => *odd_power,
+    });
+    top = bottom;
+  }
+  acc.unwrap_or(mont.one)
+}
+
+/// Fixed-window exponentiation in Montgomery form by `|n|`, for secret `n`. Every window costs the
+/// same squarings and one multiplication by a table entry read with `U2048::sec_select`, so the
+/// timing and memory accesses depend only on the bit length of `n`.
+fn mont_pow_sec(base: &U2048, n: &Integer) -> U2048 {
+  let mont = &*MONTGOMERY;
+  let (m, m_inv) = (&mont.modulus, mont.m_inv);
+  let n = n.as_abs();
+
+  // All powers `base^0, base^1, ..., base^(2^w - 1)`.
+  let mut powers = [U2048::zero(); 1 << SEC_WINDOW_BITS];
+  powers[0] = mont.one;
+  for i in 1..powers.len() {
+    powers[i] = powers[i - 1].mont_mul_sec(base, m, m_inv);
+  }
+
+  let mut acc = mont.one;
+  let windows = n.significant_bits().div_ceil(SEC_WINDOW_BITS);
+  for i in (0..windows).rev() {
+    let mut window = 0;
+    for j in (0..SEC_WINDOW_BITS).rev() {
+      acc = acc.mont_mul_sec(&acc, m, m_inv);
+      window = (window << 1) | usize::from(n.get_bit(i * SEC_WINDOW_BITS + j));
+    }
+    acc = acc.mont_mul_sec(&U2048::sec_select(&powers, window), m, m_inv);
+  }
+  acc
+}
+
+/// Picks the window size minimizing table setup plus multiplications for a `bits`-bit exponent.
+fn window_bits(bits: u32) -> u32 {
+  (1..=MAX_WINDOW_BITS)
+    .min_by_key(|w| (1 << (w - 1)) + bits / (w + 1))
+    .unwrap()
+}
+
 impl<T> ElemFrom<T> for Rsa2048
 where
   Integer: From<T>,
 {
   fn elem(t: T) -> Rsa2048Elem {
     let modulus = Self::rep();
-    let val = int(t) % modulus;
-    if val > *HALF_MODULUS {
-      Rsa2048Elem(<(Integer, Integer)>::from((-val).div_rem_euc_ref(&modulus)).1)
-    } else {
-      Rsa2048Elem(val)
+    let val = <(Integer, Integer)>::from(int(t).div_rem_euc_ref(modulus)).1;
+    let mont = &*MONTGOMERY;
+    Rsa2048Elem::from_mont(U2048::from(&val).mont_mul(&mont.r2, &mont.modulus, mont.m_inv))
+  }
+}
+
+/// Accepts integers in `[0, N)` that are units modulo `N`.
+impl<T> TryElemFrom<T> for Rsa2048
+where
+  Integer: From<T>,
+{
+  fn try_elem(t: T) -> Result<Rsa2048Elem, ElemError> {
+    let val = int(t);
+    if val < 0 || val >= *Self::rep() {
+      return Err(ElemError::OutOfRange);
+    }
+    let elem = <Self as ElemFrom<Integer>>::elem(val);
+    if !Self::is_valid(&elem) {
+      return Err(ElemError::NotUnit);
     }
+    Ok(elem)
   }
 }
 
@@ -78,6 +310,10 @@ impl UnknownOrderGroup for Rsa2048 {
   fn unknown_order_elem_(_: &Integer) -> Rsa2048Elem {
     Self::elem(2)
   }
+
+  fn exp_unknown_order_elem_(_: &Integer, n: &Integer) -> Rsa2048Elem {
+    UNKNOWN_ORDER_TABLE.exp(n)
+  }
 }
 
 #[cfg(test)]
@@ -129,10 +365,75 @@ mod tests {
     dbg!(d);
   }
 
+  /// Compares Montgomery arithmetic against GMP's `mpz` functions.
+  #[test]
+  fn test_matches_integer_arithmetic() {
+    let a = int(3).pow_mod(&int(1_000_003), &RSA2048_MODULUS).unwrap();
+    let b = int(RSA2048_MODULUS.clone() - 12_345);
+    let exps = [
+      int(0),
+      int(1),
+      int(65_537),
+      -int(65_537),
+      int(&*RSA2048_MODULUS * 7),
+    ];
+    for n in &exps {
+      let expected = int(a.pow_mod_ref(n, &RSA2048_MODULUS).unwrap());
+      assert_eq!(
+        Rsa2048::exp(&Rsa2048::elem(a.clone()), n),
+        Rsa2048::elem(expected.clone())
+      );
+      assert_eq!(
+        Rsa2048::exp_sec(&Rsa2048::elem(a.clone()), n),
+        Rsa2048::elem(expected)
+      );
+    }
+    assert_eq!(
+      Rsa2048::op(&Rsa2048::elem(a.clone()), &Rsa2048::elem(b.clone())),
+      Rsa2048::elem(a * b % &*RSA2048_MODULUS)
+    );
+  }
+
+  #[test]
+  fn test_hash_and_debug_use_plain_integer() {
+    let x = Rsa2048::elem(RSA2048_MODULUS.clone() - 3);
+    assert_eq!(format!("{:?}", x), "Rsa2048Elem(3)");
+    assert_eq!(hash_of(&x), hash_of(&int(3)));
+  }
+
+  fn hash_of<T: Hash>(t: &T) -> u64 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    t.hash(&mut hasher);
+    hasher.finish()
+  }
+
   #[test]
   fn test_inv() {
     let x = Rsa2048::elem(2);
     let inv = Rsa2048::inv(&x);
     assert!(Rsa2048::op(&x, &inv) == Rsa2048::id());
   }
+
+  #[test]
+  fn test_try_elem() {
+    assert!(Rsa2048::try_elem(2) == Ok(Rsa2048::elem(2)));
+    assert!(Rsa2048::try_elem(-2) == Err(ElemError::OutOfRange));
+    assert!(Rsa2048::try_elem(Rsa2048::rep().clone()) == Err(ElemError::OutOfRange));
+    assert!(Rsa2048::try_elem(0) == Err(ElemError::NotUnit));
+    assert!(Rsa2048::is_valid(&Rsa2048::elem(2)));
+    assert!(!Rsa2048::is_valid(&Rsa2048::elem(0)));
+  }
+
+  #[test]
+  fn test_try_inv() {
+    let x = Rsa2048::elem(2);
+    assert!(Rsa2048::try_inv(&x) == Ok(Rsa2048::inv(&x)));
+    assert!(Rsa2048::try_inv(&Rsa2048::elem(0)) == Err(ElemError::NotUnit));
+  }
+
+  #[should_panic(expected = "non-units have no inverse")]
+  #[test]
+  fn test_inv_zero() {
+    let _ = Rsa2048::exp(&Rsa2048::elem(0), &int(-3));
+  }
 }
@@ -1,9 +1,9 @@
 //! This module wraps `blake2b_rfc` into a convenient hashing interface (`GeneralHasher`) and
 //! exports the generalized `hash` function. Also exported is `hash_to_prime`, which works by
 //! repeatedly `hash`ing a value together with an incrementing nonce until the output is prime.
+use crate::bigint::Integer;
+use crate::bigint::Order;
 use crate::uint::u256;
-use rug::integer::Order;
-use rug::Integer;
 use std::hash::{Hash, Hasher};
 
 mod blake2b;
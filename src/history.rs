@@ -0,0 +1,600 @@
+//! Accumulator history, for light clients that need to catch up on many epochs at once.
+//!
+//! An `AccumulatorHistory` records the accumulator value at every epoch, along with the
+//! `CatchUpProof` for the update that produced it: a deletion proof from `delete_with_proof` and an
+//! addition proof from `add_with_proof`, which share the accumulator in between as their witness.
+//!
+//! Any range of epochs compresses into a single `CatchUpProof` of the same shape, covering only the
+//! net deletions and net additions over the range. Elements that were added and deleted again
+//! within the range drop out, as do elements that were deleted and added back. Verifying it takes
+//! one `Poe` check for each of the two, however many epochs the range spans.
+//!
+//! For audits, a history can also track the element set, which lets it prove membership and
+//! nonmembership against the accumulator at any past epoch it still holds. To bound storage, it
+//! keeps full snapshots of the set only every few epochs and rebuilds the others from the epoch
+//! proofs, and it can drop epochs past a configurable age (see `RetentionConfig`).
+use crate::accumulator::{AccError, Accumulator, MembershipProof, NonmembershipProof, Witness};
+use crate::bigint::Integer;
+use crate::group::UnknownOrderGroup;
+use crate::util::{int, prime_hash_product, PrimeHashable};
+use std::collections::{BTreeMap, HashSet};
+use std::hash::Hash;
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+/// A proof that an accumulator went from an old value to a new one by deleting `deletions` and then
+/// adding `additions`.
+pub struct CatchUpProof<G: UnknownOrderGroup, T: Hash> {
+  deletions: Vec<T>,
+  additions: Vec<T>,
+  // Membership of the deletions in the old accumulator and of the additions in the new one. Both
+  // share the same witness: the accumulator in between.
+  deletion_proof: MembershipProof<G, T>,
+  addition_proof: MembershipProof<G, T>,
+}
+
+impl<G: UnknownOrderGroup, T: Clone + Eq + PrimeHashable> CatchUpProof<G, T> {
+  /// Returns the elements deleted between the old and the new accumulator.
+  pub fn deletions(&self) -> &[T] {
+    &self.deletions
+  }
+
+  /// Returns the elements added between the old and the new accumulator.
+  pub fn additions(&self) -> &[T] {
+    &self.additions
+  }
+
+  /// Deletes `deletions` from `acc` and then adds `additions`, given a batch membership proof of
+  /// the `deletions` in `acc` (e.g. one aggregated from several parties' proofs). Returns the new
+  /// accumulator along with a proof of the update.
+  pub fn prove_update(
+    acc: &Accumulator<G, T>,
+    deletions: &[T],
+    deletion_proof: MembershipProof<G, T>,
+    additions: &[T],
+  ) -> Result<(Accumulator<G, T>, Self), AccError> {
+    if !acc.verify_membership_batch(deletions, &deletion_proof) {
+      return Err(AccError::BadProof);
+    }
+    let (new_acc, addition_proof) = deletion_proof.witness.0.clone().add_with_proof(additions);
+    let proof = Self {
+      deletions: deletions.to_vec(),
+      additions: additions.to_vec(),
+      deletion_proof,
+      addition_proof,
+    };
+    Ok((new_acc, proof))
+  }
+
+  /// Returns the witness shared by both parts of the proof: a witness for the deletions in the old
+  /// accumulator, and for the additions in the new one.
+  pub fn addition_witness(&self) -> &Witness<G, T> {
+    &self.addition_proof.witness
+  }
+
+  /// Verifies that `new_acc` follows from `old_acc` by the deletions and additions of this proof.
+  pub fn verify(&self, old_acc: &Accumulator<G, T>, new_acc: &Accumulator<G, T>) -> bool {
+    self.deletion_proof.witness == self.addition_proof.witness
+      && old_acc.verify_membership_batch(&self.deletions, &self.deletion_proof)
+      && new_acc.verify_membership_batch(&self.additions, &self.addition_proof)
+  }
+}
+
+// Distinct elements in the order they were inserted, along with the product of their prime hashes.
+struct NetElems<T> {
+  elems: Vec<T>,
+  index: HashSet<T>,
+  x: Integer,
+}
+
+impl<T: Clone + Eq + PrimeHashable> NetElems<T> {
+  fn new() -> Self {
+    Self {
+      elems: vec![],
+      index: HashSet::new(),
+      x: int(1),
+    }
+  }
+
+  /// Splits `elems` into those that are present and those that are not.
+  fn partition(&self, elems: &[T]) -> (Vec<T>, Vec<T>) {
+    elems
+      .iter()
+      .cloned()
+      .partition(|elem| self.index.contains(elem))
+  }
+
+  /// Inserts `elems`, which are not present, with prime hash product `x`.
+  fn insert(&mut self, elems: Vec<T>, x: &Integer) {
+    self.index.extend(elems.iter().cloned());
+    self.elems.extend(elems);
+    self.x *= x;
+  }
+
+  /// Removes `elems`, which are present, with prime hash product `x`.
+  fn remove(&mut self, elems: &[T], x: &Integer) {
+    for elem in elems {
+      self.index.remove(elem);
+    }
+    let index = &self.index;
+    self.elems.retain(|elem| index.contains(elem));
+    self.x = int(&self.x / x);
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+/// How much of its past an `AccumulatorHistory` keeps.
+pub struct RetentionConfig {
+  /// Number of past epochs to keep, besides the current one. `None` keeps all of them. A history
+  /// that tracks element sets prunes in steps of up to `snapshot_interval` epochs, so it may keep
+  /// up to `snapshot_interval - 1` more.
+  pub max_epochs: Option<u64>,
+  /// Number of epochs between snapshots of the element set, for histories that track it. The set
+  /// at any other epoch is rebuilt from the closest earlier snapshot by replaying fewer than this
+  /// many epochs.
+  pub snapshot_interval: u64,
+}
+
+impl Default for RetentionConfig {
+  /// Keeps every epoch, with a snapshot of the element set every 64 epochs.
+  fn default() -> Self {
+    Self {
+      max_epochs: None,
+      snapshot_interval: 64,
+    }
+  }
+}
+
+// The element set at the current epoch, and snapshots of it at past epochs.
+struct ElemSets<T> {
+  current: HashSet<T>,
+  snapshots: BTreeMap<u64, Vec<T>>,
+}
+
+/// The values of an accumulator at every epoch since genesis (epoch 0), or at the most recent
+/// epochs as set by a `RetentionConfig`, with a proof for each transition. See the module
+/// documentation.
+///
+/// Histories created by `with_elems` also track the element set, and can prove membership and
+/// nonmembership at any retained epoch.
+pub struct AccumulatorHistory<G: UnknownOrderGroup, T: Hash> {
+  // The oldest retained epoch.
+  offset: u64,
+  values: Vec<Accumulator<G, T>>,
+  // `proofs[i]` takes `values[i]` to `values[i + 1]`.
+  proofs: Vec<CatchUpProof<G, T>>,
+  elem_sets: Option<ElemSets<T>>,
+  retention: RetentionConfig,
+}
+
+impl<G: UnknownOrderGroup, T: Clone + Eq + PrimeHashable> AccumulatorHistory<G, T> {
+  /// Starts a history at the `genesis` accumulator.
+  pub fn new(genesis: Accumulator<G, T>) -> Self {
+    Self {
+      offset: 0,
+      values: vec![genesis],
+      proofs: vec![],
+      elem_sets: None,
+      retention: RetentionConfig::default(),
+    }
+  }
+
+  /// Starts a history at the accumulator of `elems`, which also tracks the element set.
+  pub fn with_elems(elems: &[T]) -> Self {
+    let mut history = Self::new(Accumulator::from(elems));
+    let mut snapshots = BTreeMap::new();
+    snapshots.insert(0, elems.to_vec());
+    history.elem_sets = Some(ElemSets {
+      current: elems.iter().cloned().collect(),
+      snapshots,
+    });
+    history
+  }
+
+  /// Sets how much of its past the history keeps, and prunes it accordingly.
+  ///
+  /// Panics if `retention.snapshot_interval` is zero.
+  pub fn set_retention(&mut self, retention: RetentionConfig) {
+    assert!(
+      retention.snapshot_interval > 0,
+      "Snapshot interval must be positive."
+    );
+    self.retention = retention;
+    self.prune();
+  }
+
+  /// Returns the current epoch.
+  pub fn epoch(&self) -> u64 {
+    self.offset + self.proofs.len() as u64
+  }
+
+  /// Returns the oldest epoch the history holds.
+  pub fn oldest_epoch(&self) -> u64 {
+    self.offset
+  }
+
+  /// Returns the accumulator at the current epoch.
+  pub fn current(&self) -> &Accumulator<G, T> {
+    self.values.last().unwrap()
+  }
+
+  /// Returns the accumulator at `epoch`, if the history holds it.
+  pub fn value(&self, epoch: u64) -> Option<&Accumulator<G, T>> {
+    let i = epoch.checked_sub(self.offset)?;
+    self.values.get(i as usize)
+  }
+
+  /// Returns the proof for the update that produced `epoch`, if the history holds it.
+  pub fn epoch_proof(&self, epoch: u64) -> Option<&CatchUpProof<G, T>> {
+    let i = epoch.checked_sub(self.offset + 1)?;
+    self.proofs.get(i as usize)
+  }
+
+  /// Returns the element set at `epoch`, in no particular order. Fails with
+  /// `AccError::UnknownEpoch` if the history does not hold `epoch` or does not track element sets.
+  pub fn elems_at(&self, epoch: u64) -> Result<Vec<T>, AccError> {
+    let elem_sets = self
+      .elem_sets
+      .as_ref()
+      .ok_or(AccError::UnknownEpoch { epoch })?;
+    if epoch < self.offset || epoch > self.epoch() {
+      return Err(AccError::UnknownEpoch { epoch });
+    }
+    let (&snapshot_epoch, snapshot) = elem_sets
+      .snapshots
+      .range(..=epoch)
+      .next_back()
+      .ok_or(AccError::UnknownEpoch { epoch })?;
+    let mut elems: HashSet<T> = snapshot.iter().cloned().collect();
+    for e in snapshot_epoch + 1..=epoch {
+      let proof = self
+        .epoch_proof(e)
+        .ok_or(AccError::UnknownEpoch { epoch: e })?;
+      for elem in &proof.deletions {
+        elems.remove(elem);
+      }
+      elems.extend(proof.additions.iter().cloned());
+    }
+    Ok(elems.into_iter().collect())
+  }
+
+  /// Proves that `elems` were members of the accumulator at `epoch`, i.e. `value(epoch)`. Needs a
+  /// history that tracks element sets.
+  pub fn prove_membership_at(
+    &self,
+    epoch: u64,
+    elems: &[T],
+  ) -> Result<MembershipProof<G, T>, AccError> {
+    let mut remaining: HashSet<T> = self.elems_at(epoch)?.into_iter().collect();
+    for (index, elem) in elems.iter().enumerate() {
+      if !remaining.remove(elem) {
+        return Err(AccError::UnknownElem { index });
+      }
+    }
+    let others: Vec<T> = remaining.into_iter().collect();
+    let witness = Witness(Accumulator::from(&others[..]));
+    let acc = self.value(epoch).ok_or(AccError::UnknownEpoch { epoch })?;
+    Ok(acc.prove_membership_with_witness(elems, witness))
+  }
+
+  /// Proves that `elems` were not members of the accumulator at `epoch`, i.e. `value(epoch)`. Needs
+  /// a history that tracks element sets.
+  pub fn prove_nonmembership_at(
+    &self,
+    epoch: u64,
+    elems: &[T],
+  ) -> Result<NonmembershipProof<G, T>, AccError> {
+    let acc_set = self.elems_at(epoch)?;
+    let acc = self.value(epoch).ok_or(AccError::UnknownEpoch { epoch })?;
+    acc.prove_nonmembership(&acc_set, elems)
+  }
+
+  /// Starts a new epoch by deleting the elements in `elem_witnesses` from the current accumulator
+  /// and then adding `additions`. Returns the new epoch.
+  ///
+  /// # Arguments
+  ///
+  /// * `elem_witnesses` - Tuples consisting of (element to delete, element's witness).
+  /// * `additions` - Elements to add.
+  pub fn update(
+    &mut self,
+    elem_witnesses: &[(T, Witness<G, T>)],
+    additions: &[T],
+  ) -> Result<u64, AccError> {
+    let deletions: Vec<T> = elem_witnesses
+      .iter()
+      .map(|(elem, _)| elem.clone())
+      .collect();
+    let deletion_proof = self.current().prove_membership(elem_witnesses)?;
+    let (acc, proof) =
+      CatchUpProof::prove_update(self.current(), &deletions, deletion_proof, additions)?;
+    Ok(self.push_(acc, proof))
+  }
+
+  /// Starts a new epoch at `acc`, produced elsewhere, after checking its `proof` against the
+  /// current accumulator. Returns the new epoch.
+  pub fn push(
+    &mut self,
+    acc: Accumulator<G, T>,
+    proof: CatchUpProof<G, T>,
+  ) -> Result<u64, AccError> {
+    if !proof.verify(self.current(), &acc) {
+      return Err(AccError::BadProof);
+    }
+    Ok(self.push_(acc, proof))
+  }
+
+  fn push_(&mut self, acc: Accumulator<G, T>, proof: CatchUpProof<G, T>) -> u64 {
+    let epoch = self.epoch() + 1;
+    if let Some(elem_sets) = &mut self.elem_sets {
+      for elem in &proof.deletions {
+        elem_sets.current.remove(elem);
+      }
+      elem_sets.current.extend(proof.additions.iter().cloned());
+      if epoch.is_multiple_of(self.retention.snapshot_interval) {
+        let snapshot = elem_sets.current.iter().cloned().collect();
+        elem_sets.snapshots.insert(epoch, snapshot);
+      }
+    }
+    self.values.push(acc);
+    self.proofs.push(proof);
+    self.prune();
+    epoch
+  }
+
+  /// Drops epochs older than the retention config allows. Histories that track element sets only
+  /// drop whole runs of epochs up to a snapshot, so that every retained set can still be rebuilt.
+  fn prune(&mut self) {
+    let max_epochs = match self.retention.max_epochs {
+      Some(max_epochs) => max_epochs,
+      None => return,
+    };
+    let mut offset = match self.epoch().checked_sub(max_epochs) {
+      Some(offset) if offset > self.offset => offset,
+      _ => return,
+    };
+    if let Some(elem_sets) = &mut self.elem_sets {
+      offset = match elem_sets.snapshots.range(..=offset).next_back() {
+        Some((&snapshot_epoch, _)) => snapshot_epoch,
+        None => return,
+      };
+      elem_sets.snapshots = elem_sets.snapshots.split_off(&offset);
+    }
+    let dropped = (offset - self.offset) as usize;
+    self.values.drain(..dropped);
+    self.proofs.drain(..dropped);
+    self.offset = offset;
+  }
+
+  /// Compresses the updates from epoch `from` to epoch `to` into a single proof of the net
+  /// deletions and additions between the two, which verifies against `value(from)` and `value(to)`.
+  pub fn catch_up(&self, from: u64, to: u64) -> Result<CatchUpProof<G, T>, AccError> {
+    if from < self.offset || from > to {
+      return Err(AccError::UnknownEpoch { epoch: from });
+    }
+    if to > self.epoch() {
+      return Err(AccError::UnknownEpoch { epoch: to });
+    }
+    let (from, to) = ((from - self.offset) as usize, (to - self.offset) as usize);
+
+    // Invariant: `witness` is a witness for `deleted` in `values[from]` and for `added` in the
+    // accumulator after the epochs processed so far.
+    let mut witness = Witness(self.values[from].clone());
+    let mut deleted = NetElems::new();
+    let mut added = NetElems::new();
+    for proof in &self.proofs[from..to] {
+      // Elements added earlier in the range just drop out of `added`. The others are new net
+      // deletions. The new witness is a root of both the old witness and the epoch's intermediate
+      // accumulator (the witness of its deletion proof), which the Shamir trick combines.
+      let (cancelled, new_deleted) = added.partition(&proof.deletions);
+      added.remove(&cancelled, &prime_hash_product(&cancelled));
+      let new_deleted_x = prime_hash_product(&new_deleted);
+      witness = witness
+        .union_product(&added.x, &proof.deletion_proof.witness, &new_deleted_x)
+        .ok_or(AccError::BadProof)?;
+      deleted.insert(new_deleted, &new_deleted_x);
+
+      // Elements deleted earlier in the range drop out of `deleted`, and the witness no longer
+      // needs to exclude them. The others are new net additions.
+      let (restored, new_added) = deleted.partition(&proof.additions);
+      deleted.remove(&restored, &prime_hash_product(&restored));
+      witness = Witness(witness.0.add(&restored));
+      let new_added_x = prime_hash_product(&new_added);
+      added.insert(new_added, &new_added_x);
+    }
+
+    Ok(CatchUpProof {
+      deletion_proof: self.values[from]
+        .prove_membership_with_witness(&deleted.elems, witness.clone()),
+      addition_proof: self.values[to].prove_membership_with_witness(&added.elems, witness),
+      deletions: deleted.elems,
+      additions: added.elems,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::group::Rsa2048;
+
+  // Deletes `deletions` from and adds `additions` to both `history` and its element set `elems`.
+  fn update(
+    history: &mut AccumulatorHistory<Rsa2048, &'static str>,
+    elems: &mut Vec<&'static str>,
+    deletions: &[&'static str],
+    additions: &[&'static str],
+  ) {
+    let elem_witnesses: Vec<_> = deletions
+      .iter()
+      .map(|elem| {
+        let others: Vec<_> = elems
+          .iter()
+          .cloned()
+          .filter(|other| other != elem)
+          .collect();
+        (*elem, Witness(Accumulator::from(&others[..])))
+      })
+      .collect();
+    history.update(&elem_witnesses, additions).unwrap();
+    elems.retain(|elem| !deletions.contains(elem));
+    elems.extend_from_slice(additions);
+    assert!(*history.current() == Accumulator::from(&elems[..]));
+  }
+
+  fn sorted(elems: &[&'static str]) -> Vec<&'static str> {
+    let mut elems = elems.to_vec();
+    elems.sort();
+    elems
+  }
+
+  fn new_history() -> AccumulatorHistory<Rsa2048, &'static str> {
+    let mut elems = vec!["a", "b", "c"];
+    let mut history = AccumulatorHistory::new(Accumulator::from(&elems[..]));
+    update(&mut history, &mut elems, &[], &["d", "e"]);
+    update(&mut history, &mut elems, &["a"], &["f"]);
+    update(&mut history, &mut elems, &["d"], &["a"]);
+    update(&mut history, &mut elems, &["b"], &[]);
+    history
+  }
+
+  #[test]
+  fn test_catch_up() {
+    let history = new_history();
+    assert_eq!(history.epoch(), 4);
+    for from in 0..=4 {
+      for to in from..=4 {
+        let proof = history.catch_up(from, to).unwrap();
+        let (old_acc, new_acc) = (history.value(from).unwrap(), history.value(to).unwrap());
+        assert!(proof.verify(old_acc, new_acc));
+      }
+    }
+
+    // "a" was deleted and added back, and "d" was added and deleted again.
+    let proof = history.catch_up(0, 4).unwrap();
+    assert_eq!(sorted(proof.deletions()), vec!["b"]);
+    assert_eq!(sorted(proof.additions()), vec!["e", "f"]);
+    assert!(!proof.verify(history.value(0).unwrap(), history.value(3).unwrap()));
+    assert!(!proof.verify(history.value(1).unwrap(), history.value(4).unwrap()));
+    let proof = history.catch_up(1, 3).unwrap();
+    assert_eq!(sorted(proof.deletions()), vec!["d"]);
+    assert_eq!(sorted(proof.additions()), vec!["f"]);
+
+    assert!(history.catch_up(3, 2).is_err());
+    assert!(history.catch_up(0, 5).is_err());
+  }
+
+  #[test]
+  fn test_push() {
+    let history = new_history();
+    let mut replica = AccumulatorHistory::new(history.value(0).unwrap().clone());
+    let proof = |epoch| history.epoch_proof(epoch).unwrap().clone();
+    let value = |epoch| history.value(epoch).unwrap().clone();
+    assert!(history.epoch_proof(0).is_none());
+    assert!(replica.push(value(2), proof(2)).is_err());
+    assert!(replica.push(value(2), proof(1)).is_err());
+    for epoch in 1..=4 {
+      assert_eq!(replica.push(value(epoch), proof(epoch)).unwrap(), epoch);
+    }
+    assert!(replica.current() == history.current());
+
+    // A compressed proof serves as well as the individual ones.
+    let mut light = AccumulatorHistory::new(value(0));
+    light
+      .push(value(4), history.catch_up(0, 4).unwrap())
+      .unwrap();
+    assert!(light.current() == history.current());
+    assert!(light.elems_at(0).is_err());
+  }
+
+  #[test]
+  fn test_historical_proofs() {
+    let mut elems = vec!["a", "b", "c"];
+    let mut history = AccumulatorHistory::with_elems(&elems);
+    update(&mut history, &mut elems, &["a"], &["d"]);
+    update(&mut history, &mut elems, &[], &["a"]);
+
+    let expected = [
+      vec!["a", "b", "c"],
+      vec!["b", "c", "d"],
+      vec!["a", "b", "c", "d"],
+    ];
+    for (epoch, members) in expected.iter().enumerate() {
+      let epoch = epoch as u64;
+      let acc = history.value(epoch).unwrap();
+      assert_eq!(sorted(&history.elems_at(epoch).unwrap()), *members);
+      let proof = history.prove_membership_at(epoch, members).unwrap();
+      assert!(acc.verify_membership_batch(members, &proof));
+      let proof = history.prove_nonmembership_at(epoch, &["e"]).unwrap();
+      assert!(acc.verify_nonmembership(&["e"], &proof));
+    }
+
+    // "a" was deleted at epoch 1, and "d" did not exist yet at epoch 0.
+    assert!(history.prove_membership_at(1, &["a"]).is_err());
+    let proof = history.prove_nonmembership_at(1, &["a"]).unwrap();
+    assert!(history
+      .value(1)
+      .unwrap()
+      .verify_nonmembership(&["a"], &proof));
+    assert!(history.prove_nonmembership_at(0, &["b", "d"]).is_err());
+    assert!(history.prove_membership_at(3, &["a"]).is_err());
+  }
+
+  #[test]
+  fn test_retention() {
+    let mut elems = vec!["genesis"];
+    let mut history = AccumulatorHistory::with_elems(&elems);
+    history.set_retention(RetentionConfig {
+      max_epochs: Some(3),
+      snapshot_interval: 2,
+    });
+    let mut past_elems = vec![elems.clone()];
+    let new_elems = ["a", "b", "c", "d", "e", "f", "g", "h", "i"];
+    for (i, elem) in new_elems.iter().enumerate() {
+      // Delete every other element as we go.
+      let deletions = if i % 2 == 1 {
+        vec![new_elems[i - 1]]
+      } else {
+        vec![]
+      };
+      update(&mut history, &mut elems, &deletions, &[elem]);
+      past_elems.push(elems.clone());
+
+      let epoch = history.epoch();
+      let oldest = history.oldest_epoch();
+      assert!(epoch - oldest >= 3.min(epoch) && epoch - oldest < 5);
+      for e in oldest..=epoch {
+        let members = &past_elems[e as usize];
+        assert_eq!(sorted(&history.elems_at(e).unwrap()), sorted(members));
+      }
+    }
+
+    let oldest = history.oldest_epoch();
+    assert!(oldest > 0);
+    assert!(history.value(oldest - 1).is_none());
+    assert!(history.epoch_proof(oldest).is_none());
+    assert!(history.elems_at(oldest - 1).is_err());
+    assert!(history.catch_up(oldest - 1, oldest).is_err());
+    let proof = history.catch_up(oldest, history.epoch()).unwrap();
+    assert!(proof.verify(history.value(oldest).unwrap(), history.current()));
+    let members = &past_elems[oldest as usize];
+    let proof = history.prove_membership_at(oldest, members).unwrap();
+    assert!(history
+      .value(oldest)
+      .unwrap()
+      .verify_membership_batch(members, &proof));
+
+    // Histories that do not track element sets prune to the exact age.
+    let mut light = AccumulatorHistory::new(history.value(oldest).unwrap().clone());
+    light.set_retention(RetentionConfig {
+      max_epochs: Some(1),
+      ..RetentionConfig::default()
+    });
+    for e in oldest + 1..=history.epoch() {
+      let proof = history.epoch_proof(e).unwrap().clone();
+      light
+        .push(history.value(e).unwrap().clone(), proof)
+        .unwrap();
+    }
+    assert_eq!(light.epoch() - light.oldest_epoch(), 1);
+  }
+}
@@ -11,7 +11,9 @@
 //! application, you must ensure that **no element is accumulated twice**. In particular, deleting
 //! a doubly-accumulated element will remove only one "copy" of it from the accumulator, meaning
 //! that its membership can still be verified. Hence, an accumulator without this invariant can be
-//! viewed as a multiset.
+//! viewed as a multiset. Applications that want one should use `MultisetAccumulator`, which
+//! tracks multiplicities and proves them exactly. Conversely, `Accumulator::add_unique` enforces the
+//! invariant, proving to third parties that none of the added elements was already accumulated.
 //!
 //! # What is an accumulator?
 //!
@@ -41,7 +43,8 @@
 //! (Think VC : Vector :: Accumulator : Set.)
 //!
 //! Our vector commitment implementation is a work-in-progress (WIP), and should be treated with
-//! even more skepticism than our accumulators.
+//! even more skepticism than our accumulators. The same goes for our map commitment, which extends
+//! the idea to an authenticated dictionary from keys to values.
 //!
 //! # Usage
 //! ```
@@ -66,10 +69,16 @@
 //! Typical users of this library will access public-facing routines on `accumulator` and
 //! `vector_commitment`. However, we also export internal modules for useful traits, types (such as
 //! the `Rsa2048` group), and specialized procedures. **Use internal components at your own risk**.
+//! Applications that need their accumulator state to survive restarts can keep it in one of the
+//! backends of `store`, and credential issuers can use the registry in `revocation`. Services in
+//! other languages can reach an accumulator over JSON-RPC via `server` (behind the `server`
+//! feature), C programs via `ffi` (behind the `ffi` feature), and Python via the extension module
+//! built with the `python` feature.
 //!
 //! You can find a more interesting application of our library
 //! [here](https://github.com/cambrian/accumulator-demo), where we create a proof-of-concept for
-//! stateless Bitcoin nodes!
+//! stateless Bitcoin nodes! The `stateless` module sketches the same idea within this crate, down
+//! to a deterministic simulator of miners, validators and bridge nodes.
 //!
 //! # Groups
 //!
@@ -88,7 +97,24 @@
 //!
 //! Most accumulator or vector commitment functions will bottleneck in hashing to large primes. To
 //! alleviate this, we created a zero-allocation `U256` type that uses the low-level `mpn_`
-//! functions in [GMP](https://gmplib.org). Our `hash_to_prime` uses this type internally.
+//! functions in [GMP](https://gmplib.org). Our `hash_to_prime` uses this type internally. `Rsa2048`
+//! elements take the same approach, stored as fixed-width integers in Montgomery form so that group
+//! operations do not allocate.
+//!
+//! Exponentiations of each group's unknown-order element (which dominate building accumulators
+//! from scratch and proving nonmembership) use lazily-built fixed-base tables. Memory-constrained
+//! users can shrink or disable these via `group::set_fixed_base_config`.
+//!
+//! All of the above is variable-time. Provers exponentiating by secrets (as in `Poke2::prove`)
+//! should use `Group::exp_sec`, which for `Rsa2048` is a constant-time fixed-window ladder.
+//!
+//! The optional `parallel` feature runs hashing to primes, product trees, batch deletes and
+//! `compute_individual_witnesses` on a [rayon](https://docs.rs/rayon) thread pool. Accumulated
+//! types must be `Sync` whether or not it is enabled (see `util::PrimeHashable`), so the feature
+//! only changes how work is scheduled, never which types compile.
+//!
+//! Platforms without GMP can build with `--no-default-features --features pure-rust`, which swaps
+//! in a slower pure-Rust bignum backend with identical outputs (see `bigint`).
 //!
 //! Class groups are currently not performant for any meaningful use case. A pull request is in the
 //! works to drastically improve their performance using techniques learned from the
@@ -108,10 +134,26 @@ mod accumulator;
 pub use crate::accumulator::*;
 mod vector_commitment;
 pub use vector_commitment::*;
+mod map_commitment;
+pub use map_commitment::*;
+mod history;
+pub use history::*;
+mod multiset;
+pub use multiset::*;
 
+pub mod bigint;
+#[cfg(feature = "ffi")]
+pub mod ffi;
 pub mod group;
 pub mod hash;
 pub mod proof;
+#[cfg(feature = "python")]
+mod python;
+pub mod revocation;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod stateless;
+pub mod store;
 #[allow(missing_docs)]
 pub mod uint;
 pub mod util;
@@ -0,0 +1,471 @@
+//! Key-value map commitment (authenticated dictionary), built on top of our accumulator.
+//!
+//! A map is committed to as an accumulator holding two elements per entry: the key on its own and
+//! the (key, value) pair. Lookups of present keys are membership proofs for their pairs, while
+//! absent keys get a nonmembership proof for the bare key. Both batch into constant-size proofs.
+//!
+//! Uniqueness of keys is not enforced by the accumulator itself. Instead, every update comes with
+//! an `UpdateProof` showing that it removed exactly the old pairs of the keys it touched, so parties
+//! who verify each update know that every key maps to at most one value.
+use super::accumulator::{Accumulator, MembershipProof, NonmembershipProof, Witness};
+use crate::group::UnknownOrderGroup;
+use crate::util::PrimeHashable;
+use std::collections::{HashMap, HashSet};
+use std::error;
+use std::fmt;
+use std::hash::Hash;
+
+#[derive(Debug)]
+/// The different types of map commitment errors.
+pub enum MCError {
+  /// When the same key appears more than once in a lookup or update.
+  ConflictingKeys,
+  /// Unexpected state while generating a proof.
+  UnexpectedState,
+}
+
+impl fmt::Display for MCError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let msg = match self {
+      MCError::ConflictingKeys => "a key appears more than once",
+      MCError::UnexpectedState => "map commitment is in an unexpected state",
+    };
+    f.write_str(msg)
+  }
+}
+
+impl error::Error for MCError {}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+enum MapElem<K, V> {
+  Key(K),
+  Entry(K, V),
+}
+
+// Two lists of accumulator elements, e.g. those to prove present and absent.
+type ElemLists<K, V> = (Vec<MapElem<K, V>>, Vec<MapElem<K, V>>);
+
+#[derive(Debug, Eq, Hash, PartialEq)]
+/// A map commitment, wrapping an underlying accumulator.
+pub struct MapCommitment<G: UnknownOrderGroup, K, V>(Accumulator<G, MapElem<K, V>>);
+
+// Manual clone impl for the same reason as `Accumulator`.
+impl<G: UnknownOrderGroup, K: Hash, V: Hash> Clone for MapCommitment<G, K, V> {
+  fn clone(&self) -> Self {
+    Self(self.0.clone())
+  }
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+/// A batch lookup proof, covering both present and absent keys.
+pub struct LookupProof<G: UnknownOrderGroup, K: Hash, V: Hash> {
+  membership_proof: MembershipProof<G, MapElem<K, V>>,
+  nonmembership_proof: NonmembershipProof<G, MapElem<K, V>>,
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+/// A proof that a batch update was applied correctly. Carries the old values of the updated keys,
+/// which the verifier needs to know what was removed.
+pub struct UpdateProof<G: UnknownOrderGroup, K: Hash, V: Hash> {
+  old_values: Vec<Option<V>>,
+  // Nonmembership of the keys that were previously absent.
+  nonmembership_proof: NonmembershipProof<G, MapElem<K, V>>,
+  // Membership of the removed elements in the old commitment and of the added elements in the new
+  // one. Both share the same witness: the commitment in between.
+  deletion_proof: MembershipProof<G, MapElem<K, V>>,
+  addition_proof: MembershipProof<G, MapElem<K, V>>,
+}
+
+impl<G: UnknownOrderGroup, K: Hash, V: Hash> UpdateProof<G, K, V> {
+  /// Returns the values of the updated keys before the update (`None` for absent keys).
+  pub fn old_values(&self) -> &[Option<V>] {
+    &self.old_values
+  }
+}
+
+fn has_conflicting_keys<'a, K: 'a + Eq + Hash>(keys: impl Iterator<Item = &'a K>) -> bool {
+  let mut seen_keys = HashSet::new();
+  !keys.into_iter().all(|key| seen_keys.insert(key))
+}
+
+/// Splits lookup results into the pairs to prove present and the keys to prove absent.
+fn group_by_presence<K: Clone, V: Clone>(entries: &[(K, Option<V>)]) -> ElemLists<K, V> {
+  let mut present = vec![];
+  let mut absent = vec![];
+  for (key, value) in entries {
+    match value {
+      Some(value) => present.push(MapElem::Entry(key.clone(), value.clone())),
+      None => absent.push(MapElem::Key(key.clone())),
+    }
+  }
+  (present, absent)
+}
+
+/// Returns the elements to remove from and add to the accumulator to take each key in `updates`
+/// from its value in `old_values` to its new value.
+fn transition<K: Clone, V: Clone>(
+  updates: &[(K, Option<V>)],
+  old_values: &[Option<V>],
+) -> ElemLists<K, V> {
+  let mut deleted = vec![];
+  let mut added = vec![];
+  for ((key, new_value), old_value) in updates.iter().zip(old_values) {
+    if let Some(old_value) = old_value {
+      deleted.push(MapElem::Entry(key.clone(), old_value.clone()));
+      if new_value.is_none() {
+        deleted.push(MapElem::Key(key.clone()));
+      }
+    }
+    if let Some(new_value) = new_value {
+      added.push(MapElem::Entry(key.clone(), new_value.clone()));
+      if old_value.is_none() {
+        added.push(MapElem::Key(key.clone()));
+      }
+    }
+  }
+  (deleted, added)
+}
+
+impl<G, K, V> MapCommitment<G, K, V>
+where
+  G: UnknownOrderGroup,
+  K: Clone + Eq + PrimeHashable,
+  V: Clone + Eq + PrimeHashable,
+{
+  /// Initializes a commitment to the empty map.
+  pub fn empty() -> Self {
+    Self(Accumulator::empty())
+  }
+
+  /// Verifies a batch lookup.
+  ///
+  /// # Arguments
+  ///
+  /// * `entries` - Tuples (key, value) to verify, where a value of `None` means the key is absent.
+  /// * `LookupProof` - A `LookupProof` to verify against.
+  pub fn verify_lookup(
+    mc: &Self,
+    entries: &[(K, Option<V>)],
+    LookupProof {
+      membership_proof,
+      nonmembership_proof,
+    }: &LookupProof<G, K, V>,
+  ) -> bool {
+    if has_conflicting_keys(entries.iter().map(|(key, _)| key)) {
+      return false;
+    }
+    let (present, absent) = group_by_presence(entries);
+    mc.0.verify_membership_batch(&present, membership_proof)
+      && mc.0.verify_nonmembership(&absent, nonmembership_proof)
+  }
+
+  /// Verifies that `new_mc` results from applying `updates` to `old_mc`.
+  ///
+  /// # Arguments
+  ///
+  /// * `updates` - Tuples (key, new value), where a new value of `None` removes the key.
+  /// * `UpdateProof` - An `UpdateProof` to verify against.
+  pub fn verify_update(
+    old_mc: &Self,
+    new_mc: &Self,
+    updates: &[(K, Option<V>)],
+    UpdateProof {
+      old_values,
+      nonmembership_proof,
+      deletion_proof,
+      addition_proof,
+    }: &UpdateProof<G, K, V>,
+  ) -> bool {
+    if old_values.len() != updates.len() || has_conflicting_keys(updates.iter().map(|(key, _)| key))
+    {
+      return false;
+    }
+    let absent: Vec<_> = updates
+      .iter()
+      .zip(old_values)
+      .filter(|(_, old_value)| old_value.is_none())
+      .map(|((key, _), _)| MapElem::Key(key.clone()))
+      .collect();
+    let (deleted, added) = transition(updates, old_values);
+    deletion_proof.witness == addition_proof.witness
+      && old_mc.0.verify_nonmembership(&absent, nonmembership_proof)
+      && old_mc.0.verify_membership_batch(&deleted, deletion_proof)
+      && new_mc.0.verify_membership_batch(&added, addition_proof)
+  }
+}
+
+#[derive(Debug)]
+/// A map commitment together with the map it commits to, for the party generating proofs.
+///
+/// Proofs are computed from the full contents of the map, so each costs a number of group
+/// operations linear in its size.
+pub struct MapCommitmentManager<G: UnknownOrderGroup, K, V> {
+  mc: MapCommitment<G, K, V>,
+  entries: HashMap<K, V>,
+}
+
+impl<G, K, V> Default for MapCommitmentManager<G, K, V>
+where
+  G: UnknownOrderGroup,
+  K: Clone + Eq + PrimeHashable,
+  V: Clone + Eq + PrimeHashable,
+{
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<G, K, V> MapCommitmentManager<G, K, V>
+where
+  G: UnknownOrderGroup,
+  K: Clone + Eq + PrimeHashable,
+  V: Clone + Eq + PrimeHashable,
+{
+  /// Creates a manager for the empty map.
+  pub fn new() -> Self {
+    Self {
+      mc: MapCommitment::empty(),
+      entries: HashMap::new(),
+    }
+  }
+
+  /// Returns the current commitment.
+  pub fn commitment(&self) -> &MapCommitment<G, K, V> {
+    &self.mc
+  }
+
+  /// Returns the value for `key`, if present.
+  pub fn get(&self, key: &K) -> Option<&V> {
+    self.entries.get(key)
+  }
+
+  /// Returns the values for `keys` along with a proof of them.
+  #[allow(clippy::type_complexity)]
+  pub fn lookup(&self, keys: &[K]) -> Result<(Vec<Option<V>>, LookupProof<G, K, V>), MCError> {
+    if has_conflicting_keys(keys.iter()) {
+      return Err(MCError::ConflictingKeys);
+    }
+    let entries: Vec<(K, Option<V>)> = keys
+      .iter()
+      .map(|key| (key.clone(), self.entries.get(key).cloned()))
+      .collect();
+    let (present, absent) = group_by_presence(&entries);
+    let membership_proof = self.prove_membership(&present);
+    let nonmembership_proof = self
+      .mc
+      .0
+      .prove_nonmembership(&self.elems(), &absent)
+      .map_err(|_| MCError::UnexpectedState)?;
+    Ok((
+      entries.into_iter().map(|(_, value)| value).collect(),
+      LookupProof {
+        membership_proof,
+        nonmembership_proof,
+      },
+    ))
+  }
+
+  /// Applies `updates` (key, new value), where a new value of `None` removes the key, and returns a
+  /// proof of the update.
+  pub fn update(&mut self, updates: &[(K, Option<V>)]) -> Result<UpdateProof<G, K, V>, MCError> {
+    if has_conflicting_keys(updates.iter().map(|(key, _)| key)) {
+      return Err(MCError::ConflictingKeys);
+    }
+    let old_values: Vec<Option<V>> = updates
+      .iter()
+      .map(|(key, _)| self.entries.get(key).cloned())
+      .collect();
+    let absent: Vec<_> = updates
+      .iter()
+      .zip(&old_values)
+      .filter(|(_, old_value)| old_value.is_none())
+      .map(|((key, _), _)| MapElem::Key(key.clone()))
+      .collect();
+    let nonmembership_proof = self
+      .mc
+      .0
+      .prove_nonmembership(&self.elems(), &absent)
+      .map_err(|_| MCError::UnexpectedState)?;
+
+    let (deleted, added) = transition(updates, &old_values);
+    let deletion_proof = self.prove_membership(&deleted);
+    let (new_acc, addition_proof) = deletion_proof.witness.0.clone().add_with_proof(&added);
+
+    self.mc = MapCommitment(new_acc);
+    for (key, new_value) in updates {
+      match new_value {
+        Some(value) => self.entries.insert(key.clone(), value.clone()),
+        None => self.entries.remove(key),
+      };
+    }
+    Ok(UpdateProof {
+      old_values,
+      nonmembership_proof,
+      deletion_proof,
+      addition_proof,
+    })
+  }
+
+  /// Returns every element of the underlying accumulator.
+  fn elems(&self) -> Vec<MapElem<K, V>> {
+    self
+      .entries
+      .iter()
+      .flat_map(|(key, value)| {
+        vec![
+          MapElem::Key(key.clone()),
+          MapElem::Entry(key.clone(), value.clone()),
+        ]
+      })
+      .collect()
+  }
+
+  /// Proves membership of `elems`, all of which must be in the map, using the accumulator of
+  /// every other element as their witness.
+  fn prove_membership(&self, elems: &[MapElem<K, V>]) -> MembershipProof<G, MapElem<K, V>> {
+    let elem_set: HashSet<_> = elems.iter().collect();
+    let others: Vec<_> = self
+      .elems()
+      .into_iter()
+      .filter(|elem| !elem_set.contains(elem))
+      .collect();
+    let witness = Witness(Accumulator::empty().add(&others));
+    self.mc.0.prove_membership_with_witness(elems, witness)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::group::Rsa2048;
+
+  type Manager = MapCommitmentManager<Rsa2048, &'static str, u64>;
+  type Commitment = MapCommitment<Rsa2048, &'static str, u64>;
+
+  fn new_manager() -> Manager {
+    let mut manager = Manager::new();
+    manager
+      .update(&[("a", Some(1)), ("b", Some(2)), ("c", Some(3))])
+      .unwrap();
+    manager
+  }
+
+  #[test]
+  fn test_lookup() {
+    let manager = new_manager();
+    let mc = manager.commitment();
+    let (values, proof) = manager.lookup(&["b", "d", "a"]).unwrap();
+    assert_eq!(values, vec![Some(2), None, Some(1)]);
+    let entries = [("b", Some(2)), ("d", None), ("a", Some(1))];
+    assert!(Commitment::verify_lookup(mc, &entries, &proof));
+
+    // Wrong values, absent keys claimed present and present keys claimed absent.
+    assert!(!Commitment::verify_lookup(
+      mc,
+      &[("b", Some(3)), ("d", None), ("a", Some(1))],
+      &proof
+    ));
+    assert!(!Commitment::verify_lookup(
+      mc,
+      &[("b", Some(2)), ("d", Some(4)), ("a", Some(1))],
+      &proof
+    ));
+    assert!(!Commitment::verify_lookup(
+      mc,
+      &[("b", Some(2)), ("d", None), ("a", None)],
+      &proof
+    ));
+    // A pair is never a member of its own map as a bare key.
+    let (_, absent_proof) = manager.lookup(&["d"]).unwrap();
+    assert!(!Commitment::verify_lookup(
+      mc,
+      &[("a", None)],
+      &absent_proof
+    ));
+  }
+
+  #[test]
+  fn test_update() {
+    let mut manager = new_manager();
+    let old_mc = manager.commitment().clone();
+    let updates = [("a", Some(10)), ("b", None), ("d", Some(4)), ("e", None)];
+    let proof = manager.update(&updates).unwrap();
+    assert_eq!(proof.old_values(), &[Some(1), Some(2), None, None]);
+    let new_mc = manager.commitment();
+    assert!(Commitment::verify_update(&old_mc, new_mc, &updates, &proof));
+
+    // Matches a commitment built from scratch.
+    let mut fresh = Manager::new();
+    fresh
+      .update(&[("c", Some(3)), ("a", Some(10)), ("d", Some(4))])
+      .unwrap();
+    assert_eq!(fresh.commitment(), new_mc);
+
+    let (values, proof) = manager.lookup(&["a", "b", "c", "d"]).unwrap();
+    assert_eq!(values, vec![Some(10), None, Some(3), Some(4)]);
+    let entries = [("a", Some(10)), ("b", None), ("c", Some(3)), ("d", Some(4))];
+    assert!(Commitment::verify_lookup(new_mc, &entries, &proof));
+  }
+
+  #[test]
+  fn test_rejected_updates() {
+    let mut manager = new_manager();
+    let old_mc = manager.commitment().clone();
+    let updates = [("a", Some(10)), ("d", Some(4))];
+    let proof = manager.update(&updates).unwrap();
+    let new_mc = manager.commitment().clone();
+
+    // Different new values or keys.
+    assert!(!Commitment::verify_update(
+      &old_mc,
+      &new_mc,
+      &[("a", Some(11)), ("d", Some(4))],
+      &proof
+    ));
+    assert!(!Commitment::verify_update(
+      &old_mc,
+      &new_mc,
+      &[("a", Some(10))],
+      &proof
+    ));
+    // Swapped commitments.
+    assert!(!Commitment::verify_update(
+      &new_mc, &old_mc, &updates, &proof
+    ));
+    // Tampered old values, e.g. hiding that "a" was present.
+    let mut tampered = proof.clone();
+    tampered.old_values[0] = None;
+    assert!(!Commitment::verify_update(
+      &old_mc, &new_mc, &updates, &tampered
+    ));
+
+    // Adding a second value for "b" without removing the first is not a valid update.
+    let mut honest = new_manager();
+    let updates = [("b", Some(20))];
+    let proof = honest.update(&updates).unwrap();
+    assert!(Commitment::verify_update(
+      &old_mc,
+      honest.commitment(),
+      &updates,
+      &proof
+    ));
+    let cheat_mc = MapCommitment(old_mc.0.clone().add(&[MapElem::Entry("b", 20)]));
+    assert!(!Commitment::verify_update(
+      &old_mc, &cheat_mc, &updates, &proof
+    ));
+  }
+
+  #[test]
+  fn test_conflicting_keys() {
+    let mut manager = new_manager();
+    assert!(manager.lookup(&["a", "a"]).is_err());
+    assert!(manager.update(&[("d", Some(1)), ("d", None)]).is_err());
+    assert_eq!(manager.get(&"d"), None);
+    let (_, proof) = manager.lookup(&["a"]).unwrap();
+    assert!(!Commitment::verify_lookup(
+      manager.commitment(),
+      &[("a", Some(1)), ("a", Some(1))],
+      &proof
+    ));
+  }
+}
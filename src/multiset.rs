@@ -0,0 +1,207 @@
+//! Multiset accumulators, which track how many times each element has been added.
+//!
+//! Adding an element `k` times raises the accumulator to `p^k` for the element's prime hash `p`.
+//! That `p` appears exactly `k` times is then proven by a membership proof for `p^k` together with
+//! a nonmembership proof for `p` in the witness of that membership, which rules out `p^(k + 1)`.
+use crate::accumulator::{AccError, Accumulator, MembershipProof, NonmembershipProof, Witness};
+use crate::bigint::{Integer, Pow};
+use crate::group::UnknownOrderGroup;
+use crate::hash::hash_to_prime;
+use crate::util::{int, PrimeHashable};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+/// A proof that an element appears in an accumulator exactly `multiplicity` times.
+pub struct MultiplicityProof<G: UnknownOrderGroup, T: Hash> {
+  multiplicity: u32,
+  membership: MembershipProof<G, T>,
+  nonmembership: NonmembershipProof<G, T>,
+}
+
+impl<G: UnknownOrderGroup, T: Eq + PrimeHashable> MultiplicityProof<G, T> {
+  /// Returns the number of times the element appears.
+  pub fn multiplicity(&self) -> u32 {
+    self.multiplicity
+  }
+
+  /// Verifies that `elem` appears exactly `self.multiplicity()` times in `acc`.
+  pub fn verify(&self, acc: &Accumulator<G, T>, elem: &T) -> bool {
+    let p = hash_to_prime(elem);
+    acc.verify_membership_product(&p.clone().pow(self.multiplicity), &self.membership)
+      && self
+        .membership
+        .witness
+        .0
+        .verify_nonmembership_product(&p, &self.nonmembership)
+  }
+}
+
+#[derive(Clone, Debug)]
+/// An accumulator that may hold several copies of an element, along with the multiplicity of every
+/// element in it.
+///
+/// Proving keeps no state beyond the multiplicities, so each proof recomputes a witness from all
+/// the other elements, at the cost of one exponentiation by their prime hash product.
+pub struct MultisetAccumulator<G: UnknownOrderGroup, T: Hash> {
+  acc: Accumulator<G, T>,
+  // The multiplicity and prime hash of each element.
+  elems: HashMap<T, (u32, Integer)>,
+}
+
+impl<G: UnknownOrderGroup, T: Clone + Eq + PrimeHashable> MultisetAccumulator<G, T> {
+  /// Returns a new, empty multiset accumulator.
+  pub fn new() -> Self {
+    Self {
+      acc: Accumulator::empty(),
+      elems: HashMap::new(),
+    }
+  }
+
+  /// Returns the underlying accumulator.
+  pub fn accumulator(&self) -> &Accumulator<G, T> {
+    &self.acc
+  }
+
+  /// Returns the number of times `elem` has been added (and not deleted).
+  pub fn multiplicity(&self, elem: &T) -> u32 {
+    self.elems.get(elem).map_or(0, |(k, _)| *k)
+  }
+
+  /// Adds `k` copies of `elem`.
+  ///
+  /// Panics if the multiplicity of `elem` overflows a `u32`, which is far beyond what the group
+  /// arithmetic could handle anyway.
+  pub fn add_n(&mut self, elem: T, k: u32) {
+    if k == 0 {
+      return;
+    }
+    let entry = self
+      .elems
+      .entry(elem)
+      .or_insert_with_key(|elem| (0, hash_to_prime(elem)));
+    entry.0 = entry.0.checked_add(k).expect("multiplicity overflow");
+    self.acc = self.acc.add_product(&entry.1.clone().pow(k));
+  }
+
+  /// Deletes one copy of `elem`, returning a proof of its remaining multiplicity. Fails with
+  /// `UnknownElem` if `elem` is not in the accumulator.
+  pub fn delete_one(&mut self, elem: &T) -> Result<MultiplicityProof<G, T>, AccError> {
+    let (k, p) = match self.elems.get_mut(elem) {
+      Some(entry) => {
+        entry.0 -= 1;
+        (entry.0, entry.1.clone())
+      }
+      None => return Err(AccError::UnknownElem { index: 0 }),
+    };
+    if k == 0 {
+      self.elems.remove(elem);
+    }
+    let others = self.others_product(elem);
+    let witness = Accumulator::empty().add_product(&others);
+    self.acc = witness.add_product(&p.clone().pow(k));
+    self.prove_multiplicity_(&p, k, &others, Witness(witness))
+  }
+
+  /// Proves the multiplicity of `elem`, which may be zero.
+  pub fn prove_multiplicity(&self, elem: &T) -> Result<MultiplicityProof<G, T>, AccError> {
+    let p = match self.elems.get(elem) {
+      Some((_, p)) => p.clone(),
+      None => hash_to_prime(elem),
+    };
+    let others = self.others_product(elem);
+    let witness = Witness(Accumulator::empty().add_product(&others));
+    self.prove_multiplicity_(&p, self.multiplicity(elem), &others, witness)
+  }
+
+  // Returns the prime hash product of every element other than `elem`, with multiplicities.
+  fn others_product(&self, elem: &T) -> Integer {
+    self
+      .elems
+      .iter()
+      .filter(|(other, _)| *other != elem)
+      .fold(int(1), |s, (_, (k, p))| s * p.clone().pow(*k))
+  }
+
+  // Proves that `p` has multiplicity `k`, given the product of all `others` and their accumulator.
+  fn prove_multiplicity_(
+    &self,
+    p: &Integer,
+    k: u32,
+    others: &Integer,
+    witness: Witness<G, T>,
+  ) -> Result<MultiplicityProof<G, T>, AccError> {
+    // Only fails if another element shares the prime hash of this one.
+    let nonmembership_witness = Accumulator::compute_nonmembership_witness_product(others, p)
+      .ok_or(AccError::InputsNotCoprime { index: 0 })?;
+    let nonmembership = witness
+      .0
+      .prove_nonmembership_product(p, &nonmembership_witness);
+    let membership = self
+      .acc
+      .prove_membership_product(&p.clone().pow(k), witness);
+    Ok(MultiplicityProof {
+      multiplicity: k,
+      membership,
+      nonmembership,
+    })
+  }
+}
+
+impl<G: UnknownOrderGroup, T: Clone + Eq + PrimeHashable> Default for MultisetAccumulator<G, T> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::group::Rsa2048;
+
+  #[test]
+  fn test_multiplicity_proofs() {
+    let mut multiset = MultisetAccumulator::<Rsa2048, &'static str>::new();
+    multiset.add_n("a", 3);
+    multiset.add_n("b", 1);
+    multiset.add_n("a", 2);
+    multiset.add_n("c", 0);
+    assert_eq!(multiset.multiplicity(&"a"), 5);
+    let acc = multiset.accumulator().clone();
+    assert!(acc == Accumulator::from(&["a", "a", "a", "a", "a", "b"][..]));
+
+    for &(elem, k) in &[("a", 5), ("b", 1), ("c", 0)] {
+      let proof = multiset.prove_multiplicity(&elem).unwrap();
+      assert_eq!(proof.multiplicity(), k);
+      assert!(proof.verify(&acc, &elem));
+      assert!(!proof.verify(&acc, &"d"));
+    }
+
+    // A proof for fewer copies than there are fails, since the witness still holds one.
+    let proof = multiset.prove_multiplicity(&"a").unwrap();
+    let fewer = MultiplicityProof {
+      multiplicity: 4,
+      membership: acc.prove_membership_product(
+        &hash_to_prime(&"a").pow(4),
+        proof.membership.witness.clone(),
+      ),
+      nonmembership: proof.nonmembership.clone(),
+    };
+    assert!(!fewer.verify(&acc, &"a"));
+  }
+
+  #[test]
+  fn test_delete_one() {
+    let mut multiset = MultisetAccumulator::<Rsa2048, &'static str>::new();
+    multiset.add_n("a", 2);
+    multiset.add_n("b", 1);
+    let proof = multiset.delete_one(&"a").unwrap();
+    assert_eq!(proof.multiplicity(), 1);
+    assert!(proof.verify(multiset.accumulator(), &"a"));
+    let proof = multiset.delete_one(&"a").unwrap();
+    assert_eq!(proof.multiplicity(), 0);
+    assert!(proof.verify(multiset.accumulator(), &"a"));
+    assert!(multiset.accumulator() == &Accumulator::from(&["b"][..]));
+    assert!(multiset.delete_one(&"a").is_err());
+  }
+}
@@ -4,9 +4,112 @@
 //! Use standalone with caution.
 //!
 //! Implementations are based on Section 3 of BBF.
+use crate::bigint::Integer;
+use crate::group::{exp_product, Group};
+use crate::hash::blake2b;
+use crate::util::int;
 mod poe;
 pub use poe::Poe;
 mod pokcr;
 pub use pokcr::Pokcr;
 mod poke2;
 pub use poke2::Poke2;
+
+/// Bit length of the random exponents `verify_batch` combines equations with. A batch containing a
+/// false equation passes with probability about `2^-GAMMA_BITS`.
+const GAMMA_BITS: u32 = 128;
+
+#[derive(Debug, Clone)]
+/// An equation `prod(lhs) = prod(rhs)` between products of powers, as checked by a proof verifier.
+/// Verifiers expose these so that `verify_batch` can check the equations of many proofs at once.
+pub(crate) struct Equation<G: Group> {
+  lhs: Vec<(G::Elem, Integer)>,
+  rhs: Vec<(G::Elem, Integer)>,
+}
+
+impl<G: Group> Equation<G> {
+  pub(crate) fn new(lhs: Vec<(G::Elem, Integer)>, rhs: Vec<(G::Elem, Integer)>) -> Self {
+    Self { lhs, rhs }
+  }
+
+  /// Returns whether the equation holds.
+  pub(crate) fn holds(&self) -> bool {
+    let eval = |terms: &[(G::Elem, Integer)]| {
+      terms
+        .iter()
+        .fold(G::id(), |val, (base, exp)| G::op(&val, &G::exp(base, exp)))
+    };
+    eval(&self.lhs) == eval(&self.rhs)
+  }
+}
+
+/// Returns whether all of `equations` hold, checking them together: each side of each equation is
+/// raised to a random `GAMMA_BITS`-bit exponent, and the products of both sides over all equations
+/// are compared with one simultaneous exponentiation each (see `group::exp_product`). The exponents
+/// are derived by hashing the equations, so the check is deterministic. See BBF (Section 3.3) for
+/// batching PoE and PoKE2 proofs this way.
+pub(crate) fn verify_batch<G: Group>(equations: &[Equation<G>]) -> bool {
+  if equations.len() <= 1 {
+    return equations.iter().all(Equation::holds);
+  }
+  let terms: Vec<_> = equations.iter().map(|eq| (&eq.lhs, &eq.rhs)).collect();
+  let seed = blake2b(&terms);
+  let modulus = int(1) << GAMMA_BITS;
+  let mut lhs = vec![];
+  let mut rhs = vec![];
+  for (i, eq) in equations.iter().enumerate() {
+    let gamma = int(blake2b(&(&seed, i as u64)) % &modulus);
+    let scale = |terms: &[(G::Elem, Integer)]| {
+      terms
+        .iter()
+        .map(|(base, exp)| (base.clone(), int(exp * &gamma)))
+        .collect::<Vec<_>>()
+    };
+    lhs.extend(scale(&eq.lhs));
+    rhs.extend(scale(&eq.rhs));
+  }
+  exp_product::<G>(&lhs) == exp_product::<G>(&rhs)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::group::{ElemFrom, Rsa2048, UnknownOrderGroup};
+
+  #[test]
+  fn test_verify_batch() {
+    let base = Rsa2048::unknown_order_elem();
+    let statements: Vec<_> = [int(20), int(1_000_003), int(1) << 300]
+      .iter()
+      .map(|exp| {
+        let result = Rsa2048::exp(&base, exp);
+        let proof = Poe::<Rsa2048>::prove(&base, exp, &result);
+        (exp.clone(), result, proof)
+      })
+      .collect();
+    let equations: Vec<_> = statements
+      .iter()
+      .map(|(exp, result, proof)| Poe::equation(&base, exp, result, proof).unwrap())
+      .collect();
+    assert!(verify_batch(&equations));
+    assert!(verify_batch::<Rsa2048>(&[]));
+
+    // A proof of another statement breaks the equation it is used in.
+    let mut swapped = equations.clone();
+    swapped[0] =
+      Poe::equation(&base, &statements[0].0, &statements[0].1, &statements[2].2).unwrap();
+    assert!(!verify_batch(&swapped));
+
+    // Errors that cancel out in the plain product of all equations are still caught.
+    let (a, b, e) = (Rsa2048::elem(2), Rsa2048::elem(3), Rsa2048::elem(5));
+    let cancelling = [
+      Equation::<Rsa2048>::new(
+        vec![(a.clone(), int(1)), (e.clone(), int(1))],
+        vec![(a, int(1))],
+      ),
+      Equation::new(vec![(b.clone(), int(1))], vec![(b, int(1)), (e, int(1))]),
+    ];
+    assert!(!cancelling[0].holds() && !cancelling[1].holds());
+    assert!(!verify_batch(&cancelling));
+  }
+}
@@ -1,8 +1,10 @@
 //! Non-Interactive Proofs of Exponentiation (NI-PoE). See BBF (pages 8 and 42) for details.
+use super::Equation;
+use crate::bigint::Integer;
 use crate::group::Group;
 use crate::hash::hash_to_prime;
+use crate::store::Persist;
 use crate::util::int;
-use rug::Integer;
 
 #[allow(non_snake_case)]
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
@@ -21,13 +23,44 @@ impl<G: Group> Poe<G> {
     }
   }
 
-  /// Verifies that `base ^ exp = result` using the given proof to avoid computation.
+  /// Verifies that `base ^ exp = result` using the given proof to avoid computation. Rejects
+  /// elements outside the group.
   pub fn verify(base: &G::Elem, exp: &Integer, result: &G::Elem, proof: &Self) -> bool {
+    Self::equation(base, exp, result, proof).is_some_and(|equation| equation.holds())
+  }
+
+  /// Returns the equation `verify` checks, or `None` if an element is outside the group.
+  pub(crate) fn equation(
+    base: &G::Elem,
+    exp: &Integer,
+    result: &G::Elem,
+    proof: &Self,
+  ) -> Option<Equation<G>> {
+    if !G::is_valid(base) || !G::is_valid(result) || !G::is_valid(&proof.Q) {
+      return None;
+    }
     let l = hash_to_prime(&(base, exp, result));
     let r = int(exp % &l);
-    // w = Q^l * u^r
-    let w = G::op(&G::exp(&proof.Q, &l), &G::exp(&base, &r));
-    w == *result
+    // Q^l * u^r = w
+    Some(Equation::new(
+      vec![(proof.Q.clone(), l), (base.clone(), r)],
+      vec![(result.clone(), int(1))],
+    ))
+  }
+}
+
+impl<G: Group> Persist for Poe<G>
+where
+  G::Elem: Persist,
+{
+  fn encode(&self, out: &mut Vec<u8>) {
+    self.Q.encode(out);
+  }
+
+  fn decode(bytes: &mut &[u8]) -> Option<Self> {
+    Some(Self {
+      Q: G::Elem::decode(bytes)?,
+    })
   }
 }
 
@@ -37,6 +70,15 @@ mod tests {
   use crate::group::{ElemFrom, Rsa2048, UnknownOrderGroup};
   use crate::util::int;
 
+  #[test]
+  fn test_poe_invalid_elems() {
+    // `0^20 = 0` holds, but zero is not in the group.
+    let zero = Rsa2048::elem(0);
+    let exp = int(20);
+    let proof = Poe::<Rsa2048>::prove(&zero, &exp, &zero);
+    assert!(!Poe::verify(&zero, &exp, &zero, &proof));
+  }
+
   #[test]
   fn test_poe_small_exp() {
     // 2^20 = 1048576
@@ -1,6 +1,7 @@
 //! Non-Interactive Proofs of Knowledge of Co-prime Roots (NI-PoKCR). See BBF (page 11) for details.
+use crate::bigint::Integer;
 use crate::group::{multi_exp, Group};
-use rug::Integer;
+use crate::store::Persist;
 
 #[allow(non_snake_case)]
 #[derive(PartialEq, Eq, Hash, Clone, Debug)]
@@ -17,14 +18,32 @@ impl<G: Group> Pokcr<G> {
     }
   }
 
-  /// Verifies an NI-PoKCR proof.
+  /// Verifies an NI-PoKCR proof. Rejects elements outside the group.
   pub fn verify(alphas: &[G::Elem], x: &[Integer], proof: &Self) -> bool {
+    if !G::is_valid(&proof.w) || alphas.iter().any(|alpha| !G::is_valid(alpha)) {
+      return false;
+    }
     let y = multi_exp::<G>(alphas, x);
     let lhs = G::exp(&proof.w, &x.iter().product());
     lhs == y
   }
 }
 
+impl<G: Group> Persist for Pokcr<G>
+where
+  G::Elem: Persist,
+{
+  fn encode(&self, out: &mut Vec<u8>) {
+    self.w.encode(out);
+  }
+
+  fn decode(bytes: &mut &[u8]) -> Option<Self> {
+    Some(Self {
+      w: G::Elem::decode(bytes)?,
+    })
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -1,8 +1,11 @@
 //! Non-Interactive Proofs of Knowledge of Exponent (NI-PoKE2). See BBF (pages 10 and 42) for
 //! details.
+use super::Equation;
+use crate::bigint::Integer;
 use crate::group::UnknownOrderGroup;
 use crate::hash::{blake2b, hash_to_prime};
-use rug::Integer;
+use crate::store::Persist;
+use crate::util::int;
 
 #[allow(non_snake_case)]
 #[derive(PartialEq, Eq, Hash, Clone, Debug)]
@@ -14,30 +17,67 @@ pub struct Poke2<G: UnknownOrderGroup> {
 }
 
 impl<G: UnknownOrderGroup> Poke2<G> {
-  /// Computes a proof that you know `exp` s.t. `base ^ exp = result`.
+  /// Computes a proof that you know `exp` s.t. `base ^ exp = result`. Exponentiations by `exp` and
+  /// values derived from it use `exp_sec`, since `exp` is the secret being proven.
   pub fn prove(base: &G::Elem, exp: &Integer, result: &G::Elem) -> Self {
-    let g = G::unknown_order_elem();
-    let z = G::exp(&g, exp);
+    let z = G::exp_sec(&G::unknown_order_elem(), exp);
     let l = hash_to_prime(&(base, result, &z));
     let alpha = blake2b(&(base, result, &z, &l));
     let (q, r) = <(Integer, Integer)>::from(exp.div_rem_euc_ref(&l));
     #[allow(non_snake_case)]
-    let Q = G::exp(&G::op(&base, &G::exp(&g, &alpha)), &q);
+    let Q = G::exp_sec(&G::op(&base, &G::exp_unknown_order_elem(&alpha)), &q);
     Self { z, Q, r }
   }
 
-  /// Verifies that the prover knows `exp` s.t. `base ^ exp = result`.
+  /// Verifies that the prover knows `exp` s.t. `base ^ exp = result`. Rejects elements outside the
+  /// group.
+  pub fn verify(base: &G::Elem, result: &G::Elem, proof: &Self) -> bool {
+    Self::equation(base, result, proof).is_some_and(|equation| equation.holds())
+  }
+
+  /// Returns the equation `verify` checks, or `None` if an element is outside the group or `r` is
+  /// out of range.
   #[allow(non_snake_case)]
-  pub fn verify(base: &G::Elem, result: &G::Elem, Self { z, Q, r }: &Self) -> bool {
-    let g = G::unknown_order_elem();
+  pub(crate) fn equation(
+    base: &G::Elem,
+    result: &G::Elem,
+    Self { z, Q, r }: &Self,
+  ) -> Option<Equation<G>> {
+    if [base, result, z, Q].iter().any(|elem| !G::is_valid(elem)) {
+      return None;
+    }
     let l = hash_to_prime(&(base, result, &z));
+    if *r < 0 || *r >= l {
+      return None;
+    }
     let alpha = blake2b(&(base, result, &z, &l));
-    let lhs = G::op(
-      &G::exp(Q, &l),
-      &G::exp(&G::op(&base, &G::exp(&g, &alpha)), &r),
-    );
-    let rhs = G::op(result, &G::exp(&z, &alpha));
-    lhs == rhs
+    // Q^l * (u * g^alpha)^r = w * z^alpha
+    Some(Equation::new(
+      vec![
+        (Q.clone(), l),
+        (G::op(&base, &G::exp_unknown_order_elem(&alpha)), r.clone()),
+      ],
+      vec![(result.clone(), int(1)), (z.clone(), alpha)],
+    ))
+  }
+}
+
+impl<G: UnknownOrderGroup> Persist for Poke2<G>
+where
+  G::Elem: Persist,
+{
+  fn encode(&self, out: &mut Vec<u8>) {
+    self.z.encode(out);
+    self.Q.encode(out);
+    self.r.encode(out);
+  }
+
+  fn decode(bytes: &mut &[u8]) -> Option<Self> {
+    Some(Self {
+      z: G::Elem::decode(bytes)?,
+      Q: G::Elem::decode(bytes)?,
+      r: Integer::decode(bytes)?,
+    })
   }
 }
 
@@ -0,0 +1,416 @@
+//! Python bindings, built as the `accumulator` extension module with maturin (see
+//! `pyproject.toml`).
+//!
+//! Accumulated elements are Python `bytes`, and every object belongs to the group it was created
+//! for, either `"rsa2048"` (the default) or `"class"`. Mixing objects of different groups, failed
+//! accumulator operations and malformed serialized data all raise `ValueError`.
+//!
+//! `to_bytes` and `from_bytes` round-trip objects through the `Persist` encoding of the `store`
+//! module. Serialized data does not record its group, which must be passed to `from_bytes`.
+//!
+//! ```python
+//! import accumulator
+//!
+//! acc = accumulator.Accumulator("class")
+//! proof = acc.add([b"dog", b"cat"])
+//! assert acc.verify_membership([b"dog", b"cat"], proof)
+//! proof = acc.prove_nonmembership([b"dog", b"cat"], [b"cow"])
+//! assert acc.verify_nonmembership([b"cow"], proof)
+//! ```
+//!
+//! The test suite is in `python/tests`.
+// The PyO3 macros convert every error into a `PyErr`, including those that already are one.
+#![allow(clippy::useless_conversion)]
+use crate::accumulator::{Accumulator, MembershipProof, NonmembershipProof, Witness};
+use crate::bigint::Integer;
+use crate::group::{ClassGroup, Rsa2048, UnknownOrderGroup};
+use crate::hash;
+use crate::store::Persist;
+use crate::vector_commitment::{VectorCommitment, VectorCommitmentManager, VectorProof};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use std::collections::HashSet;
+use std::fmt::Display;
+
+type Elem = Vec<u8>;
+
+#[derive(Clone)]
+// A value of type `R` for `Rsa2048`, or `C` for `ClassGroup`.
+enum Grouped<R, C> {
+  Rsa2048(R),
+  Class(C),
+}
+
+impl<R, C> Grouped<R, C> {
+  fn group(&self) -> &'static str {
+    match self {
+      Grouped::Rsa2048(_) => "rsa2048",
+      Grouped::Class(_) => "class",
+    }
+  }
+}
+
+// Evaluates `$body` with `$val` bound to the value in `$grouped`, whatever its group.
+macro_rules! with {
+  ($grouped:expr, |$val:ident| $body:expr) => {
+    match $grouped {
+      Grouped::Rsa2048($val) => $body,
+      Grouped::Class($val) => $body,
+    }
+  };
+}
+
+// Like `with`, but wraps the result back into the group of `$grouped`.
+macro_rules! map {
+  ($grouped:expr, |$val:ident| $body:expr) => {
+    match $grouped {
+      Grouped::Rsa2048($val) => Grouped::Rsa2048($body),
+      Grouped::Class($val) => Grouped::Class($body),
+    }
+  };
+}
+
+// Like `with`, but for two values, failing unless they have the same group.
+macro_rules! with2 {
+  ($a:expr, $b:expr, |$x:ident, $y:ident| $body:expr) => {
+    match ($a, $b) {
+      (Grouped::Rsa2048($x), Grouped::Rsa2048($y)) => $body,
+      (Grouped::Class($x), Grouped::Class($y)) => $body,
+      _ => return Err(PyValueError::new_err("objects belong to different groups")),
+    }
+  };
+}
+
+fn value_error<E: Display>(err: E) -> PyErr {
+  PyValueError::new_err(err.to_string())
+}
+
+fn to_bytes<'py, T: Persist>(py: Python<'py>, val: &T) -> Bound<'py, PyBytes> {
+  let mut bytes = vec![];
+  val.encode(&mut bytes);
+  PyBytes::new_bound(py, &bytes)
+}
+
+fn from_bytes<R: Persist, C: Persist>(mut data: &[u8], group: &str) -> PyResult<Grouped<R, C>> {
+  let val = match group {
+    "rsa2048" => R::decode(&mut data).map(Grouped::Rsa2048),
+    "class" => C::decode(&mut data).map(Grouped::Class),
+    _ => return Err(PyValueError::new_err(format!("unknown group `{}`", group))),
+  };
+  match val {
+    Some(val) if data.is_empty() => Ok(val),
+    _ => Err(PyValueError::new_err("malformed data")),
+  }
+}
+
+// Converts an `Integer` to a Python `int`.
+fn to_int(py: Python, x: &Integer) -> PyResult<PyObject> {
+  let int = py.import_bound("builtins")?.getattr("int")?;
+  Ok(int.call1((x.to_string(),))?.unbind())
+}
+
+// Computes a witness for `elems` from the accumulated `set`, failing if `elems` is not a subset.
+fn witness<G: UnknownOrderGroup>(set: &[Elem], elems: &[Elem]) -> PyResult<Witness<G, Elem>> {
+  let mut others: HashSet<&Elem> = set.iter().collect();
+  for elem in elems {
+    if !others.remove(elem) {
+      return Err(PyValueError::new_err(
+        "element is not in the accumulated set",
+      ));
+    }
+  }
+  let others: Vec<Elem> = others.into_iter().cloned().collect();
+  Ok(Witness(Accumulator::empty().add(&others)))
+}
+
+fn bits(bits: &[(bool, u64)]) -> Vec<(bool, Integer)> {
+  bits
+    .iter()
+    .map(|(bit, i)| (*bit, Integer::from(*i)))
+    .collect()
+}
+
+/// Returns the prime that `data` hashes to, as used for accumulated elements.
+#[pyfunction]
+fn hash_to_prime(py: Python, data: &[u8]) -> PyResult<PyObject> {
+  to_int(py, &hash::hash_to_prime(data))
+}
+
+/// An accumulator of byte strings.
+#[pyclass(name = "Accumulator", module = "accumulator")]
+#[derive(Clone)]
+struct PyAccumulator(Grouped<Accumulator<Rsa2048, Elem>, Accumulator<ClassGroup, Elem>>);
+
+/// A batch membership proof.
+#[pyclass(name = "MembershipProof", module = "accumulator")]
+#[derive(Clone)]
+struct PyMembershipProof(
+  Grouped<MembershipProof<Rsa2048, Elem>, MembershipProof<ClassGroup, Elem>>,
+);
+
+/// A batch nonmembership proof.
+#[pyclass(name = "NonmembershipProof", module = "accumulator")]
+#[derive(Clone)]
+struct PyNonmembershipProof(
+  Grouped<NonmembershipProof<Rsa2048, Elem>, NonmembershipProof<ClassGroup, Elem>>,
+);
+
+#[pymethods]
+impl PyAccumulator {
+  /// Returns a new, empty accumulator over `group`.
+  #[new]
+  #[pyo3(signature = (group = "rsa2048"))]
+  fn new(group: &str) -> PyResult<Self> {
+    match group {
+      "rsa2048" => Ok(Self(Grouped::Rsa2048(Accumulator::empty()))),
+      "class" => Ok(Self(Grouped::Class(Accumulator::empty()))),
+      _ => Err(PyValueError::new_err(format!("unknown group `{}`", group))),
+    }
+  }
+
+  /// The group of this accumulator.
+  #[getter]
+  fn group(&self) -> &'static str {
+    self.0.group()
+  }
+
+  /// Adds `elems`, returning a membership proof for them.
+  fn add(&mut self, elems: Vec<Elem>) -> PyMembershipProof {
+    PyMembershipProof(map!(&mut self.0, |acc| {
+      let (new_acc, proof) = acc.clone().add_with_proof(&elems);
+      *acc = new_acc;
+      proof
+    }))
+  }
+
+  /// Deletes `elems`, given a membership proof for them (such as one from `add` or
+  /// `prove_membership`).
+  fn delete(&mut self, elems: Vec<Elem>, proof: &PyMembershipProof) -> PyResult<()> {
+    with2!(&mut self.0, &proof.0, |acc, proof| {
+      let elem_witnesses = proof.witness.compute_individual_witnesses(&elems);
+      *acc = acc.clone().delete(&elem_witnesses).map_err(value_error)?;
+    });
+    Ok(())
+  }
+
+  /// Proves that `elems` are in this accumulator, whose accumulated elements are `acc_set`.
+  fn prove_membership(&self, acc_set: Vec<Elem>, elems: Vec<Elem>) -> PyResult<PyMembershipProof> {
+    Ok(PyMembershipProof(match &self.0 {
+      Grouped::Rsa2048(acc) => {
+        Grouped::Rsa2048(acc.prove_membership_with_witness(&elems, witness(&acc_set, &elems)?))
+      }
+      Grouped::Class(acc) => {
+        Grouped::Class(acc.prove_membership_with_witness(&elems, witness(&acc_set, &elems)?))
+      }
+    }))
+  }
+
+  /// Proves that `elems` are not in this accumulator, whose accumulated elements are `acc_set`.
+  fn prove_nonmembership(
+    &self,
+    acc_set: Vec<Elem>,
+    elems: Vec<Elem>,
+  ) -> PyResult<PyNonmembershipProof> {
+    Ok(PyNonmembershipProof(map!(&self.0, |acc| acc
+      .prove_nonmembership(&acc_set, &elems)
+      .map_err(value_error)?)))
+  }
+
+  /// Verifies a membership proof for `elems`.
+  fn verify_membership(&self, elems: Vec<Elem>, proof: &PyMembershipProof) -> PyResult<bool> {
+    Ok(with2!(&self.0, &proof.0, |acc, proof| acc
+      .verify_membership_batch(&elems, proof)))
+  }
+
+  /// Verifies a nonmembership proof for `elems`.
+  fn verify_nonmembership(&self, elems: Vec<Elem>, proof: &PyNonmembershipProof) -> PyResult<bool> {
+    Ok(with2!(&self.0, &proof.0, |acc, proof| acc
+      .verify_nonmembership(&elems, proof)))
+  }
+
+  /// Serializes this accumulator.
+  fn to_bytes<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+    with!(&self.0, |acc| to_bytes(py, acc))
+  }
+
+  /// Deserializes an accumulator over `group`.
+  #[staticmethod]
+  #[pyo3(signature = (data, group = "rsa2048"))]
+  fn from_bytes(data: &[u8], group: &str) -> PyResult<Self> {
+    from_bytes(data, group).map(Self)
+  }
+
+  fn __eq__(&self, other: &Self) -> bool {
+    match (&self.0, &other.0) {
+      (Grouped::Rsa2048(a), Grouped::Rsa2048(b)) => a == b,
+      (Grouped::Class(a), Grouped::Class(b)) => a == b,
+      _ => false,
+    }
+  }
+}
+
+#[pymethods]
+impl PyMembershipProof {
+  /// The group of this proof.
+  #[getter]
+  fn group(&self) -> &'static str {
+    self.0.group()
+  }
+
+  /// Serializes this proof.
+  fn to_bytes<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+    with!(&self.0, |proof| to_bytes(py, proof))
+  }
+
+  /// Deserializes a proof over `group`.
+  #[staticmethod]
+  #[pyo3(signature = (data, group = "rsa2048"))]
+  fn from_bytes(data: &[u8], group: &str) -> PyResult<Self> {
+    from_bytes(data, group).map(Self)
+  }
+}
+
+#[pymethods]
+impl PyNonmembershipProof {
+  /// The group of this proof.
+  #[getter]
+  fn group(&self) -> &'static str {
+    self.0.group()
+  }
+
+  /// Serializes this proof.
+  fn to_bytes<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+    with!(&self.0, |proof| to_bytes(py, proof))
+  }
+
+  /// Deserializes a proof over `group`.
+  #[staticmethod]
+  #[pyo3(signature = (data, group = "rsa2048"))]
+  fn from_bytes(data: &[u8], group: &str) -> PyResult<Self> {
+    from_bytes(data, group).map(Self)
+  }
+}
+
+/// A commitment to a vector of bits, which keeps the vector so it can open any of them. Bits are
+/// written and opened as `(bit, index)` pairs.
+#[pyclass(name = "VectorCommitment", module = "accumulator")]
+struct PyVectorCommitment(
+  Grouped<VectorCommitmentManager<Rsa2048>, VectorCommitmentManager<ClassGroup>>,
+);
+
+/// A proof of some bits of a vector commitment.
+#[pyclass(name = "VectorProof", module = "accumulator")]
+#[derive(Clone)]
+struct PyVectorProof(Grouped<VectorProof<Rsa2048>, VectorProof<ClassGroup>>);
+
+#[pymethods]
+impl PyVectorCommitment {
+  /// Returns a commitment to the all-zero vector over `group`.
+  #[new]
+  #[pyo3(signature = (group = "rsa2048"))]
+  fn new(group: &str) -> PyResult<Self> {
+    match group {
+      "rsa2048" => Ok(Self(Grouped::Rsa2048(VectorCommitmentManager::new()))),
+      "class" => Ok(Self(Grouped::Class(VectorCommitmentManager::new()))),
+      _ => Err(PyValueError::new_err(format!("unknown group `{}`", group))),
+    }
+  }
+
+  /// The group of this commitment.
+  #[getter]
+  fn group(&self) -> &'static str {
+    self.0.group()
+  }
+
+  /// Returns the bit at index `i`.
+  fn get(&self, i: u64) -> bool {
+    with!(&self.0, |manager| manager.get(&Integer::from(i)))
+  }
+
+  /// Writes `bits`, returning a proof of their new values.
+  fn set(&mut self, bits: Vec<(bool, u64)>) -> PyResult<PyVectorProof> {
+    let bits = self::bits(&bits);
+    Ok(PyVectorProof(map!(&mut self.0, |manager| manager
+      .set(&bits)
+      .map_err(value_error)?)))
+  }
+
+  /// Returns the bits at `indices` along with a proof of their values.
+  fn open(&mut self, indices: Vec<u64>) -> PyResult<(Vec<bool>, PyVectorProof)> {
+    let indices: Vec<Integer> = indices.into_iter().map(Integer::from).collect();
+    let (values, proof) = match &mut self.0 {
+      Grouped::Rsa2048(manager) => {
+        let (values, proof) = manager.get_with_proof(&indices).map_err(value_error)?;
+        (values, Grouped::Rsa2048(proof))
+      }
+      Grouped::Class(manager) => {
+        let (values, proof) = manager.get_with_proof(&indices).map_err(value_error)?;
+        (values, Grouped::Class(proof))
+      }
+    };
+    Ok((values, PyVectorProof(proof)))
+  }
+
+  /// Verifies a proof of `bits` against this commitment.
+  fn verify(&self, bits: Vec<(bool, u64)>, proof: &PyVectorProof) -> PyResult<bool> {
+    let bits = self::bits(&bits);
+    Ok(with2!(&self.0, &proof.0, |manager, proof| {
+      VectorCommitment::verify(manager.commitment(), &bits, proof)
+    }))
+  }
+
+  /// Serializes the commitment, which `verify_commitment` checks proofs against.
+  fn to_bytes<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+    with!(&self.0, |manager| to_bytes(py, manager.commitment()))
+  }
+
+  /// Verifies a proof of `bits` against a serialized commitment of the proof's group.
+  #[staticmethod]
+  fn verify_commitment(
+    commitment: &[u8],
+    bits: Vec<(bool, u64)>,
+    proof: &PyVectorProof,
+  ) -> PyResult<bool> {
+    let bits = self::bits(&bits);
+    let commitment: Grouped<VectorCommitment<Rsa2048>, VectorCommitment<ClassGroup>> =
+      from_bytes(commitment, proof.0.group())?;
+    Ok(with2!(&commitment, &proof.0, |vc, proof| {
+      VectorCommitment::verify(vc, &bits, proof)
+    }))
+  }
+}
+
+#[pymethods]
+impl PyVectorProof {
+  /// The group of this proof.
+  #[getter]
+  fn group(&self) -> &'static str {
+    self.0.group()
+  }
+
+  /// Serializes this proof.
+  fn to_bytes<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+    with!(&self.0, |proof| to_bytes(py, proof))
+  }
+
+  /// Deserializes a proof over `group`.
+  #[staticmethod]
+  #[pyo3(signature = (data, group = "rsa2048"))]
+  fn from_bytes(data: &[u8], group: &str) -> PyResult<Self> {
+    from_bytes(data, group).map(Self)
+  }
+}
+
+/// Cryptographic accumulators and vector commitments over RSA-2048 and class groups.
+#[pymodule]
+#[pyo3(name = "accumulator")]
+fn init(m: &Bound<'_, PyModule>) -> PyResult<()> {
+  m.add_function(wrap_pyfunction!(hash_to_prime, m)?)?;
+  m.add_class::<PyAccumulator>()?;
+  m.add_class::<PyMembershipProof>()?;
+  m.add_class::<PyNonmembershipProof>()?;
+  m.add_class::<PyVectorCommitment>()?;
+  m.add_class::<PyVectorProof>()?;
+  Ok(())
+}
@@ -0,0 +1,465 @@
+//! Credential revocation registries, built on the accumulator.
+//!
+//! An `Issuer` hands out credentials, each identified by an ID, and later revokes some of them.
+//! Every change to the registry is published as a `RegistryDelta`, which carries a `CatchUpProof`
+//! from the previous accumulator to the new one. Holders apply the deltas to their `Credential`s,
+//! which keeps their witnesses current, and show verifiers a `NonRevocationProof` against the
+//! latest accumulator.
+//!
+//! The registry works in one of two modes (see `RevocationMode`). In `Membership` mode the
+//! accumulator holds the IDs of valid credentials, so issuing and revoking both change it. In
+//! `Nonmembership` mode it holds the IDs of revoked credentials, so issuing leaves it alone and
+//! holders prove nonmembership instead.
+use crate::accumulator::{
+  AccError, Accumulator, MembershipProof, NonmembershipProof, NonmembershipWitness, Witness,
+};
+use crate::bigint::Integer;
+use crate::group::UnknownOrderGroup;
+use crate::hash::hash_to_prime;
+use crate::history::CatchUpProof;
+use crate::util::{int, prime_hash_product, PrimeHashable};
+use std::collections::HashSet;
+use std::error;
+use std::fmt;
+use std::hash::Hash;
+use std::slice;
+
+#[derive(Debug)]
+/// The different types of revocation errors.
+pub enum RevocationError {
+  /// A credential ID that has been issued before, or that appears twice in a batch.
+  DuplicateCredential,
+  /// A credential that has not been issued, or that has already been revoked.
+  UnknownCredential,
+  /// The credential has been revoked.
+  Revoked,
+  /// A delta that does not follow from the holder's view of the registry.
+  InvalidDelta,
+  /// An accumulator operation failed, e.g. because two credential IDs share a prime hash.
+  Accumulator(AccError),
+}
+
+impl fmt::Display for RevocationError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let msg = match self {
+      RevocationError::DuplicateCredential => {
+        "credential has already been issued, or appears twice"
+      }
+      RevocationError::UnknownCredential => {
+        "credential has not been issued, or has already been revoked"
+      }
+      RevocationError::Revoked => "credential has been revoked",
+      RevocationError::InvalidDelta => "delta does not follow from the previous registry state",
+      RevocationError::Accumulator(err) => return write!(f, "accumulator error: {}", err),
+    };
+    f.write_str(msg)
+  }
+}
+
+impl error::Error for RevocationError {
+  fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+    match self {
+      RevocationError::Accumulator(err) => Some(err),
+      _ => None,
+    }
+  }
+}
+
+impl From<AccError> for RevocationError {
+  fn from(err: AccError) -> Self {
+    RevocationError::Accumulator(err)
+  }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+/// What a registry accumulates.
+pub enum RevocationMode {
+  /// The accumulator holds valid IDs. Holders prove membership.
+  Membership,
+  /// The accumulator holds revoked IDs. Holders prove nonmembership.
+  Nonmembership,
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+/// A change to a registry: the credentials issued and revoked in one epoch.
+pub struct RegistryDelta<G: UnknownOrderGroup, T: Hash> {
+  mode: RevocationMode,
+  epoch: u64,
+  issued: Vec<T>,
+  revoked: Vec<T>,
+  acc: Accumulator<G, T>,
+  proof: CatchUpProof<G, T>,
+}
+
+impl<G: UnknownOrderGroup, T: Clone + Eq + PrimeHashable> RegistryDelta<G, T> {
+  /// Returns the epoch of the registry after this delta.
+  pub fn epoch(&self) -> u64 {
+    self.epoch
+  }
+
+  /// Returns the IDs of the credentials issued.
+  pub fn issued(&self) -> &[T] {
+    &self.issued
+  }
+
+  /// Returns the IDs of the credentials revoked.
+  pub fn revoked(&self) -> &[T] {
+    &self.revoked
+  }
+
+  /// Returns the accumulator after this delta.
+  pub fn accumulator(&self) -> &Accumulator<G, T> {
+    &self.acc
+  }
+
+  /// Verifies that this delta follows from `prev_acc`, the accumulator at the previous epoch.
+  pub fn verify(&self, prev_acc: &Accumulator<G, T>) -> bool {
+    let (deletions, additions): (&[T], &[T]) = match self.mode {
+      RevocationMode::Membership => (&self.revoked, &self.issued),
+      RevocationMode::Nonmembership => (&[], &self.revoked),
+    };
+    self.proof.deletions() == deletions
+      && self.proof.additions() == additions
+      && self.proof.verify(prev_acc, &self.acc)
+  }
+}
+
+fn has_duplicates<T: Eq + Hash>(ids: &[T]) -> bool {
+  let mut seen = HashSet::new();
+  !ids.iter().all(|id| seen.insert(id))
+}
+
+/// The issuer of credentials, who maintains the registry.
+pub struct Issuer<G: UnknownOrderGroup, T: Hash> {
+  mode: RevocationMode,
+  epoch: u64,
+  acc: Accumulator<G, T>,
+  // Prime hash product of the accumulated IDs (the valid ones in `Membership` mode, the revoked
+  // ones otherwise), kept up to date so that the IDs need not be hashed again.
+  x: Integer,
+  valid: HashSet<T>,
+  revoked: HashSet<T>,
+}
+
+impl<G: UnknownOrderGroup, T: Clone + Eq + PrimeHashable> Issuer<G, T> {
+  /// Creates an empty registry in the given `mode`, at epoch 0.
+  pub fn new(mode: RevocationMode) -> Self {
+    Self {
+      mode,
+      epoch: 0,
+      acc: Accumulator::empty(),
+      x: int(1),
+      valid: HashSet::new(),
+      revoked: HashSet::new(),
+    }
+  }
+
+  /// Returns the mode of the registry.
+  pub fn mode(&self) -> RevocationMode {
+    self.mode
+  }
+
+  /// Returns the current epoch.
+  pub fn epoch(&self) -> u64 {
+    self.epoch
+  }
+
+  /// Returns the current accumulator.
+  pub fn accumulator(&self) -> &Accumulator<G, T> {
+    &self.acc
+  }
+
+  /// Returns whether `id` has been issued and not revoked.
+  pub fn is_valid(&self, id: &T) -> bool {
+    self.valid.contains(id)
+  }
+
+  /// Returns whether `id` has been revoked.
+  pub fn is_revoked(&self, id: &T) -> bool {
+    self.revoked.contains(id)
+  }
+
+  /// Issues a credential for each of `ids`, none of which may have been issued before. Returns the
+  /// delta to publish along with the new credentials.
+  #[allow(clippy::type_complexity)]
+  pub fn issue(
+    &mut self,
+    ids: &[T],
+  ) -> Result<(RegistryDelta<G, T>, Vec<Credential<G, T>>), RevocationError> {
+    if has_duplicates(ids)
+      || ids
+        .iter()
+        .any(|id| self.is_valid(id) || self.is_revoked(id))
+    {
+      return Err(RevocationError::DuplicateCredential);
+    }
+    // Issuing leaves a `Nonmembership` mode accumulator alone, so its witnesses can be computed
+    // before publishing, which keeps the registry unchanged if one of them fails.
+    let nonmembership_witnesses = match self.mode {
+      RevocationMode::Membership => vec![],
+      RevocationMode::Nonmembership => ids
+        .iter()
+        .enumerate()
+        .map(|(index, id)| {
+          Accumulator::<G, T>::compute_nonmembership_witness_product(&self.x, &hash_to_prime(id))
+            .ok_or(AccError::InputsNotCoprime { index })
+        })
+        .collect::<Result<Vec<_>, _>>()?,
+    };
+    let delta = self.publish(ids, &[])?;
+    let credentials = match self.mode {
+      RevocationMode::Membership => delta
+        .proof
+        .addition_witness()
+        .compute_individual_witnesses(ids)
+        .into_iter()
+        .map(|(id, witness)| Credential::new(&delta, id, CredentialWitness::Membership(witness)))
+        .collect(),
+      RevocationMode::Nonmembership => ids
+        .iter()
+        .zip(nonmembership_witnesses)
+        .map(|(id, witness)| {
+          Credential::new(
+            &delta,
+            id.clone(),
+            CredentialWitness::Nonmembership(witness),
+          )
+        })
+        .collect(),
+    };
+    self.valid.extend(ids.iter().cloned());
+    Ok((delta, credentials))
+  }
+
+  /// Revokes the credentials for `ids`, all of which must be valid. Returns the delta to publish.
+  pub fn revoke(&mut self, ids: &[T]) -> Result<RegistryDelta<G, T>, RevocationError> {
+    if has_duplicates(ids) || ids.iter().any(|id| !self.is_valid(id)) {
+      return Err(RevocationError::UnknownCredential);
+    }
+    let delta = self.publish(&[], ids)?;
+    for id in ids {
+      self.valid.remove(id);
+      self.revoked.insert(id.clone());
+    }
+    Ok(delta)
+  }
+
+  // Moves the registry to the next epoch, accumulating `issued` and `revoked` according to the
+  // mode. Assumes both have been checked against the registry. On error, the registry is left as
+  // it was.
+  fn publish(&mut self, issued: &[T], revoked: &[T]) -> Result<RegistryDelta<G, T>, AccError> {
+    let (deletions, additions) = match self.mode {
+      RevocationMode::Membership => (revoked, issued),
+      RevocationMode::Nonmembership => (&[][..], revoked),
+    };
+    let deletions_x = prime_hash_product(deletions);
+    // A witness for the deletions is the accumulator of the remaining IDs, whose prime hash product
+    // is at hand. Without deletions, it is the accumulator itself.
+    let remaining_x = int(&self.x / &deletions_x);
+    let witness = if deletions.is_empty() {
+      Witness(self.acc.clone())
+    } else {
+      Witness(Accumulator::empty().add_product(&remaining_x))
+    };
+    let deletion_proof = self.acc.prove_membership_product(&deletions_x, witness);
+    let (acc, proof) = CatchUpProof::prove_update(&self.acc, deletions, deletion_proof, additions)?;
+    self.epoch += 1;
+    self.acc = acc.clone();
+    self.x = remaining_x * prime_hash_product(additions);
+    Ok(RegistryDelta {
+      mode: self.mode,
+      epoch: self.epoch,
+      issued: issued.to_vec(),
+      revoked: revoked.to_vec(),
+      acc,
+      proof,
+    })
+  }
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+enum CredentialWitness<G: UnknownOrderGroup, T: Hash> {
+  Membership(Witness<G, T>),
+  Nonmembership(NonmembershipWitness<G, T>),
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+/// A credential, as kept by its holder: its ID and a witness that it has not been revoked as of
+/// the holder's latest view of the registry.
+pub struct Credential<G: UnknownOrderGroup, T: Hash> {
+  id: T,
+  epoch: u64,
+  acc: Accumulator<G, T>,
+  witness: CredentialWitness<G, T>,
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+/// A proof that a credential has not been revoked.
+pub enum NonRevocationProof<G: UnknownOrderGroup, T: Hash> {
+  /// Membership of the ID in a `Membership` mode registry.
+  Membership(MembershipProof<G, T>),
+  /// Nonmembership of the ID in a `Nonmembership` mode registry.
+  Nonmembership(NonmembershipProof<G, T>),
+}
+
+impl<G: UnknownOrderGroup, T: Clone + Eq + PrimeHashable> Credential<G, T> {
+  fn new(delta: &RegistryDelta<G, T>, id: T, witness: CredentialWitness<G, T>) -> Self {
+    Self {
+      id,
+      epoch: delta.epoch,
+      acc: delta.acc.clone(),
+      witness,
+    }
+  }
+
+  /// Returns the ID of the credential.
+  pub fn id(&self) -> &T {
+    &self.id
+  }
+
+  /// Returns the epoch of the registry that the witness is current for.
+  pub fn epoch(&self) -> u64 {
+    self.epoch
+  }
+
+  /// Returns the accumulator that the witness is current for.
+  pub fn accumulator(&self) -> &Accumulator<G, T> {
+    &self.acc
+  }
+
+  /// Verifies `delta` as the next delta of the registry and updates the witness to match. Fails
+  /// with `Revoked` if `delta` revokes this credential, in which case the witness is unchanged.
+  pub fn apply(&mut self, delta: &RegistryDelta<G, T>) -> Result<(), RevocationError> {
+    if delta.epoch != self.epoch + 1 || !delta.verify(&self.acc) {
+      return Err(RevocationError::InvalidDelta);
+    }
+    if delta.revoked.contains(&self.id) {
+      return Err(RevocationError::Revoked);
+    }
+    let id = [self.id.clone()];
+    let witness = match &self.witness {
+      CredentialWitness::Membership(witness) => CredentialWitness::Membership(
+        delta
+          .acc
+          .update_membership_witness(witness.clone(), &id, &delta.issued, &delta.revoked)
+          .map_err(|_| RevocationError::InvalidDelta)?,
+      ),
+      CredentialWitness::Nonmembership(witness) => CredentialWitness::Nonmembership(
+        self
+          .acc
+          .update_nonmembership_witness(witness.clone(), &id, &delta.revoked)
+          .map_err(|_| RevocationError::InvalidDelta)?,
+      ),
+    };
+    self.epoch = delta.epoch;
+    self.acc = delta.acc.clone();
+    self.witness = witness;
+    Ok(())
+  }
+
+  /// Proves that the credential has not been revoked as of the current epoch.
+  pub fn prove(&self) -> NonRevocationProof<G, T> {
+    let id = [self.id.clone()];
+    match &self.witness {
+      CredentialWitness::Membership(witness) => {
+        NonRevocationProof::Membership(self.acc.prove_membership_with_witness(&id, witness.clone()))
+      }
+      CredentialWitness::Nonmembership(witness) => {
+        NonRevocationProof::Nonmembership(self.acc.prove_nonmembership_with_witness(&id, witness))
+      }
+    }
+  }
+}
+
+impl<G: UnknownOrderGroup, T: Clone + Eq + PrimeHashable> NonRevocationProof<G, T> {
+  /// Verifies that the credential `id` has not been revoked in a registry of the given `mode`
+  /// whose current accumulator is `acc`.
+  pub fn verify(&self, mode: RevocationMode, acc: &Accumulator<G, T>, id: &T) -> bool {
+    match (mode, self) {
+      (RevocationMode::Membership, NonRevocationProof::Membership(proof)) => {
+        acc.verify_membership(id, proof)
+      }
+      (RevocationMode::Nonmembership, NonRevocationProof::Nonmembership(proof)) => {
+        acc.verify_nonmembership(slice::from_ref(id), proof)
+      }
+      _ => false,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::group::Rsa2048;
+
+  fn test_registry(mode: RevocationMode) {
+    let mut issuer = Issuer::<Rsa2048, u64>::new(mode);
+    let (delta_1, mut credentials) = issuer.issue(&[1, 2, 3]).unwrap();
+    let (delta_2, new_credentials) = issuer.issue(&[4, 5]).unwrap();
+    let delta_3 = issuer.revoke(&[2, 5]).unwrap();
+    assert!(issuer.is_revoked(&2) && !issuer.is_valid(&2) && issuer.is_valid(&4));
+    assert!(delta_1.verify(&Accumulator::empty()));
+    assert!(delta_2.verify(delta_1.accumulator()));
+    assert!(delta_3.verify(delta_2.accumulator()));
+    assert!(!delta_3.verify(delta_3.accumulator()));
+    assert!(Accumulator::empty().add_product(&issuer.x) == *issuer.accumulator());
+    credentials.extend(new_credentials);
+
+    let acc = issuer.accumulator();
+    for mut credential in credentials {
+      let id = *credential.id();
+      let revoked = id == 2 || id == 5;
+      if credential.epoch() == 1 {
+        assert!(credential.apply(&delta_3).is_err());
+        credential.apply(&delta_2).unwrap();
+      }
+      let stale_proof = credential.prove();
+      match credential.apply(&delta_3) {
+        Ok(()) => assert!(!revoked),
+        Err(RevocationError::Revoked) => assert!(revoked),
+        Err(_) => panic!("unexpected error"),
+      }
+      let proof = credential.prove();
+      assert_eq!(proof.verify(mode, acc, &id), !revoked);
+      assert!(!proof.verify(mode, acc, &(id + 1)));
+      assert!(stale_proof.verify(mode, delta_2.accumulator(), &id));
+      if revoked {
+        assert!(!stale_proof.verify(mode, acc, &id));
+      }
+    }
+  }
+
+  #[test]
+  fn test_membership_registry() {
+    test_registry(RevocationMode::Membership);
+  }
+
+  #[test]
+  fn test_nonmembership_registry() {
+    test_registry(RevocationMode::Nonmembership);
+  }
+
+  #[test]
+  fn test_registry_rejects_bad_input() {
+    for &mode in &[RevocationMode::Membership, RevocationMode::Nonmembership] {
+      let mut issuer = Issuer::<Rsa2048, u64>::new(mode);
+      let (_, credentials) = issuer.issue(&[1, 2]).unwrap();
+      assert!(issuer.issue(&[3, 3]).is_err());
+      assert!(issuer.issue(&[2]).is_err());
+      assert!(issuer.revoke(&[3]).is_err());
+      issuer.revoke(&[1]).unwrap();
+      assert!(issuer.revoke(&[1]).is_err());
+      assert!(issuer.issue(&[1]).is_err());
+      assert_eq!(issuer.epoch(), 2);
+
+      // Proofs are only accepted in the mode of the registry.
+      let proof = credentials[1].prove();
+      let other_mode = match mode {
+        RevocationMode::Membership => RevocationMode::Nonmembership,
+        RevocationMode::Nonmembership => RevocationMode::Membership,
+      };
+      assert!(proof.verify(mode, credentials[1].accumulator(), &2));
+      assert!(!proof.verify(other_mode, credentials[1].accumulator(), &2));
+    }
+  }
+}
@@ -0,0 +1,526 @@
+//! A JSON-RPC 2.0 service that manages an accumulator of strings, for use from other languages.
+//!
+//! A `Server` holds an `Accumulator` together with its element set and an epoch counter, which
+//! every successful `add` or `delete` advances by one. It answers JSON-RPC requests sent as the
+//! body of an HTTP `POST` to any path, one request per connection. Requests are handled one at a
+//! time, so updates are applied in the order they arrive. Since a slow client holds up everyone
+//! else, each connection gets a fixed time to send its request (see `Server::set_timeout`), and
+//! request headers are capped in size.
+//!
+//! Accumulator values and proofs are passed as hex strings of their `Persist` encoding (see the
+//! `store` module), and elements as JSON strings. The methods are:
+//!
+//! * `state()` - Returns `{"value", "epoch", "size"}` for the current accumulator.
+//! * `add({"elems"})` - Adds elements, none of which may already be accumulated. Returns
+//!   `{"value", "epoch", "proof"}`, where `proof` is a membership proof for the added elements.
+//! * `delete({"elems"})` - Deletes accumulated elements. Returns `{"value", "epoch", "proof"}`,
+//!   where `proof` is a membership proof for the deleted elements in the previous accumulator.
+//! * `prove_membership({"elems"})` - Returns `{"proof"}` for accumulated elements.
+//! * `prove_nonmembership({"elems"})` - Returns `{"proof"}` for elements that are not accumulated.
+//! * `verify({"kind", "elems", "proof", "value"?})` - Returns `{"valid"}` for a proof of the given
+//!   `kind` (`membership` or `nonmembership`), against `value` if given and the current accumulator
+//!   otherwise.
+//!
+//! Failed updates leave the state untouched and are reported as JSON-RPC errors, with code
+//! `APP_ERROR` for requests that are well-formed but cannot be carried out. `Client` wraps the
+//! protocol for Rust callers.
+use crate::accumulator::{Accumulator, MembershipProof, NonmembershipProof, Witness};
+use crate::group::UnknownOrderGroup;
+use crate::store::Persist;
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::error;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+
+/// The JSON-RPC error code for requests that are not valid JSON.
+pub const PARSE_ERROR: i64 = -32700;
+/// The JSON-RPC error code for JSON that is not a valid request.
+pub const INVALID_REQUEST: i64 = -32600;
+/// The JSON-RPC error code for unknown methods.
+pub const METHOD_NOT_FOUND: i64 = -32601;
+/// The JSON-RPC error code for missing or malformed parameters.
+pub const INVALID_PARAMS: i64 = -32602;
+/// The error code for requests that are well-formed but cannot be carried out, such as adding an
+/// element twice.
+pub const APP_ERROR: i64 = -32000;
+
+// Bodies larger than this are rejected, since a request is read fully into memory.
+const MAX_BODY_LEN: usize = 1 << 24;
+// Limit on the request line and headers together.
+const MAX_HEADER_LEN: u64 = 1 << 16;
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug)]
+/// The different types of service errors.
+pub enum RpcError {
+  /// Connecting to or talking with the server failed.
+  Io(io::Error),
+  /// The server answered with a JSON-RPC error of the given code and message.
+  Remote(i64, String),
+  /// The server's answer could not be parsed.
+  Malformed,
+}
+
+impl fmt::Display for RpcError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      RpcError::Io(err) => write!(f, "I/O error: {}", err),
+      RpcError::Remote(code, message) => write!(f, "server error {}: {}", code, message),
+      RpcError::Malformed => f.write_str("malformed response"),
+    }
+  }
+}
+
+impl error::Error for RpcError {
+  fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+    match self {
+      RpcError::Io(err) => Some(err),
+      _ => None,
+    }
+  }
+}
+
+impl From<io::Error> for RpcError {
+  fn from(err: io::Error) -> Self {
+    RpcError::Io(err)
+  }
+}
+
+/// An accumulator service listening on a TCP socket.
+pub struct Server<G: UnknownOrderGroup> {
+  listener: TcpListener,
+  acc: Accumulator<G, String>,
+  elems: HashSet<String>,
+  epoch: u64,
+  timeout: Duration,
+}
+
+impl<G: UnknownOrderGroup> Server<G>
+where
+  G::Elem: Persist,
+{
+  /// Binds a server for an empty accumulator to `addr`. Bind to port 0 to pick a free port, which
+  /// `local_addr` then reports.
+  pub fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+    Ok(Self {
+      listener: TcpListener::bind(addr)?,
+      acc: Accumulator::empty(),
+      elems: HashSet::new(),
+      epoch: 0,
+      timeout: DEFAULT_TIMEOUT,
+    })
+  }
+
+  /// Sets how long a connection may take to send its request, and to accept each write of the
+  /// response, before it is dropped. Defaults to 10 seconds.
+  pub fn set_timeout(&mut self, timeout: Duration) {
+    self.timeout = timeout;
+  }
+
+  /// Returns the address the server is listening on.
+  pub fn local_addr(&self) -> io::Result<SocketAddr> {
+    self.listener.local_addr()
+  }
+
+  /// Serves requests until accepting a connection fails. Errors on individual connections are
+  /// dropped, since the client that caused them is gone.
+  pub fn serve(mut self) -> io::Result<()> {
+    loop {
+      let (stream, _) = self.listener.accept()?;
+      let _ = self.serve_connection(stream);
+    }
+  }
+
+  fn serve_connection(&mut self, mut stream: TcpStream) -> io::Result<()> {
+    stream.set_write_timeout(Some(self.timeout))?;
+    let request = read_http_request(&stream, Instant::now() + self.timeout)?;
+    let (status, body) = match request {
+      Some(request) => ("200 OK", self.handle(&request).to_string()),
+      None => ("400 Bad Request", String::new()),
+    };
+    write!(
+      stream,
+      "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+       Connection: close\r\n\r\n{}",
+      status,
+      body.len(),
+      body
+    )
+  }
+
+  /// Handles a JSON-RPC request, returning the response.
+  pub fn handle(&mut self, request: &[u8]) -> Value {
+    let request: Value = match serde_json::from_slice(request) {
+      Ok(request) => request,
+      Err(err) => return error_response(&Value::Null, PARSE_ERROR, &err.to_string()),
+    };
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+    let method = match request.get("method").and_then(Value::as_str) {
+      Some(method) if request.get("jsonrpc") == Some(&json!("2.0")) => method,
+      _ => return error_response(&id, INVALID_REQUEST, "invalid request"),
+    };
+    let params = request.get("params").cloned().unwrap_or(Value::Null);
+    match self.call(method, &params) {
+      Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+      Err((code, message)) => error_response(&id, code, &message),
+    }
+  }
+
+  fn call(&mut self, method: &str, params: &Value) -> Result<Value, (i64, String)> {
+    match method {
+      "state" => Ok(json!({
+        "value": to_hex(&self.acc),
+        "epoch": self.epoch,
+        "size": self.elems.len(),
+      })),
+      "add" => {
+        let elems = elems_param(params)?;
+        let mut seen = HashSet::new();
+        if let Some(elem) = elems
+          .iter()
+          .find(|elem| self.elems.contains(*elem) || !seen.insert(*elem))
+        {
+          return Err(app_error(format!("`{}` is already accumulated", elem)));
+        }
+        let (acc, proof) = self.acc.clone().add_with_proof(&elems);
+        self.acc = acc;
+        self.elems.extend(elems);
+        Ok(self.update(&proof))
+      }
+      "delete" => {
+        let elems = elems_param(params)?;
+        let others = self.others(&elems)?;
+        let witness = Witness(Accumulator::empty().add(&others));
+        let proof = self.acc.prove_membership_with_witness(&elems, witness);
+        self.acc = proof.witness.0.clone();
+        self.elems = others.into_iter().collect();
+        Ok(self.update(&proof))
+      }
+      "prove_membership" => {
+        let elems = elems_param(params)?;
+        let witness = Witness(Accumulator::empty().add(&self.others(&elems)?));
+        let proof = self.acc.prove_membership_with_witness(&elems, witness);
+        Ok(json!({ "proof": to_hex(&proof) }))
+      }
+      "prove_nonmembership" => {
+        let elems = elems_param(params)?;
+        let set: Vec<String> = self.elems.iter().cloned().collect();
+        let proof = self
+          .acc
+          .prove_nonmembership(&set, &elems)
+          .map_err(|err| app_error(err.to_string()))?;
+        Ok(json!({ "proof": to_hex(&proof) }))
+      }
+      "verify" => {
+        let elems = elems_param(params)?;
+        let acc = match params.get("value") {
+          Some(value) => hex_param(value)?,
+          None => self.acc.clone(),
+        };
+        let proof = params.get("proof").unwrap_or(&Value::Null);
+        let valid = match params.get("kind").and_then(Value::as_str) {
+          Some("membership") => acc.verify_membership_batch(&elems, &hex_param(proof)?),
+          Some("nonmembership") => acc.verify_nonmembership(&elems, &hex_param(proof)?),
+          _ => {
+            return Err(invalid_params(
+              "`kind` must be `membership` or `nonmembership`",
+            ))
+          }
+        };
+        Ok(json!({ "valid": valid }))
+      }
+      _ => Err((METHOD_NOT_FOUND, format!("unknown method `{}`", method))),
+    }
+  }
+
+  // Advances the epoch and returns the result of an update with the given `proof`.
+  fn update(&mut self, proof: &MembershipProof<G, String>) -> Value {
+    self.epoch += 1;
+    json!({ "value": to_hex(&self.acc), "epoch": self.epoch, "proof": to_hex(proof) })
+  }
+
+  // Returns the accumulated elements other than `elems`, failing if any of `elems` is not
+  // accumulated or appears twice.
+  fn others(&self, elems: &[String]) -> Result<Vec<String>, (i64, String)> {
+    let mut others = self.elems.clone();
+    for elem in elems {
+      if !others.remove(elem) {
+        return Err(app_error(format!("`{}` is not accumulated", elem)));
+      }
+    }
+    Ok(others.into_iter().collect())
+  }
+}
+
+/// A client for a `Server`, which opens a connection per request.
+pub struct Client<G: UnknownOrderGroup> {
+  addr: SocketAddr,
+  phantom: std::marker::PhantomData<G>,
+}
+
+impl<G: UnknownOrderGroup> Client<G>
+where
+  G::Elem: Persist,
+{
+  /// Returns a client for the server at `addr`.
+  pub fn new(addr: SocketAddr) -> Self {
+    Self {
+      addr,
+      phantom: std::marker::PhantomData,
+    }
+  }
+
+  /// Returns the server's current accumulator and epoch.
+  pub fn state(&self) -> Result<(Accumulator<G, String>, u64), RpcError> {
+    let result = self.call("state", Value::Null)?;
+    Ok((from_hex(&result["value"])?, epoch(&result)?))
+  }
+
+  /// Adds `elems` on the server, returning the new accumulator and epoch and a membership proof
+  /// for `elems`.
+  #[allow(clippy::type_complexity)]
+  pub fn add(
+    &self,
+    elems: &[&str],
+  ) -> Result<(Accumulator<G, String>, u64, MembershipProof<G, String>), RpcError> {
+    let result = self.call("add", json!({ "elems": elems }))?;
+    Ok((
+      from_hex(&result["value"])?,
+      epoch(&result)?,
+      from_hex(&result["proof"])?,
+    ))
+  }
+
+  /// Deletes `elems` on the server, returning the new accumulator and epoch and a membership proof
+  /// for `elems` in the previous accumulator.
+  #[allow(clippy::type_complexity)]
+  pub fn delete(
+    &self,
+    elems: &[&str],
+  ) -> Result<(Accumulator<G, String>, u64, MembershipProof<G, String>), RpcError> {
+    let result = self.call("delete", json!({ "elems": elems }))?;
+    Ok((
+      from_hex(&result["value"])?,
+      epoch(&result)?,
+      from_hex(&result["proof"])?,
+    ))
+  }
+
+  /// Requests a membership proof for `elems` in the server's current accumulator.
+  pub fn prove_membership(&self, elems: &[&str]) -> Result<MembershipProof<G, String>, RpcError> {
+    let result = self.call("prove_membership", json!({ "elems": elems }))?;
+    from_hex(&result["proof"])
+  }
+
+  /// Requests a nonmembership proof for `elems` in the server's current accumulator.
+  pub fn prove_nonmembership(
+    &self,
+    elems: &[&str],
+  ) -> Result<NonmembershipProof<G, String>, RpcError> {
+    let result = self.call("prove_nonmembership", json!({ "elems": elems }))?;
+    from_hex(&result["proof"])
+  }
+
+  /// Asks the server to verify a membership proof for `elems` in its current accumulator.
+  pub fn verify_membership(
+    &self,
+    elems: &[&str],
+    proof: &MembershipProof<G, String>,
+  ) -> Result<bool, RpcError> {
+    self.verify("membership", elems, &to_hex(proof))
+  }
+
+  /// Asks the server to verify a nonmembership proof for `elems` in its current accumulator.
+  pub fn verify_nonmembership(
+    &self,
+    elems: &[&str],
+    proof: &NonmembershipProof<G, String>,
+  ) -> Result<bool, RpcError> {
+    self.verify("nonmembership", elems, &to_hex(proof))
+  }
+
+  fn verify(&self, kind: &str, elems: &[&str], proof: &str) -> Result<bool, RpcError> {
+    let params = json!({ "kind": kind, "elems": elems, "proof": proof });
+    self.call("verify", params)?["valid"]
+      .as_bool()
+      .ok_or(RpcError::Malformed)
+  }
+
+  fn call(&self, method: &str, params: Value) -> Result<Value, RpcError> {
+    let request = json!({ "jsonrpc": "2.0", "id": 0, "method": method, "params": params });
+    let body = request.to_string();
+    let mut stream = TcpStream::connect(self.addr)?;
+    write!(
+      stream,
+      "POST / HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\n\
+       Content-Length: {}\r\n\r\n{}",
+      self.addr,
+      body.len(),
+      body
+    )?;
+    let mut response = vec![];
+    stream.read_to_end(&mut response)?;
+    let body = match response.windows(4).position(|window| window == b"\r\n\r\n") {
+      Some(i) => &response[i + 4..],
+      None => return Err(RpcError::Malformed),
+    };
+    let mut response: Value = serde_json::from_slice(body).map_err(|_| RpcError::Malformed)?;
+    if let Some(error) = response.get("error") {
+      let code = error["code"].as_i64().ok_or(RpcError::Malformed)?;
+      let message = error["message"].as_str().ok_or(RpcError::Malformed)?;
+      return Err(RpcError::Remote(code, message.to_string()));
+    }
+    match response.get_mut("result") {
+      Some(result) => Ok(result.take()),
+      None => Err(RpcError::Malformed),
+    }
+  }
+}
+
+// Reads from a stream, failing with `TimedOut` once `deadline` has passed. A read timeout alone
+// would let a client that trickles in bytes hold the connection indefinitely.
+struct DeadlineReader<'a> {
+  stream: &'a TcpStream,
+  deadline: Instant,
+}
+
+impl<'a> Read for DeadlineReader<'a> {
+  fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+    let now = Instant::now();
+    if now >= self.deadline {
+      return Err(io::ErrorKind::TimedOut.into());
+    }
+    self.stream.set_read_timeout(Some(self.deadline - now))?;
+    let mut stream = self.stream;
+    stream.read(buf)
+  }
+}
+
+// Reads an HTTP `POST` request from `stream` by `deadline`, returning its body, or `None` if the
+// request is not one or its headers are too long.
+fn read_http_request(stream: &TcpStream, deadline: Instant) -> io::Result<Option<Vec<u8>>> {
+  let mut reader = BufReader::new(DeadlineReader { stream, deadline }.take(MAX_HEADER_LEN));
+  let mut line = String::new();
+  reader.read_line(&mut line)?;
+  if !line.starts_with("POST ") {
+    return Ok(None);
+  }
+  let mut len = None;
+  loop {
+    line.clear();
+    if reader.read_line(&mut line)? == 0 {
+      return Ok(None);
+    }
+    let header = line.trim_end();
+    if header.is_empty() {
+      break;
+    }
+    if let Some((name, value)) = header.split_once(':') {
+      if name.eq_ignore_ascii_case("content-length") {
+        len = value.trim().parse::<usize>().ok();
+      }
+    }
+  }
+  match len {
+    Some(len) if len <= MAX_BODY_LEN => {
+      // Part of the body may already be buffered, so this limit only needs to cover the rest.
+      reader.get_mut().set_limit(len as u64);
+      let mut body = vec![0; len];
+      reader.read_exact(&mut body)?;
+      Ok(Some(body))
+    }
+    _ => Ok(None),
+  }
+}
+
+fn error_response(id: &Value, code: i64, message: &str) -> Value {
+  json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+fn app_error(message: String) -> (i64, String) {
+  (APP_ERROR, message)
+}
+
+fn invalid_params(message: &str) -> (i64, String) {
+  (INVALID_PARAMS, message.to_string())
+}
+
+fn elems_param(params: &Value) -> Result<Vec<String>, (i64, String)> {
+  params
+    .get("elems")
+    .and_then(Value::as_array)
+    .and_then(|elems| {
+      elems
+        .iter()
+        .map(|elem| elem.as_str().map(String::from))
+        .collect()
+    })
+    .ok_or_else(|| invalid_params("`elems` must be an array of strings"))
+}
+
+fn hex_param<T: Persist>(value: &Value) -> Result<T, (i64, String)> {
+  from_hex(value).map_err(|_| invalid_params("malformed hex value"))
+}
+
+fn epoch(result: &Value) -> Result<u64, RpcError> {
+  result["epoch"].as_u64().ok_or(RpcError::Malformed)
+}
+
+fn to_hex<T: Persist>(val: &T) -> String {
+  let mut bytes = vec![];
+  val.encode(&mut bytes);
+  bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+// Decodes a hex string of a `Persist` encoding, failing unless it is exactly one value.
+fn from_hex<T: Persist>(value: &Value) -> Result<T, RpcError> {
+  let hex = value.as_str().ok_or(RpcError::Malformed)?;
+  if !hex.is_ascii() || hex.len() % 2 != 0 {
+    return Err(RpcError::Malformed);
+  }
+  let bytes = (0..hex.len())
+    .step_by(2)
+    .map(|i| u8::from_str_radix(&hex[i..i + 2], 16))
+    .collect::<Result<Vec<u8>, _>>()
+    .map_err(|_| RpcError::Malformed)?;
+  let mut rest = &bytes[..];
+  match T::decode(&mut rest) {
+    Some(val) if rest.is_empty() => Ok(val),
+    _ => Err(RpcError::Malformed),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::group::Rsa2048;
+
+  fn call(server: &mut Server<Rsa2048>, request: Value) -> Value {
+    server.handle(request.to_string().as_bytes())
+  }
+
+  #[test]
+  fn test_handle_errors() {
+    let mut server = Server::<Rsa2048>::bind("127.0.0.1:0").unwrap();
+    let code = |response: Value| response["error"]["code"].as_i64();
+    assert_eq!(code(server.handle(b"{")), Some(PARSE_ERROR));
+    let request = json!({ "jsonrpc": "1.0", "id": 1, "method": "state" });
+    assert_eq!(code(call(&mut server, request)), Some(INVALID_REQUEST));
+    let request = json!({ "jsonrpc": "2.0", "id": 1, "method": "reset" });
+    assert_eq!(code(call(&mut server, request)), Some(METHOD_NOT_FOUND));
+    let request = json!({ "jsonrpc": "2.0", "id": 1, "method": "add", "params": { "elems": [1] } });
+    assert_eq!(code(call(&mut server, request)), Some(INVALID_PARAMS));
+    let request =
+      json!({ "jsonrpc": "2.0", "id": 1, "method": "add", "params": { "elems": ["a", "a"] } });
+    assert_eq!(code(call(&mut server, request)), Some(APP_ERROR));
+
+    let request = json!({ "jsonrpc": "2.0", "id": 7, "method": "state" });
+    let response = call(&mut server, request);
+    assert_eq!(response["id"], json!(7));
+    assert_eq!(response["result"]["epoch"], json!(0));
+    assert_eq!(response["result"]["size"], json!(0));
+  }
+}
@@ -0,0 +1,424 @@
+//! Stateless UTXO validation, as in Section 6.1 of BBF.
+//!
+//! The UTXO set lives in an accumulator. Transactions spend UTXOs by proving their membership and
+//! create new ones, which get accumulated. A miner aggregates the input proofs of a block's
+//! transactions into one, deletes the spent UTXOs and adds the new ones, and publishes the result
+//! with a `CatchUpProof`. A `StatelessNode` validates a block by checking that one proof against
+//! the previous accumulator, storing nothing but the accumulator itself.
+//!
+//! Users need current witnesses for their UTXOs to spend them. Rather than have every user follow
+//! the chain, a `BridgeNode` keeps those witnesses up to date (via a `WitnessManager`) and hands out
+//! membership proofs on request. `Simulator` wires all of these roles together in-process.
+//!
+//! This module is concerned with the accumulator only. Authorization (e.g. signatures by UTXO
+//! owners), amounts and the uniqueness of new UTXOs are left to the application.
+use crate::accumulator::{AccError, Accumulator, MembershipProof, Witness, WitnessManager};
+use crate::group::UnknownOrderGroup;
+use crate::history::CatchUpProof;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::error;
+use std::fmt;
+
+#[derive(Debug)]
+/// The different types of stateless validation errors.
+pub enum StatelessError {
+  /// A transaction whose input proof does not verify, or that conflicts with another transaction
+  /// in the same block.
+  InvalidTransaction,
+  /// A block that does not follow from the previous accumulator.
+  InvalidBlock,
+  /// A UTXO that the bridge node does not hold a witness for.
+  UnknownUtxo,
+}
+
+impl fmt::Display for StatelessError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let msg = match self {
+      StatelessError::InvalidTransaction => {
+        "transaction has an invalid input proof or conflicts with another"
+      }
+      StatelessError::InvalidBlock => "block does not follow from the previous accumulator",
+      StatelessError::UnknownUtxo => "no witness is held for the UTXO",
+    };
+    f.write_str(msg)
+  }
+}
+
+impl error::Error for StatelessError {}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+/// An unspent transaction output.
+pub struct Utxo {
+  /// Identifies the output. Must be unique across all outputs ever created.
+  pub id: u64,
+  /// The user that may spend the output.
+  pub owner: u64,
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+/// A transaction, which spends `inputs` and creates `outputs`.
+pub struct Transaction<G: UnknownOrderGroup> {
+  /// The UTXOs spent.
+  pub inputs: Vec<Utxo>,
+  /// A batch proof of membership of the `inputs` in the current accumulator.
+  pub input_proof: MembershipProof<G, Utxo>,
+  /// The UTXOs created.
+  pub outputs: Vec<Utxo>,
+}
+
+impl<G: UnknownOrderGroup> Transaction<G> {
+  /// Returns a transaction with no inputs, which creates `outputs` out of thin air.
+  pub fn coinbase(acc: &Accumulator<G, Utxo>, outputs: Vec<Utxo>) -> Self {
+    Self {
+      inputs: vec![],
+      input_proof: acc.prove_membership_with_witness(&[], Witness(acc.clone())),
+      outputs,
+    }
+  }
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+/// A block of transactions, along with the accumulator after applying them.
+pub struct Block<G: UnknownOrderGroup> {
+  /// The height of the block. The first block has height 1.
+  pub height: u64,
+  /// The transactions in the block.
+  pub transactions: Vec<Transaction<G>>,
+  /// The accumulator after the block.
+  pub acc: Accumulator<G, Utxo>,
+  /// Proof that `acc` follows from the previous accumulator by deleting every input and adding
+  /// every output of `transactions`.
+  pub proof: CatchUpProof<G, Utxo>,
+}
+
+// Returns every input and every output of `transactions`.
+fn inputs_and_outputs<G: UnknownOrderGroup>(
+  transactions: &[Transaction<G>],
+) -> (Vec<Utxo>, Vec<Utxo>) {
+  let inputs = transactions
+    .iter()
+    .flat_map(|tx| tx.inputs.iter().cloned())
+    .collect();
+  let outputs = transactions
+    .iter()
+    .flat_map(|tx| tx.outputs.iter().cloned())
+    .collect();
+  (inputs, outputs)
+}
+
+fn has_duplicates(utxos: &[Utxo]) -> bool {
+  let mut seen = HashSet::new();
+  !utxos.iter().all(|utxo| seen.insert(utxo))
+}
+
+impl<G: UnknownOrderGroup> Block<G> {
+  /// Mines a block at `height` on top of `acc`, aggregating the input proofs of `transactions`.
+  /// Fails if any transaction's input proof does not verify against `acc`, or if two transactions
+  /// spend or create the same UTXO.
+  pub fn mine(
+    acc: &Accumulator<G, Utxo>,
+    height: u64,
+    transactions: Vec<Transaction<G>>,
+  ) -> Result<Self, StatelessError> {
+    let (inputs, outputs) = inputs_and_outputs(&transactions);
+    if has_duplicates(&inputs) || has_duplicates(&outputs) {
+      return Err(StatelessError::InvalidTransaction);
+    }
+    let input_proofs: Vec<_> = transactions
+      .iter()
+      .map(|tx| (&tx.inputs[..], &tx.input_proof))
+      .collect();
+    let deletion_proof = acc
+      .aggregate_membership_proofs(&input_proofs)
+      .map_err(|_| StatelessError::InvalidTransaction)?;
+    let (new_acc, proof) = CatchUpProof::prove_update(acc, &inputs, deletion_proof, &outputs)
+      .map_err(|_| StatelessError::InvalidTransaction)?;
+    Ok(Self {
+      height,
+      transactions,
+      acc: new_acc,
+      proof,
+    })
+  }
+
+  /// Verifies that this block follows from `prev_acc`. Checks only the aggregated block proof, not
+  /// the input proofs of the individual transactions.
+  pub fn verify(&self, prev_acc: &Accumulator<G, Utxo>) -> bool {
+    let (inputs, outputs) = inputs_and_outputs(&self.transactions);
+    self.proof.deletions() == &inputs[..]
+      && self.proof.additions() == &outputs[..]
+      && !has_duplicates(&inputs)
+      && !has_duplicates(&outputs)
+      && self.proof.verify(prev_acc, &self.acc)
+  }
+}
+
+#[derive(Clone, Debug)]
+/// A validating node that stores only the current accumulator.
+pub struct StatelessNode<G: UnknownOrderGroup> {
+  acc: Accumulator<G, Utxo>,
+  height: u64,
+}
+
+impl<G: UnknownOrderGroup> StatelessNode<G> {
+  /// Creates a node at the empty UTXO set, before the first block.
+  pub fn new() -> Self {
+    Self {
+      acc: Accumulator::empty(),
+      height: 0,
+    }
+  }
+
+  /// Returns the current accumulator.
+  pub fn accumulator(&self) -> &Accumulator<G, Utxo> {
+    &self.acc
+  }
+
+  /// Returns the height of the last block applied.
+  pub fn height(&self) -> u64 {
+    self.height
+  }
+
+  /// Validates `block` as the next block and, if it is valid, moves to it.
+  pub fn apply_block(&mut self, block: &Block<G>) -> Result<(), StatelessError> {
+    if block.height != self.height + 1 || !block.verify(&self.acc) {
+      return Err(StatelessError::InvalidBlock);
+    }
+    self.acc = block.acc.clone();
+    self.height = block.height;
+    Ok(())
+  }
+}
+
+impl<G: UnknownOrderGroup> Default for StatelessNode<G> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+/// A node that follows the chain on behalf of users, keeping witnesses for their UTXOs current so
+/// that they can spend them.
+pub struct BridgeNode<G: UnknownOrderGroup> {
+  node: StatelessNode<G>,
+  manager: WitnessManager<G, Utxo>,
+  users: HashSet<u64>,
+  // UTXOs of the served users, by owner.
+  utxos: BTreeMap<u64, BTreeSet<Utxo>>,
+}
+
+impl<G: UnknownOrderGroup> BridgeNode<G> {
+  /// Creates a bridge node at the empty UTXO set, serving `users`.
+  pub fn new(users: &[u64]) -> Self {
+    Self {
+      node: StatelessNode::new(),
+      manager: WitnessManager::new(Accumulator::empty()),
+      users: users.iter().cloned().collect(),
+      utxos: BTreeMap::new(),
+    }
+  }
+
+  /// Returns the node the bridge uses to validate blocks.
+  pub fn node(&self) -> &StatelessNode<G> {
+    &self.node
+  }
+
+  /// Returns the unspent outputs of `user`, in order of their IDs.
+  pub fn utxos(&self, user: u64) -> Vec<Utxo> {
+    self
+      .utxos
+      .get(&user)
+      .map(|utxos| utxos.iter().cloned().collect())
+      .unwrap_or_default()
+  }
+
+  /// Returns the current witness for `utxo`.
+  pub fn witness(&mut self, utxo: &Utxo) -> Result<Witness<G, Utxo>, StatelessError> {
+    self
+      .manager
+      .witness(utxo)
+      .ok_or(StatelessError::UnknownUtxo)
+  }
+
+  /// Proves membership of `utxos` in the current accumulator, e.g. for a transaction spending them.
+  pub fn prove(&self, utxos: &[Utxo]) -> Result<MembershipProof<G, Utxo>, StatelessError> {
+    self
+      .manager
+      .prove_membership(utxos)
+      .map_err(|_| StatelessError::UnknownUtxo)
+  }
+
+  /// Validates `block` as the next block and updates the witnesses of the served users' UTXOs.
+  pub fn apply_block(&mut self, block: &Block<G>) -> Result<(), StatelessError> {
+    self.node.apply_block(block)?;
+    let (inputs, outputs) = inputs_and_outputs(&block.transactions);
+    let map_err = |_: AccError| StatelessError::InvalidBlock;
+    for utxo in self
+      .manager
+      .update(&block.acc, &outputs, &inputs)
+      .map_err(map_err)?
+    {
+      if let Some(utxos) = self.utxos.get_mut(&utxo.owner) {
+        utxos.remove(&utxo);
+      }
+    }
+
+    // The block's addition proof has a witness for all of its outputs. Raise it to the other
+    // outputs to get one for just those of served users, and split that up.
+    let (served, others): (Vec<Utxo>, Vec<Utxo>) = outputs
+      .into_iter()
+      .partition(|utxo| self.users.contains(&utxo.owner));
+    let witness = Witness(block.proof.addition_witness().0.clone().add(&others));
+    let witnesses = witness.compute_individual_witnesses(&served);
+    self.manager.register(&witnesses).map_err(map_err)?;
+    for utxo in served {
+      self.utxos.entry(utxo.owner).or_default().insert(utxo);
+    }
+    Ok(())
+  }
+}
+
+/// A deterministic, in-process network for end-to-end tests: users, a bridge node serving all of
+/// them, a miner and a stateless validator.
+///
+/// In each step, user `height % users` receives a new coin, and every user who holds a UTXO spends
+/// the one with the lowest ID to the next user (wrapping around). The miner bundles these
+/// transactions into a block, which both the validator and the bridge node apply.
+pub struct Simulator<G: UnknownOrderGroup> {
+  users: u64,
+  next_id: u64,
+  validator: StatelessNode<G>,
+  bridge: BridgeNode<G>,
+}
+
+impl<G: UnknownOrderGroup> Simulator<G> {
+  /// Creates a network of `users` users, numbered from 0, with an empty UTXO set.
+  ///
+  /// Panics if `users` is zero.
+  pub fn new(users: u64) -> Self {
+    assert!(users > 0, "The simulation needs at least one user.");
+    Self {
+      users,
+      next_id: 0,
+      validator: StatelessNode::new(),
+      bridge: BridgeNode::new(&(0..users).collect::<Vec<_>>()),
+    }
+  }
+
+  /// Returns the validator.
+  pub fn validator(&self) -> &StatelessNode<G> {
+    &self.validator
+  }
+
+  /// Returns the bridge node.
+  pub fn bridge(&mut self) -> &mut BridgeNode<G> {
+    &mut self.bridge
+  }
+
+  fn new_utxo(&mut self, owner: u64) -> Utxo {
+    let utxo = Utxo {
+      id: self.next_id,
+      owner,
+    };
+    self.next_id += 1;
+    utxo
+  }
+
+  /// Mines and applies the next block, which it returns.
+  pub fn step(&mut self) -> Result<Block<G>, StatelessError> {
+    let acc = self.validator.accumulator().clone();
+    let height = self.validator.height() + 1;
+    let coinbase_output = self.new_utxo(height % self.users);
+    let mut transactions = vec![Transaction::coinbase(&acc, vec![coinbase_output])];
+    for user in 0..self.users {
+      if let Some(&input) = self.bridge.utxos(user).first() {
+        let output = self.new_utxo((user + 1) % self.users);
+        transactions.push(Transaction {
+          inputs: vec![input],
+          input_proof: self.bridge.prove(&[input])?,
+          outputs: vec![output],
+        });
+      }
+    }
+
+    let block = Block::mine(&acc, height, transactions)?;
+    self.validator.apply_block(&block)?;
+    self.bridge.apply_block(&block)?;
+    Ok(block)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::group::Rsa2048;
+
+  #[test]
+  fn test_simulation() {
+    let mut simulator = Simulator::<Rsa2048>::new(3);
+    for height in 1..=5 {
+      let block = simulator.step().unwrap();
+      assert_eq!(block.height, height);
+    }
+    let acc = simulator.validator().accumulator().clone();
+    assert!(simulator.bridge().node().accumulator() == &acc);
+
+    // Every coin minted is still around, just with a different owner.
+    let bridge = simulator.bridge();
+    let utxos: Vec<Utxo> = (0..3).flat_map(|user| bridge.utxos(user)).collect();
+    assert_eq!(utxos.len(), 5);
+    assert!(acc == Accumulator::from(&utxos[..]));
+    for utxo in &utxos {
+      assert!(bridge.witness(utxo).unwrap().0.add(&[*utxo]) == acc);
+    }
+    let proof = bridge.prove(&utxos).unwrap();
+    assert!(acc.verify_membership_batch(&utxos, &proof));
+  }
+
+  #[test]
+  fn test_invalid_blocks() {
+    let mut simulator = Simulator::<Rsa2048>::new(2);
+    simulator.step().unwrap();
+    simulator.step().unwrap();
+    let acc = simulator.validator().accumulator().clone();
+    let utxos = simulator.bridge().utxos(0);
+    let utxo = utxos[0];
+    let spend = |proof: MembershipProof<Rsa2048, Utxo>, id| Transaction {
+      inputs: vec![utxo],
+      input_proof: proof,
+      outputs: vec![Utxo { id, owner: 1 }],
+    };
+    let proof = simulator.bridge().prove(&[utxo]).unwrap();
+
+    // Double spends and proofs that do not verify.
+    let double_spend = vec![spend(proof.clone(), 100), spend(proof.clone(), 101)];
+    assert!(Block::mine(&acc, 3, double_spend).is_err());
+    let other = Utxo { id: 100, owner: 0 };
+    let bad_input = Transaction {
+      inputs: vec![other],
+      ..spend(proof.clone(), 101)
+    };
+    assert!(Block::mine(&acc, 3, vec![bad_input]).is_err());
+    let stale_acc = simulator
+      .bridge()
+      .node()
+      .accumulator()
+      .clone()
+      .add(&[other]);
+    assert!(Block::mine(&stale_acc, 3, vec![spend(proof.clone(), 100)]).is_err());
+
+    let block = Block::mine(&acc, 3, vec![spend(proof, 100)]).unwrap();
+    let mut node = simulator.validator().clone();
+    let mut wrong_height = block.clone();
+    wrong_height.height = 4;
+    assert!(node.apply_block(&wrong_height).is_err());
+    let mut missing_output = block.clone();
+    missing_output.transactions[0].outputs.clear();
+    assert!(node.apply_block(&missing_output).is_err());
+    let mut wrong_acc = block.clone();
+    wrong_acc.acc = acc.clone();
+    assert!(node.apply_block(&wrong_acc).is_err());
+    assert!(node.accumulator() == &acc);
+    node.apply_block(&block).unwrap();
+    assert!(node.accumulator() != &acc);
+  }
+}
@@ -0,0 +1,863 @@
+//! Persistent storage for accumulator state.
+//!
+//! An `AccumulatorStore` holds the current accumulator value, the set of accumulated elements, a
+//! membership witness for any of those elements and an epoch counter. All changes go through
+//! `AccumulatorStore::commit`, which applies a `StoreBatch` as a unit and advances the epoch by one.
+//! A batch is typically one block's worth of additions and deletions, together with the new
+//! accumulator value and whatever witnesses the caller has updated; the store does not recompute
+//! anything itself.
+//!
+//! Two backends are provided. `MemoryStore` keeps everything in memory. `FileStore` keeps the same
+//! state in memory, but also appends each batch to a log file as a single checksummed record and
+//! syncs it before returning. On open, the log is replayed, and a torn record at the tail (from a
+//! crash in the middle of a commit) is discarded, so the store always reflects a whole number of
+//! batches. Damage anywhere else in the log fails the open rather than dropping committed batches.
+//! Values are written to disk via the `Persist` trait.
+use crate::accumulator::{Accumulator, Witness};
+use crate::bigint::{Integer, Order};
+use crate::group::UnknownOrderGroup;
+use crate::util::PrimeHashable;
+use blake2_rfc::blake2b::blake2b;
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::error;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::hash::Hash;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+const LOG_MAGIC: &[u8; 8] = b"ACCLOG01";
+const CHECKSUM_BYTES: usize = 32;
+// A record's length prefix has a checksum of its own, so that a damaged length is told apart from
+// a record cut short by a crash.
+const LEN_BYTES: usize = 8;
+const LEN_CHECKSUM_BYTES: usize = 8;
+
+#[derive(Debug)]
+/// The different types of store errors.
+pub enum StoreError {
+  /// An I/O error from the storage backend.
+  Io(io::Error),
+  /// Stored data failed its checksum before the end of the log, could not be decoded, or does not
+  /// replay cleanly.
+  Corrupt,
+  /// A batch that deletes a non-member, adds a member (or the same element twice), or sets a
+  /// witness for an element that is not a member afterwards.
+  InvalidBatch,
+}
+
+impl fmt::Display for StoreError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      StoreError::Io(err) => write!(f, "storage I/O error: {}", err),
+      StoreError::Corrupt => f.write_str("stored data is corrupt"),
+      StoreError::InvalidBatch => f.write_str("batch does not apply to the stored accumulator"),
+    }
+  }
+}
+
+impl error::Error for StoreError {
+  fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+    match self {
+      StoreError::Io(err) => Some(err),
+      _ => None,
+    }
+  }
+}
+
+impl From<io::Error> for StoreError {
+  fn from(err: io::Error) -> Self {
+    StoreError::Io(err)
+  }
+}
+
+/// Types that can be written to and read back from a `FileStore`.
+pub trait Persist: Sized {
+  /// Appends the encoding of `self` to `out`.
+  fn encode(&self, out: &mut Vec<u8>);
+  /// Decodes a value from the front of `bytes` and advances `bytes` past it. Returns `None` if
+  /// `bytes` does not start with a valid encoding.
+  fn decode(bytes: &mut &[u8]) -> Option<Self>;
+}
+
+fn take<'a>(bytes: &mut &'a [u8], n: usize) -> Option<&'a [u8]> {
+  if bytes.len() < n {
+    return None;
+  }
+  let (head, tail) = bytes.split_at(n);
+  *bytes = tail;
+  Some(head)
+}
+
+fn encode_len(len: usize, out: &mut Vec<u8>) {
+  (len as u64).encode(out);
+}
+
+fn decode_len(bytes: &mut &[u8]) -> Option<usize> {
+  let len = u64::decode(bytes)?;
+  // Every encoded item takes at least a byte, so longer lengths can only come from bad input.
+  if len > bytes.len() as u64 {
+    return None;
+  }
+  Some(len as usize)
+}
+
+fn encode_list<T: Persist>(items: &[T], out: &mut Vec<u8>) {
+  encode_len(items.len(), out);
+  for item in items {
+    item.encode(out);
+  }
+}
+
+pub(crate) fn decode_list<T: Persist>(bytes: &mut &[u8]) -> Option<Vec<T>> {
+  let len = decode_len(bytes)?;
+  (0..len).map(|_| T::decode(bytes)).collect()
+}
+
+impl Persist for bool {
+  fn encode(&self, out: &mut Vec<u8>) {
+    out.push(*self as u8);
+  }
+
+  fn decode(bytes: &mut &[u8]) -> Option<Self> {
+    match take(bytes, 1)?[0] {
+      0 => Some(false),
+      1 => Some(true),
+      _ => None,
+    }
+  }
+}
+
+impl Persist for u64 {
+  fn encode(&self, out: &mut Vec<u8>) {
+    out.extend_from_slice(&self.to_le_bytes());
+  }
+
+  fn decode(bytes: &mut &[u8]) -> Option<Self> {
+    let mut buf = [0; 8];
+    buf.copy_from_slice(take(bytes, 8)?);
+    Some(Self::from_le_bytes(buf))
+  }
+}
+
+impl Persist for Vec<u8> {
+  fn encode(&self, out: &mut Vec<u8>) {
+    encode_len(self.len(), out);
+    out.extend_from_slice(self);
+  }
+
+  fn decode(bytes: &mut &[u8]) -> Option<Self> {
+    let len = decode_len(bytes)?;
+    Some(take(bytes, len)?.to_vec())
+  }
+}
+
+impl Persist for String {
+  fn encode(&self, out: &mut Vec<u8>) {
+    encode_len(self.len(), out);
+    out.extend_from_slice(self.as_bytes());
+  }
+
+  fn decode(bytes: &mut &[u8]) -> Option<Self> {
+    Self::from_utf8(Vec::decode(bytes)?).ok()
+  }
+}
+
+/// Encoded as a sign followed by the magnitude, least significant byte first.
+impl Persist for Integer {
+  fn encode(&self, out: &mut Vec<u8>) {
+    (*self < 0).encode(out);
+    self.to_digits::<u8>(Order::Lsf).encode(out);
+  }
+
+  fn decode(bytes: &mut &[u8]) -> Option<Self> {
+    let negative = bool::decode(bytes)?;
+    let magnitude = Integer::from_digits(&Vec::decode(bytes)?, Order::Lsf);
+    match (negative, magnitude == 0) {
+      (true, true) => None,
+      (true, false) => Some(-magnitude),
+      (false, _) => Some(magnitude),
+    }
+  }
+}
+
+impl<A: Persist, B: Persist> Persist for (A, B) {
+  fn encode(&self, out: &mut Vec<u8>) {
+    self.0.encode(out);
+    self.1.encode(out);
+  }
+
+  fn decode(bytes: &mut &[u8]) -> Option<Self> {
+    Some((A::decode(bytes)?, B::decode(bytes)?))
+  }
+}
+
+/// A set of changes that an `AccumulatorStore` commits as a unit. Deletions apply before additions,
+/// and the witnesses of deleted elements are dropped.
+pub struct StoreBatch<G: UnknownOrderGroup, T: Hash> {
+  value: Option<Accumulator<G, T>>,
+  deleted: Vec<T>,
+  added: Vec<T>,
+  witnesses: Vec<(T, Witness<G, T>)>,
+}
+
+impl<G: UnknownOrderGroup, T: Clone + Hash> StoreBatch<G, T> {
+  /// Returns an empty batch, which only advances the epoch.
+  pub fn new() -> Self {
+    Self {
+      value: None,
+      deleted: vec![],
+      added: vec![],
+      witnesses: vec![],
+    }
+  }
+
+  /// Sets the new accumulator value.
+  pub fn value(mut self, acc: Accumulator<G, T>) -> Self {
+    self.value = Some(acc);
+    self
+  }
+
+  #[allow(clippy::should_implement_trait)]
+  /// Adds `elems` to the element set.
+  pub fn add(mut self, elems: &[T]) -> Self {
+    self.added.extend_from_slice(elems);
+    self
+  }
+
+  /// Deletes `elems` from the element set, along with their witnesses.
+  pub fn delete(mut self, elems: &[T]) -> Self {
+    self.deleted.extend_from_slice(elems);
+    self
+  }
+
+  /// Sets the witnesses of `elem_witnesses`, replacing any stored ones.
+  pub fn witnesses(mut self, elem_witnesses: &[(T, Witness<G, T>)]) -> Self {
+    self.witnesses.extend_from_slice(elem_witnesses);
+    self
+  }
+}
+
+impl<G: UnknownOrderGroup, T: Clone + Hash> Default for StoreBatch<G, T> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+/// A store for the state of a single accumulator. See the module documentation.
+pub trait AccumulatorStore<G: UnknownOrderGroup, T: Clone + Eq + PrimeHashable> {
+  /// Returns the current accumulator value.
+  fn value(&self) -> &Accumulator<G, T>;
+
+  /// Returns the number of batches committed so far.
+  fn epoch(&self) -> u64;
+
+  /// Returns whether `elem` is in the element set.
+  fn contains(&self, elem: &T) -> bool;
+
+  /// Returns the element set, in no particular order.
+  fn elems(&self) -> Vec<T>;
+
+  /// Returns the stored witness for `elem`, if any.
+  fn witness(&self, elem: &T) -> Option<&Witness<G, T>>;
+
+  /// Applies `batch` and advances the epoch. Either the whole batch is applied or, on error,
+  /// none of it.
+  fn commit(&mut self, batch: StoreBatch<G, T>) -> Result<(), StoreError>;
+}
+
+struct StoreState<G: UnknownOrderGroup, T: Hash> {
+  value: Accumulator<G, T>,
+  elems: HashSet<T>,
+  witnesses: HashMap<T, Witness<G, T>>,
+  epoch: u64,
+}
+
+impl<G: UnknownOrderGroup, T: Clone + Eq + PrimeHashable> StoreState<G, T> {
+  fn new() -> Self {
+    Self {
+      value: Accumulator::empty(),
+      elems: HashSet::new(),
+      witnesses: HashMap::new(),
+      epoch: 0,
+    }
+  }
+
+  fn check(&self, batch: &StoreBatch<G, T>) -> Result<(), StoreError> {
+    let mut deleted = HashSet::new();
+    for elem in &batch.deleted {
+      if !self.elems.contains(elem) || !deleted.insert(elem) {
+        return Err(StoreError::InvalidBatch);
+      }
+    }
+    let is_member = |elem| self.elems.contains(elem) && !deleted.contains(elem);
+    let mut added = HashSet::new();
+    for elem in &batch.added {
+      if is_member(elem) || !added.insert(elem) {
+        return Err(StoreError::InvalidBatch);
+      }
+    }
+    if batch
+      .witnesses
+      .iter()
+      .any(|(elem, _)| !is_member(elem) && !added.contains(elem))
+    {
+      return Err(StoreError::InvalidBatch);
+    }
+    Ok(())
+  }
+
+  /// Applies a batch that has passed `check`.
+  fn apply(&mut self, batch: StoreBatch<G, T>, epoch: u64) {
+    if let Some(value) = batch.value {
+      self.value = value;
+    }
+    for elem in &batch.deleted {
+      self.elems.remove(elem);
+      self.witnesses.remove(elem);
+    }
+    self.elems.extend(batch.added);
+    self.witnesses.extend(batch.witnesses);
+    self.epoch = epoch;
+  }
+}
+
+macro_rules! delegate_to_state {
+  () => {
+    fn value(&self) -> &Accumulator<G, T> {
+      &self.state.value
+    }
+
+    fn epoch(&self) -> u64 {
+      self.state.epoch
+    }
+
+    fn contains(&self, elem: &T) -> bool {
+      self.state.elems.contains(elem)
+    }
+
+    fn elems(&self) -> Vec<T> {
+      self.state.elems.iter().cloned().collect()
+    }
+
+    fn witness(&self, elem: &T) -> Option<&Witness<G, T>> {
+      self.state.witnesses.get(elem)
+    }
+  };
+}
+
+/// An `AccumulatorStore` that lives in memory only.
+pub struct MemoryStore<G: UnknownOrderGroup, T: Hash> {
+  state: StoreState<G, T>,
+}
+
+impl<G: UnknownOrderGroup, T: Clone + Eq + PrimeHashable> MemoryStore<G, T> {
+  /// Returns a store holding the empty accumulator at epoch 0.
+  pub fn new() -> Self {
+    Self {
+      state: StoreState::new(),
+    }
+  }
+}
+
+impl<G: UnknownOrderGroup, T: Clone + Eq + PrimeHashable> Default for MemoryStore<G, T> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<G: UnknownOrderGroup, T: Clone + Eq + PrimeHashable> AccumulatorStore<G, T>
+  for MemoryStore<G, T>
+{
+  delegate_to_state!();
+
+  fn commit(&mut self, batch: StoreBatch<G, T>) -> Result<(), StoreError> {
+    self.state.check(&batch)?;
+    let epoch = self.state.epoch + 1;
+    self.state.apply(batch, epoch);
+    Ok(())
+  }
+}
+
+/// An `AccumulatorStore` backed by an append-only log file. See the module documentation.
+pub struct FileStore<G: UnknownOrderGroup, T: Hash> {
+  path: PathBuf,
+  file: File,
+  // Length of the log up to the end of the last complete record.
+  len: u64,
+  state: StoreState<G, T>,
+}
+
+fn encode_record<G: UnknownOrderGroup, T: Hash + Persist>(
+  epoch: u64,
+  batch: &StoreBatch<G, T>,
+) -> Vec<u8>
+where
+  G::Elem: Persist,
+{
+  let mut payload = vec![];
+  epoch.encode(&mut payload);
+  match &batch.value {
+    Some(value) => {
+      true.encode(&mut payload);
+      value.encode(&mut payload);
+    }
+    None => false.encode(&mut payload),
+  }
+  encode_list(&batch.deleted, &mut payload);
+  encode_list(&batch.added, &mut payload);
+  encode_list(&batch.witnesses, &mut payload);
+
+  let mut record = vec![];
+  encode_len(payload.len(), &mut record);
+  let len_checksum = blake2b(LEN_CHECKSUM_BYTES, &[], &record);
+  record.extend_from_slice(len_checksum.as_bytes());
+  record.extend_from_slice(&payload);
+  record.extend_from_slice(blake2b(CHECKSUM_BYTES, &[], &payload).as_bytes());
+  record
+}
+
+/// Returns the payload of the record at the front of `bytes` and advances `bytes` past it, or
+/// `Ok(None)` (leaving `bytes` as is) if the record is the torn tail of the log: either it runs
+/// past the end of `bytes` (as told by its checksummed length), or it is the last record and fails
+/// its checksum. Any other record that cannot be read is `StoreError::Corrupt`.
+fn read_record<'a>(bytes: &mut &'a [u8]) -> Result<Option<&'a [u8]>, StoreError> {
+  let mut rest = *bytes;
+  let (mut len, len_checksum) = match take(&mut rest, LEN_BYTES + LEN_CHECKSUM_BYTES) {
+    Some(header) => header.split_at(LEN_BYTES),
+    None => return Ok(None),
+  };
+  if blake2b(LEN_CHECKSUM_BYTES, &[], len).as_bytes() != len_checksum {
+    return Err(StoreError::Corrupt);
+  }
+  let len = u64::decode(&mut len).ok_or(StoreError::Corrupt)?;
+  let record = usize::try_from(len).ok().and_then(|len| {
+    let payload = take(&mut rest, len)?;
+    Some((payload, take(&mut rest, CHECKSUM_BYTES)?))
+  });
+  let (payload, checksum) = match record {
+    Some(record) => record,
+    None => return Ok(None),
+  };
+  if blake2b(CHECKSUM_BYTES, &[], payload).as_bytes() != checksum {
+    return if rest.is_empty() {
+      Ok(None)
+    } else {
+      Err(StoreError::Corrupt)
+    };
+  }
+  *bytes = rest;
+  Ok(Some(payload))
+}
+
+fn decode_payload<G: UnknownOrderGroup, T: Hash + Persist>(
+  mut payload: &[u8],
+) -> Option<(u64, StoreBatch<G, T>)>
+where
+  G::Elem: Persist,
+{
+  let bytes = &mut payload;
+  let epoch = u64::decode(bytes)?;
+  let value = if bool::decode(bytes)? {
+    Some(Accumulator::decode(bytes)?)
+  } else {
+    None
+  };
+  let batch = StoreBatch {
+    value,
+    deleted: decode_list(bytes)?,
+    added: decode_list(bytes)?,
+    witnesses: decode_list(bytes)?,
+  };
+  if !bytes.is_empty() {
+    return None;
+  }
+  Some((epoch, batch))
+}
+
+// Makes a rename or file creation in `path`'s directory durable. Only needed (and possible) on
+// Unix-like platforms.
+#[cfg(unix)]
+fn sync_dir(path: &Path) -> io::Result<()> {
+  match path.parent() {
+    Some(dir) if dir != Path::new("") => File::open(dir)?.sync_all(),
+    _ => File::open(".")?.sync_all(),
+  }
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_path: &Path) -> io::Result<()> {
+  Ok(())
+}
+
+impl<G: UnknownOrderGroup, T: Clone + Eq + PrimeHashable + Persist> FileStore<G, T>
+where
+  G::Elem: Persist,
+{
+  /// Opens the log at `path`, creating it if needed, and replays it. A torn record at the end of
+  /// the log is truncated away; any other damage, including records whose epochs do not follow on
+  /// from each other, is `StoreError::Corrupt`.
+  pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, StoreError> {
+    let path = path.as_ref().to_path_buf();
+    let mut file = OpenOptions::new()
+      .read(true)
+      .append(true)
+      .create(true)
+      .open(&path)?;
+    let mut log = vec![];
+    file.read_to_end(&mut log)?;
+    if log.len() < LOG_MAGIC.len() {
+      // Either a new log or one that crashed while its magic was being written. Anything else is
+      // not ours to overwrite.
+      if !LOG_MAGIC.starts_with(&log) {
+        return Err(StoreError::Corrupt);
+      }
+      file.set_len(0)?;
+      file.write_all(LOG_MAGIC)?;
+      file.sync_all()?;
+      sync_dir(&path)?;
+      log = LOG_MAGIC.to_vec();
+    }
+    if &log[..LOG_MAGIC.len()] != LOG_MAGIC {
+      return Err(StoreError::Corrupt);
+    }
+
+    let mut state = StoreState::new();
+    let mut rest = &log[LOG_MAGIC.len()..];
+    let mut first = true;
+    while let Some(payload) = read_record(&mut rest)? {
+      let (epoch, batch) = decode_payload(payload).ok_or(StoreError::Corrupt)?;
+      // The first record may be a snapshot from `compact`, which keeps the epoch it was taken at.
+      if !first && Some(epoch) != state.epoch.checked_add(1) {
+        return Err(StoreError::Corrupt);
+      }
+      state.check(&batch).map_err(|_| StoreError::Corrupt)?;
+      state.apply(batch, epoch);
+      first = false;
+    }
+    let len = (log.len() - rest.len()) as u64;
+    if !rest.is_empty() {
+      file.set_len(len)?;
+      file.sync_all()?;
+    }
+    Ok(Self {
+      path,
+      file,
+      len,
+      state,
+    })
+  }
+
+  /// Rewrites the log as a single record holding the current state, which replaces the history of
+  /// batches that led to it. The new log is written to a temporary file and renamed into place, so
+  /// a crash leaves either the old or the new log.
+  pub fn compact(&mut self) -> Result<(), StoreError> {
+    let snapshot = StoreBatch {
+      value: Some(self.state.value.clone()),
+      deleted: vec![],
+      added: self.state.elems.iter().cloned().collect(),
+      witnesses: self
+        .state
+        .witnesses
+        .iter()
+        .map(|(elem, witness)| (elem.clone(), witness.clone()))
+        .collect(),
+    };
+    let mut log = LOG_MAGIC.to_vec();
+    log.extend(encode_record(self.state.epoch, &snapshot));
+
+    let mut tmp_path = self.path.clone().into_os_string();
+    tmp_path.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_path);
+    let mut tmp = File::create(&tmp_path)?;
+    tmp.write_all(&log)?;
+    tmp.sync_all()?;
+    fs::rename(&tmp_path, &self.path)?;
+    sync_dir(&self.path)?;
+
+    self.file = OpenOptions::new()
+      .read(true)
+      .append(true)
+      .open(&self.path)?;
+    self.len = log.len() as u64;
+    Ok(())
+  }
+}
+
+impl<G: UnknownOrderGroup, T: Clone + Eq + PrimeHashable + Persist> AccumulatorStore<G, T>
+  for FileStore<G, T>
+where
+  G::Elem: Persist,
+{
+  delegate_to_state!();
+
+  /// Appends `batch` to the log and syncs it before applying it in memory.
+  fn commit(&mut self, batch: StoreBatch<G, T>) -> Result<(), StoreError> {
+    self.state.check(&batch)?;
+    let epoch = self.state.epoch + 1;
+    let record = encode_record(epoch, &batch);
+    let written = self
+      .file
+      .write_all(&record)
+      .and_then(|_| self.file.sync_data());
+    if let Err(err) = written {
+      // Drop any partial record, so that later records are not appended after it. If this fails
+      // too and later commits succeed, the next `open` reports the log as corrupt.
+      let _ = self.file.set_len(self.len);
+      return Err(err.into());
+    }
+    self.len += record.len() as u64;
+    self.state.apply(batch, epoch);
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::group::{ClassGroup, ElemFrom, Rsa2048};
+  use crate::util::int;
+
+  fn temp_path(name: &str) -> PathBuf {
+    let path =
+      std::env::temp_dir().join(format!("accumulator-store-{}-{}", std::process::id(), name));
+    let _ = fs::remove_file(&path);
+    path
+  }
+
+  fn roundtrip<T: Persist>(t: &T) -> Option<T> {
+    let mut bytes = vec![];
+    t.encode(&mut bytes);
+    let mut slice = &bytes[..];
+    let decoded = T::decode(&mut slice);
+    assert!(slice.is_empty());
+    decoded
+  }
+
+  // Commits additions of `elems` to `store`, along with fresh witnesses for all members.
+  fn add<S: AccumulatorStore<Rsa2048, u64>>(store: &mut S, elems: &[u64]) {
+    let value = store.value().clone().add(elems);
+    let mut members = store.elems();
+    members.extend_from_slice(elems);
+    let witnesses = Witness(Accumulator::empty()).compute_individual_witnesses(&members);
+    let batch = StoreBatch::new()
+      .value(value)
+      .add(elems)
+      .witnesses(&witnesses);
+    store.commit(batch).unwrap();
+  }
+
+  fn assert_same_state<S: AccumulatorStore<Rsa2048, u64>, R: AccumulatorStore<Rsa2048, u64>>(
+    a: &S,
+    b: &R,
+  ) {
+    assert_eq!(a.value(), b.value());
+    assert_eq!(a.epoch(), b.epoch());
+    let mut elems = a.elems();
+    elems.sort();
+    let mut other_elems = b.elems();
+    other_elems.sort();
+    assert_eq!(elems, other_elems);
+    for elem in &elems {
+      assert_eq!(a.witness(elem), b.witness(elem));
+    }
+  }
+
+  #[test]
+  fn test_persist_roundtrip() {
+    for x in &[int(0), int(1), int(-1), int(255), int(-256), int(1) << 300] {
+      assert_eq!(roundtrip(x).as_ref(), Some(x));
+    }
+    assert_eq!(roundtrip(&"dog".to_string()), Some("dog".to_string()));
+    let elem = Rsa2048::elem(1_234_567);
+    assert_eq!(roundtrip(&elem), Some(elem));
+    let elem = ClassGroup::unknown_order_elem();
+    assert_eq!(roundtrip(&elem), Some(elem));
+
+    let mut bytes = vec![];
+    int(-5).encode(&mut bytes);
+    assert!(Integer::decode(&mut &bytes[..bytes.len() - 1]).is_none());
+    // Negative zero.
+    assert!(Integer::decode(&mut &[1, 0, 0, 0, 0, 0, 0, 0, 0][..]).is_none());
+  }
+
+  #[test]
+  fn test_memory_store() {
+    let mut store = MemoryStore::<Rsa2048, u64>::new();
+    add(&mut store, &[1, 2, 3]);
+    assert_eq!(store.epoch(), 1);
+    assert!(store.contains(&2));
+    assert_eq!(
+      store.witness(&2).unwrap().0.clone().add(&[2]),
+      *store.value()
+    );
+
+    // Invalid batches leave the store untouched.
+    for batch in [
+      StoreBatch::new().delete(&[4]),
+      StoreBatch::new().add(&[3]),
+      StoreBatch::new().add(&[4, 4]),
+      StoreBatch::new().delete(&[1, 1]),
+      StoreBatch::new().witnesses(&[(4, Witness(Accumulator::empty()))]),
+      StoreBatch::new()
+        .delete(&[1])
+        .witnesses(&[(1, Witness(Accumulator::empty()))]),
+    ] {
+      match store.commit(batch) {
+        Err(StoreError::InvalidBatch) => (),
+        _ => panic!("Expected an invalid batch."),
+      }
+    }
+    assert_eq!(store.epoch(), 1);
+
+    store
+      .commit(StoreBatch::new().delete(&[1]).add(&[1]))
+      .unwrap();
+    assert!(store.contains(&1));
+    assert!(store.witness(&1).is_none());
+    assert!(store.witness(&2).is_some());
+    assert_eq!(store.epoch(), 2);
+  }
+
+  #[test]
+  fn test_file_store_reopen() {
+    let path = temp_path("reopen");
+    let mut memory_store = MemoryStore::new();
+    {
+      let mut store = FileStore::<Rsa2048, u64>::open(&path).unwrap();
+      add(&mut store, &[1, 2, 3]);
+      add(&mut memory_store, &[1, 2, 3]);
+      add(&mut store, &[4]);
+      add(&mut memory_store, &[4]);
+      assert_same_state(&store, &memory_store);
+    }
+    let mut store = FileStore::<Rsa2048, u64>::open(&path).unwrap();
+    assert_same_state(&store, &memory_store);
+
+    let batch = || StoreBatch::new().delete(&[2]);
+    store.commit(batch()).unwrap();
+    memory_store.commit(batch()).unwrap();
+    let store = FileStore::<Rsa2048, u64>::open(&path).unwrap();
+    assert_same_state(&store, &memory_store);
+    fs::remove_file(&path).unwrap();
+  }
+
+  #[test]
+  fn test_file_store_torn_record() {
+    let path = temp_path("torn");
+    let mut memory_store = MemoryStore::new();
+    {
+      let mut store = FileStore::<Rsa2048, u64>::open(&path).unwrap();
+      add(&mut store, &[1, 2]);
+      add(&mut memory_store, &[1, 2]);
+    }
+    let full_len = fs::metadata(&path).unwrap().len();
+
+    // Simulate a crash partway through writing the next record, inside and after its length.
+    let value = memory_store.value().clone().add(&[3]);
+    let record = encode_record(2, &StoreBatch::new().value(value).add(&[3]));
+    for &cut in &[
+      LEN_BYTES - 3,
+      LEN_BYTES + LEN_CHECKSUM_BYTES,
+      record.len() - 1,
+    ] {
+      let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+      file.write_all(&record[..cut]).unwrap();
+      drop(file);
+
+      let store = FileStore::<Rsa2048, u64>::open(&path).unwrap();
+      assert_same_state(&store, &memory_store);
+      assert_eq!(fs::metadata(&path).unwrap().len(), full_len);
+    }
+
+    let mut store = FileStore::<Rsa2048, u64>::open(&path).unwrap();
+
+    add(&mut store, &[3]);
+    add(&mut memory_store, &[3]);
+    let store = FileStore::<Rsa2048, u64>::open(&path).unwrap();
+    assert_same_state(&store, &memory_store);
+    fs::remove_file(&path).unwrap();
+  }
+
+  #[test]
+  fn test_file_store_compact() {
+    let path = temp_path("compact");
+    let mut store = FileStore::<Rsa2048, u64>::open(&path).unwrap();
+    let mut memory_store = MemoryStore::new();
+    for i in 0..4 {
+      add(&mut store, &[i]);
+      add(&mut memory_store, &[i]);
+    }
+    let batch = || StoreBatch::new().delete(&[0]);
+    store.commit(batch()).unwrap();
+    memory_store.commit(batch()).unwrap();
+    let len = fs::metadata(&path).unwrap().len();
+
+    store.compact().unwrap();
+    assert!(fs::metadata(&path).unwrap().len() < len);
+    add(&mut store, &[5]);
+    add(&mut memory_store, &[5]);
+    let store = FileStore::<Rsa2048, u64>::open(&path).unwrap();
+    assert_same_state(&store, &memory_store);
+    fs::remove_file(&path).unwrap();
+  }
+
+  fn assert_corrupt(path: &Path) {
+    match FileStore::<Rsa2048, u64>::open(path) {
+      Err(StoreError::Corrupt) => (),
+      _ => panic!("Expected a corrupt log."),
+    }
+  }
+
+  #[test]
+  fn test_file_store_corrupt_record() {
+    let path = temp_path("corrupt");
+    {
+      let mut store = FileStore::<Rsa2048, u64>::open(&path).unwrap();
+      add(&mut store, &[1, 2]);
+      add(&mut store, &[3]);
+    }
+    let log = fs::read(&path).unwrap();
+
+    // A bit flip in a record followed by another one is not a torn tail.
+    let mut flipped = log.clone();
+    flipped[LOG_MAGIC.len() + 20] ^= 1;
+    fs::write(&path, &flipped).unwrap();
+    assert_corrupt(&path);
+    assert_eq!(fs::read(&path).unwrap(), flipped);
+
+    // Nor is a bit flip that makes the length of the first record run past the end of the log.
+    let mut flipped = log.clone();
+    flipped[LOG_MAGIC.len() + LEN_BYTES - 1] ^= 0x80;
+    fs::write(&path, &flipped).unwrap();
+    assert_corrupt(&path);
+    assert_eq!(fs::read(&path).unwrap(), flipped);
+
+    // Records must have consecutive epochs.
+    let mut memory_store = MemoryStore::new();
+    add(&mut memory_store, &[1, 2]);
+    let value = memory_store.value().clone().add(&[3]);
+    let mut skipped = log[..].to_vec();
+    skipped.extend(encode_record(5, &StoreBatch::new().value(value).add(&[4])));
+    fs::write(&path, &skipped).unwrap();
+    assert_corrupt(&path);
+    fs::remove_file(&path).unwrap();
+  }
+
+  #[test]
+  fn test_file_store_bad_magic() {
+    let path = temp_path("magic");
+    fs::write(&path, b"NOTALOG!").unwrap();
+    assert_corrupt(&path);
+    fs::write(&path, b"ACX").unwrap();
+    assert_corrupt(&path);
+    assert_eq!(fs::read(&path).unwrap(), b"ACX");
+
+    // A prefix of the magic is a log that crashed while being created.
+    fs::write(&path, &LOG_MAGIC[..3]).unwrap();
+    let store = FileStore::<Rsa2048, u64>::open(&path).unwrap();
+    assert_eq!(store.epoch(), 0);
+    fs::remove_file(&path).unwrap();
+  }
+}
@@ -1,6 +1,10 @@
-//! Zero-allocation U256 and U512 types built on GMP. We created this module specifically for our
-//! use case of implementing primality checking over 256-bit integers, but it may be worth
-//! polishing a bit for more general use.
+//! Zero-allocation U256 and U512 types built on GMP (or, with the `pure-rust` feature, on the
+//! stand-ins in `bigint::mpn`). We created this module specifically for our use case of
+//! implementing primality checking over 256-bit integers, but it may be worth polishing a bit for
+//! more general use.
+//!
+//! The same approach gives us U2048, which stores RSA group elements in Montgomery form (see
+//! `U2048::mont_mul`).
 //!
 //! Obviously there are a lot of `unsafe` blocks to work with GMP. Take care when using this module
 //! because there may be bugs we did not catch.
@@ -8,10 +12,12 @@
 //! TODO: Benchmark our U256 vs. 256-bit `rug::Integer` vs. Parity U256.
 #![allow(clippy::cast_sign_loss)]
 
+#[cfg(feature = "pure-rust")]
+use crate::bigint::mpn as gmp;
+use crate::bigint::{Integer, Order};
+use gmp::mpz_t;
+#[cfg(not(feature = "pure-rust"))]
 use gmp_mpfr_sys::gmp;
-use gmp_mpfr_sys::gmp::mpz_t;
-use rug::integer::Order;
-use rug::Integer;
 use std::cmp::{min, Ord, Ordering, PartialOrd};
 use std::convert::From;
 use std::mem::transmute;
@@ -412,11 +418,21 @@ macro_rules! u_types {
           Self::from_digits(&x.limbs, Order::Lsf)
         }
       }
+
+      /// Panics if `x` is negative or does not fit.
+      impl From<&Integer> for $t {
+        fn from(x: &Integer) -> Self {
+          assert!(*x >= 0);
+          let mut limbs = [0; $size];
+          x.write_digits(&mut limbs, Order::Lsf);
+          Self::from(limbs)
+        }
+      }
     )+
   }
 }
 
-u_types!(U256, 4, U512, 8);
+u_types!(U256, 4, U512, 8, U2048, 32);
 
 impl U512 {
   /// Returns the lower half of this `U512` as a `U256`.
@@ -511,6 +527,88 @@ impl ops::Mul for U256 {
   }
 }
 
+/// Montgomery arithmetic modulo an odd 2048-bit `m`, with `R = 2^2048`. Callers supply
+/// `m_inv = -m^-1 mod 2^64` (see `U2048::mont_inv_limb`), and all operands must be less than `m`.
+impl U2048 {
+  /// Returns `-self^-1 mod 2^64`. Panics if `self` is even.
+  pub fn mont_inv_limb(&self) -> u64 {
+    assert!(self.is_odd());
+    // Newton iteration doubles the number of correct low bits each step: 1, 2, 4, ..., 64.
+    let m0 = self.limbs[0];
+    let mut inv = 1_u64;
+    for _ in 0..6 {
+      inv = inv.wrapping_mul(2_u64.wrapping_sub(m0.wrapping_mul(inv)));
+    }
+    inv.wrapping_neg()
+  }
+
+  /// Returns `self * x * R^-1 mod m`.
+  pub fn mont_mul(&self, x: &Self, m: &Self, m_inv: u64) -> Self {
+    let mut t = [0; 64];
+    unsafe { gmp::mpn_mul_n(t.as_mut_ptr(), self.data(), x.data(), 32) };
+    Self::redc(&mut t, m, m_inv)
+  }
+
+  /// Returns `self * self * R^-1 mod m`.
+  pub fn mont_sqr(&self, m: &Self, m_inv: u64) -> Self {
+    let mut t = [0; 64];
+    unsafe { gmp::mpn_sqr(t.as_mut_ptr(), self.data(), 32) };
+    Self::redc(&mut t, m, m_inv)
+  }
+
+  /// Montgomery reduction: returns `t * R^-1 mod m` for `t < m * R`, destroying `t`.
+  fn redc(t: &mut [u64; 64], m: &Self, m_inv: u64) -> Self {
+    // Zeroes out one low limb of `t` per iteration by adding a multiple of `m`. `carry` holds the
+    // overflow past limb `i + 32`, which is folded into the next iteration.
+    let mut carry = 0_u64;
+    for i in 0..32 {
+      let u = t[i].wrapping_mul(m_inv);
+      let c = unsafe { gmp::mpn_addmul_1(t[i..].as_mut_ptr(), m.data(), 32, u) };
+      let (sum, o1) = t[i + 32].overflowing_add(c);
+      let (sum, o2) = sum.overflowing_add(carry);
+      t[i + 32] = sum;
+      carry = u64::from(o1 || o2);
+    }
+    // The final subtraction of `m` is always computed and then selected with a mask, so that the
+    // timing of `mont_mul_sec` does not depend on whether it was needed.
+    let mut out = Self::zero();
+    out.limbs.copy_from_slice(&t[32..]);
+    let mut diff = Self::zero();
+    let borrow = unsafe { gmp::mpn_sub_n(diff.limbs.as_mut_ptr(), out.data(), m.data(), 32) };
+    let mask = (carry | (borrow ^ 1)).wrapping_neg();
+    for (o, d) in out.limbs.iter_mut().zip(diff.limbs.iter()) {
+      *o = (*o & !mask) | (d & mask);
+    }
+    out.normalize_size();
+    out
+  }
+
+  /// Like `mont_mul`, but performs the same sequence of operations and memory accesses for any two
+  /// operands. As in GMP's `mpn_sec_mul`, this multiplies with schoolbook `mpn_addmul_1` rows
+  /// instead of `mpn_mul_n`, whose subquadratic algorithms branch on operand values.
+  pub fn mont_mul_sec(&self, x: &Self, m: &Self, m_inv: u64) -> Self {
+    let mut t = [0; 64];
+    for i in 0..32 {
+      t[i + 32] = unsafe { gmp::mpn_addmul_1(t[i..].as_mut_ptr(), self.data(), 32, x.limbs[i]) };
+    }
+    Self::redc(&mut t, m, m_inv)
+  }
+
+  /// Returns `table[i]`, reading every entry so that the memory access pattern does not depend on
+  /// `i` (cf. GMP's `mpn_sec_tabselect`).
+  pub fn sec_select(table: &[Self], i: usize) -> Self {
+    let mut out = Self::zero();
+    for (j, entry) in table.iter().enumerate() {
+      let mask = u64::from(j == i).wrapping_neg();
+      for (o, e) in out.limbs.iter_mut().zip(entry.limbs.iter()) {
+        *o |= e & mask;
+      }
+    }
+    out.normalize_size();
+    out
+  }
+}
+
 #[allow(unused_mut)]
 fn mut_ptr<T>(mut t: &T) -> *mut T {
   t as *const T as *mut T
@@ -534,7 +632,7 @@ fn i32_to_mpz(i: i32, data: &mut u64) -> mpz_t {
   *data = i.abs() as u64;
   mpz_t {
     size: i.signum(),
-    d: std::ptr::NonNull::new(data).unwrap() ,
+    d: std::ptr::NonNull::new(data).unwrap(),
     alloc: 1,
   }
 }
@@ -595,6 +693,31 @@ mod tests {
     assert!(u256([1, 0, 1, 0]) % u256([0, 1, 0, 0]) == u256(1));
   }
 
+  #[test]
+  fn test_mont_mul() {
+    // A small odd modulus padded to 2048 bits, so `R = 2^2048` still.
+    let m = U2048::from(1_000_000_007);
+    let m_inv = m.mont_inv_limb();
+    assert_eq!(m_inv.wrapping_mul(1_000_000_007), u64::MAX);
+    let r = Integer::from(1) << 2048;
+    let to_mont = |x: u64| U2048::from(&(Integer::from(x) * &r % 1_000_000_007));
+    let from_mont = |x: U2048| x.mont_mul(&U2048::one(), &m, m_inv);
+    let product = to_mont(123_456).mont_mul(&to_mont(654_321), &m, m_inv);
+    assert!(from_mont(product) == U2048::from(123_456 * 654_321 % 1_000_000_007));
+    let square = to_mont(999_999_999).mont_sqr(&m, m_inv);
+    assert!(from_mont(square) == U2048::from(999_999_999 * 999_999_999 % 1_000_000_007));
+    let product_sec = to_mont(123_456).mont_mul_sec(&to_mont(654_321), &m, m_inv);
+    assert!(product_sec == product);
+  }
+
+  #[test]
+  fn test_sec_select() {
+    let table = [U2048::from(3), U2048::zero(), U2048::from(u64::MAX)];
+    for (i, entry) in table.iter().enumerate() {
+      assert!(U2048::sec_select(&table, i) == *entry);
+    }
+  }
+
   #[test]
   fn test_rem512() {
     assert!(u512(0) % u256(3) == u256(0));
@@ -1,9 +1,14 @@
 //! Miscellaneous functions used throughout the library.
+use crate::bigint::Integer;
 use crate::group::Group;
 use crate::hash::hash_to_prime;
-use rug::Integer;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use std::hash::Hash;
 
+/// Below this many factors, `product` multiplies sequentially instead of splitting further.
+const PRODUCT_LEAF_SIZE: usize = 64;
+
 /// Pseudo-type-level programming.
 /// This trait allows us to reflect "type-level" (i.e. static) information at runtime.
 pub trait TypeRep: 'static {
@@ -22,9 +27,52 @@ where
   Integer::from(val)
 }
 
+/// Types that can be hashed to primes by this library. They must be `Sync` so that the `parallel`
+/// feature can hash them on multiple threads. The bound applies with or without the feature, so
+/// that enabling it anywhere in a dependency graph cannot break a build.
+pub trait PrimeHashable: Hash + Sync {}
+impl<T: Hash + Sync + ?Sized> PrimeHashable for T {}
+
+/// Runs `a` and `b`, on separate threads if the `parallel` feature is enabled.
+pub fn join<A, B, RA, RB>(a: A, b: B) -> (RA, RB)
+where
+  A: FnOnce() -> RA + Send,
+  B: FnOnce() -> RB + Send,
+  RA: Send,
+  RB: Send,
+{
+  #[cfg(feature = "parallel")]
+  return rayon::join(a, b);
+  #[cfg(not(feature = "parallel"))]
+  (a(), b())
+}
+
+/// Hashes each of its arguments to a prime.
+#[cfg(not(feature = "parallel"))]
+pub fn prime_hashes<T: PrimeHashable>(ts: &[T]) -> Vec<Integer> {
+  ts.iter().map(hash_to_prime).collect()
+}
+
+/// Hashes each of its arguments to a prime.
+#[cfg(feature = "parallel")]
+pub fn prime_hashes<T: PrimeHashable>(ts: &[T]) -> Vec<Integer> {
+  ts.par_iter().map(hash_to_prime).collect()
+}
+
 /// Hashes its arguments to primes and takes their product.
-pub fn prime_hash_product<T: Hash>(ts: &[T]) -> Integer {
-  ts.iter().map(hash_to_prime).product()
+pub fn prime_hash_product<T: PrimeHashable>(ts: &[T]) -> Integer {
+  product(&prime_hashes(ts))
+}
+
+/// Computes the product of `xs` with a product tree, which keeps the operands of each
+/// multiplication balanced and lets the `parallel` feature multiply subtrees concurrently.
+pub fn product(xs: &[Integer]) -> Integer {
+  if xs.len() <= PRODUCT_LEAF_SIZE {
+    return xs.iter().product();
+  }
+  let (left, right) = xs.split_at(xs.len() / 2);
+  let (left, right) = join(|| product(left), || product(right));
+  left * right
 }
 
 /// Computes the `(xy)`th root of `g` given the `x`th and `y`th roots of `g` and `(x, y)` coprime.
@@ -50,12 +98,17 @@ pub fn shamir_trick<G: Group>(
 }
 
 /// Solves a linear congruence of form `ax = b mod m` for the set of solutions `x`. Solution sets
-/// are characterized by integers `mu` and `v` s.t. `x = mu + vn` and `n` is any integer.
+/// are characterized by integers `mu` and `v` s.t. `x = mu + vn` and `n` is any integer. Returns
+/// `None` if there are no solutions, or if `m` is zero.
 pub fn solve_linear_congruence(
   a: &Integer,
   b: &Integer,
   m: &Integer,
 ) -> Option<(Integer, Integer)> {
+  if *m == 0 {
+    return None;
+  }
+
   // g = gcd(a, m) => da + em = g
   let (g, d, _) = <(Integer, Integer, Integer)>::from(a.gcd_cofactors_ref(m));
 
@@ -72,10 +125,13 @@ pub fn solve_linear_congruence(
 }
 
 /// Folds over `xs` but in a divide-and-conquer fashion: Instead of `F(F(F(F(acc, a), b), c), d))`
-/// this computes `F(acc, F(F(a, b), F(c, d)))`.
-pub fn divide_and_conquer<F, T: Clone, E>(f: F, acc: T, xs: &[T]) -> Result<T, E>
+/// this computes `F(acc, F(F(a, b), F(c, d)))`. With the `parallel` feature, the two halves at
+/// each level are folded on separate threads.
+pub fn divide_and_conquer<F, T, E>(f: F, acc: T, xs: &[T]) -> Result<T, E>
 where
-  F: Fn(&T, &T) -> Result<T, E>,
+  F: Fn(&T, &T) -> Result<T, E> + Sync,
+  T: Clone + Send + Sync,
+  E: Send,
 {
   if xs.is_empty() {
     return Ok(acc);
@@ -84,9 +140,11 @@ where
   Ok(f(&acc, &divide_and_conquer_(&f, xs)?)?)
 }
 
-fn divide_and_conquer_<F, T: Clone, E>(f: &F, xs: &[T]) -> Result<T, E>
+fn divide_and_conquer_<F, T, E>(f: &F, xs: &[T]) -> Result<T, E>
 where
-  F: Fn(&T, &T) -> Result<T, E>,
+  F: Fn(&T, &T) -> Result<T, E> + Sync,
+  T: Clone + Send + Sync,
+  E: Send,
 {
   if xs.len() == 1 {
     return Ok(xs[0].clone());
@@ -95,10 +153,11 @@ where
   let mid = xs.len() / 2;
   let left = &xs[..mid];
   let right = &xs[mid..];
-  Ok(f(
-    &divide_and_conquer_(f, left)?,
-    &divide_and_conquer_(f, right)?,
-  )?)
+  let (left, right) = join(
+    || divide_and_conquer_(f, left),
+    || divide_and_conquer_(f, right),
+  );
+  Ok(f(&left?, &right?)?)
 }
 
 #[cfg(test)]
@@ -165,6 +224,9 @@ mod tests {
     let result =
       solve_linear_congruence(&Integer::from(13), &Integer::from(14), &Integer::from(39));
     assert!(result.is_none());
+
+    let result = solve_linear_congruence(&Integer::from(0), &Integer::from(1), &Integer::from(0));
+    assert!(result.is_none());
   }
 
   #[test]
@@ -189,4 +251,13 @@ mod tests {
     let ints = vec![int(3), int(5), int(7), int(9), int(11)];
     assert!(merge_product(&ints) == int(10395));
   }
+
+  #[test]
+  fn test_product() {
+    let ints = (1..=200).map(int).collect::<Vec<_>>();
+    let expected: Integer = ints.iter().product();
+    assert_eq!(product(&ints), expected);
+    assert_eq!(product(&ints[..3]), int(6));
+    assert_eq!(product(&[]), int(1));
+  }
 }
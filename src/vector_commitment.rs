@@ -1,18 +1,171 @@
 //! Vector commitment library, built on a generic group interface. **Very much a WIP.**
-use super::accumulator::{Accumulator, MembershipProof, NonmembershipProof, Witness};
+//!
+//! The basic `VectorCommitment` commits to a vector of bits. Vectors of `k`-bit values (BBF
+//! Section 5) are committed to by laying out entry `i` at bit positions `i * k .. (i + 1) * k`,
+//! least significant bit first; see `update_values`, `open_values` and `verify_values`.
+//!
+//! Callers who would rather not track the set indices and witnesses themselves can use
+//! `VectorCommitmentManager`, which owns the vector contents.
+//!
+//! A `FixedLengthVectorCommitment` additionally binds the vector length `n`, by accumulating the
+//! marker element `-(n + 1)` alongside the set indices. Its openings reject indices outside `0..n`
+//! and prove that the marker is accumulated, and a constant-size `BoundProof` shows that nothing
+//! else was accumulated, i.e. no bit at or past `n` is set.
+use super::accumulator::{AccError, Accumulator, MembershipProof, NonmembershipProof, Witness};
+use crate::bigint::Integer;
 use crate::group::UnknownOrderGroup;
-use rug::Integer;
-use std::collections::HashSet;
+use crate::proof::{verify_batch, Equation, Poke2};
+use crate::store::{decode_list, Persist};
+use crate::util::{int, prime_hash_product, prime_hashes, product};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::convert::TryFrom;
+use std::error;
+use std::fmt;
+use std::slice;
 
 #[derive(Debug)]
-/// The different types of vector commitment errors.
+/// The different types of vector commitment errors. Where an error concerns a single index, it is
+/// the bit index (or, for `k`-bit entries, the entry index) that caused it.
 pub enum VCError {
-  /// When there are conflicting indices in the vector commitment.
-  ConflictingIndices,
-  /// When an opening fails.
-  InvalidOpen,
+  /// When bit `index` is given conflicting values.
+  ConflictingIndices {
+    /// The offending index.
+    index: Integer,
+  },
+  /// When an opening fails at bit `index`.
+  InvalidOpen {
+    /// The offending index.
+    index: Integer,
+  },
+  /// When the opening at position `opening` among the inputs has an invalid proof.
+  InvalidProof {
+    /// Position of the offending opening.
+    opening: usize,
+  },
+  /// When the given set indices are not exactly the set bits of the vector commitment.
+  InvalidSet,
   /// Unexpected state during an update.
   UnexpectedState,
+  /// When the value of entry `index` does not fit in (or, for byte strings, does not fill) the
+  /// entry width.
+  InvalidValue {
+    /// The offending index.
+    index: Integer,
+  },
+  /// When `index` lies outside `0..len` of a fixed-length vector commitment.
+  IndexOutOfRange {
+    /// The offending index.
+    index: Integer,
+  },
+}
+
+impl VCError {
+  // Converts an accumulator error about the element at some position among `indices` into one
+  // about the bit index itself.
+  fn invalid_open(indices: &[Integer], err: &AccError) -> Self {
+    match err.index().and_then(|i| indices.get(i)) {
+      Some(index) => VCError::InvalidOpen {
+        index: index.clone(),
+      },
+      None => VCError::UnexpectedState,
+    }
+  }
+}
+
+impl fmt::Display for VCError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      VCError::ConflictingIndices { index } => {
+        write!(f, "index {} is given conflicting values", index)
+      }
+      VCError::InvalidOpen { index } => write!(
+        f,
+        "index {} does not have the claimed value in the vector commitment",
+        index
+      ),
+      VCError::InvalidProof { opening } => {
+        write!(f, "proof of opening {} does not verify", opening)
+      }
+      VCError::InvalidSet => f.write_str("set indices do not match the vector commitment"),
+      VCError::UnexpectedState => f.write_str("vector commitment is in an unexpected state"),
+      VCError::InvalidValue { index } => {
+        write!(f, "value of entry {} does not fit the entry width", index)
+      }
+      VCError::IndexOutOfRange { index } => write!(
+        f,
+        "index {} is out of range for the fixed-length vector commitment",
+        index
+      ),
+    }
+  }
+}
+
+impl error::Error for VCError {}
+
+/// A value that can be stored as an entry of a multi-valued vector commitment.
+pub trait VCValue {
+  /// Returns the `k` bits of this value, least significant first, or `None` if the value does not
+  /// have a `k`-bit representation.
+  fn to_bits(&self, k: usize) -> Option<Vec<bool>>;
+}
+
+impl VCValue for u64 {
+  fn to_bits(&self, k: usize) -> Option<Vec<bool>> {
+    if k < 64 && *self >> k != 0 {
+      return None;
+    }
+    Some((0..k).map(|j| j < 64 && (self >> j) & 1 == 1).collect())
+  }
+}
+
+/// Byte strings must be exactly `k / 8` bytes long. Bytes are laid out in order, each least
+/// significant bit first.
+impl VCValue for [u8] {
+  fn to_bits(&self, k: usize) -> Option<Vec<bool>> {
+    if self.len() * 8 != k {
+      return None;
+    }
+    Some(
+      self
+        .iter()
+        .flat_map(|byte| (0..8).map(move |j| (byte >> j) & 1 == 1))
+        .collect(),
+    )
+  }
+}
+
+impl VCValue for Vec<u8> {
+  fn to_bits(&self, k: usize) -> Option<Vec<bool>> {
+    self.as_slice().to_bits(k)
+  }
+}
+
+impl<V: VCValue + ?Sized> VCValue for &V {
+  fn to_bits(&self, k: usize) -> Option<Vec<bool>> {
+    (*self).to_bits(k)
+  }
+}
+
+/// Decomposes `k`-bit `entries` (index, value) into the tuples (truth value, bit index) of the
+/// underlying bit vector. The bit indices that come out true are the ones to track in `vc_acc_set`.
+pub fn value_bits<V: VCValue>(
+  k: usize,
+  entries: &[(Integer, V)],
+) -> Result<Vec<(bool, Integer)>, VCError> {
+  let mut bits = Vec::with_capacity(entries.len() * k);
+  for (i, value) in entries {
+    let value_bits = value
+      .to_bits(k)
+      .ok_or_else(|| VCError::InvalidValue { index: i.clone() })?;
+    let offset = int(i * k);
+    bits.extend(
+      value_bits
+        .into_iter()
+        .enumerate()
+        .map(|(j, bit)| (bit, int(&offset + j))),
+    );
+  }
+  Ok(bits)
 }
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
@@ -24,7 +177,99 @@ pub struct VectorCommitment<G: UnknownOrderGroup>(Accumulator<G, Integer>);
 /// A vector commitment proof.
 pub struct VectorProof<G: UnknownOrderGroup> {
   membership_proof: MembershipProof<G, Integer>,
-  nonmembership_proof: NonmembershipProof<G, Integer>,
+  // One nonmembership proof per opening that went into this proof (see `combine`), each with the
+  // number of zero bits it covers. Not constant size once openings are combined.
+  nonmembership_proofs: Vec<(usize, NonmembershipProof<G, Integer>)>,
+}
+
+impl<G: UnknownOrderGroup> Persist for VectorCommitment<G>
+where
+  G::Elem: Persist,
+{
+  fn encode(&self, out: &mut Vec<u8>) {
+    self.0.encode(out);
+  }
+
+  fn decode(bytes: &mut &[u8]) -> Option<Self> {
+    Some(Self(Accumulator::decode(bytes)?))
+  }
+}
+
+/// Encoded as the membership proof, then the list of nonmembership proofs, each preceded by the
+/// number of zero bits it covers.
+impl<G: UnknownOrderGroup> Persist for VectorProof<G>
+where
+  G::Elem: Persist,
+{
+  fn encode(&self, out: &mut Vec<u8>) {
+    self.membership_proof.encode(out);
+    (self.nonmembership_proofs.len() as u64).encode(out);
+    for (zero_bits, proof) in &self.nonmembership_proofs {
+      (*zero_bits as u64).encode(out);
+      proof.encode(out);
+    }
+  }
+
+  fn decode(bytes: &mut &[u8]) -> Option<Self> {
+    let membership_proof = MembershipProof::decode(bytes)?;
+    let nonmembership_proofs = decode_list::<(u64, NonmembershipProof<G, Integer>)>(bytes)?
+      .into_iter()
+      .map(|(zero_bits, proof)| Some((usize::try_from(zero_bits).ok()?, proof)))
+      .collect::<Option<_>>()?;
+    Some(Self {
+      membership_proof,
+      nonmembership_proofs,
+    })
+  }
+}
+
+impl<G: UnknownOrderGroup> VectorProof<G> {
+  /// Combines openings of disjoint index sets of `vc`, possibly made by different provers, into a
+  /// single opening. The result verifies against the concatenation of the openings' bits, in order.
+  ///
+  /// Only the membership parts are aggregated, into one constant-size proof. The nonmembership
+  /// parts are concatenated, so the result holds one nonmembership proof per opening and grows
+  /// linearly with the number of openings. Aggregating those as well (BBF Section 3.3) needs each
+  /// prover's Bezout coefficients, which a `VectorProof` does not carry.
+  ///
+  /// # Arguments
+  ///
+  /// * `openings` - Tuples consisting of (opened bits, proof of those bits).
+  pub fn combine(
+    vc: &VectorCommitment<G>,
+    openings: &[(&[(bool, Integer)], &Self)],
+  ) -> Result<Self, VCError> {
+    let all_bits: Vec<(bool, Integer)> = openings
+      .iter()
+      .flat_map(|(bits, _)| bits.iter().cloned())
+      .collect();
+    group_elems_by_bit(&all_bits)?;
+
+    let mut one_bits = vec![];
+    for (bits, _) in openings {
+      one_bits.push(group_elems_by_bit(bits)?.1);
+    }
+    let membership_proofs: Vec<_> = one_bits
+      .iter()
+      .zip(openings)
+      .map(|(elems, (_, proof))| (&elems[..], &proof.membership_proof))
+      .collect();
+    let membership_proof = vc
+      .0
+      .aggregate_membership_proofs(&membership_proofs)
+      .map_err(|err| match err.index() {
+        Some(opening) => VCError::InvalidProof { opening },
+        None => VCError::UnexpectedState,
+      })?;
+    let nonmembership_proofs = openings
+      .iter()
+      .flat_map(|(_, proof)| proof.nonmembership_proofs.iter().cloned())
+      .collect();
+    Ok(Self {
+      membership_proof,
+      nonmembership_proofs,
+    })
+  }
 }
 
 fn group_elems_by_bit(bits: &[(bool, Integer)]) -> Result<(Vec<Integer>, Vec<Integer>), VCError> {
@@ -33,7 +278,7 @@ fn group_elems_by_bit(bits: &[(bool, Integer)]) -> Result<(Vec<Integer>, Vec<Int
   let mut seen_indices = HashSet::new();
   for (bit, i) in bits {
     if !seen_indices.insert(i) {
-      return Err(VCError::ConflictingIndices);
+      return Err(VCError::ConflictingIndices { index: i.clone() });
     }
     if *bit {
       elems_with_one.push(i.clone());
@@ -67,12 +312,12 @@ impl<G: UnknownOrderGroup> VectorCommitment<G> {
     let (new_acc, membership_proof) = vc.0.add_with_proof(&elems_with_one);
     let nonmembership_proof = new_acc
       .prove_nonmembership(vc_acc_set, &elems_with_zero)
-      .map_err(|_| VCError::UnexpectedState)?;
+      .map_err(|err| VCError::invalid_open(&elems_with_zero, &err))?;
     Ok((
       Self(new_acc),
       VectorProof {
         membership_proof,
-        nonmembership_proof,
+        nonmembership_proofs: vec![(elems_with_zero.len(), nonmembership_proof)],
       },
     ))
   }
@@ -89,17 +334,17 @@ impl<G: UnknownOrderGroup> VectorCommitment<G> {
     zero_bits: &[Integer],
     one_bit_witnesses: &[(Integer, Witness<G, Integer>)],
   ) -> Result<VectorProof<G>, VCError> {
-    let membership_proof = vc
-      .0
-      .prove_membership(one_bit_witnesses)
-      .map_err(|_| VCError::InvalidOpen)?;
+    let membership_proof = vc.0.prove_membership(one_bit_witnesses).map_err(|err| {
+      let one_bits: Vec<Integer> = one_bit_witnesses.iter().map(|(i, _)| i.clone()).collect();
+      VCError::invalid_open(&one_bits, &err)
+    })?;
     let nonmembership_proof = vc
       .0
       .prove_nonmembership(vc_acc_set, zero_bits)
-      .map_err(|_| VCError::InvalidOpen)?;
+      .map_err(|err| VCError::invalid_open(zero_bits, &err))?;
     Ok(VectorProof {
       membership_proof,
-      nonmembership_proof,
+      nonmembership_proofs: vec![(zero_bits.len(), nonmembership_proof)],
     })
   }
 
@@ -109,29 +354,922 @@ impl<G: UnknownOrderGroup> VectorCommitment<G> {
   ///
   /// * `bits` - Tuples (truth value, bit index) to verify.
   /// * `VectorProof` - A `VectorProof` to verify against.
-  pub fn verify(
+  pub fn verify(vc: &Self, bits: &[(bool, Integer)], proof: &VectorProof<G>) -> bool {
+    Self::equations_(vc, bits, proof, prime_hash_product)
+      .is_some_and(|equations| equations.iter().all(Equation::holds))
+  }
+
+  /// Verifies openings of many (possibly different) VCs in one batch. Indices that appear in
+  /// several openings are hashed to primes once, and the PoE and PoKE2 checks of all the openings
+  /// are combined with random exponents into a single check (see `proof::verify_batch`), which
+  /// shares one chain of squarings between them. Unlike `verify`, a failing batch does not tell
+  /// which opening is invalid.
+  ///
+  /// # Arguments
+  ///
+  /// * `openings` - Tuples consisting of (VC, bits to verify, proof to verify against).
+  #[allow(clippy::type_complexity)]
+  pub fn verify_many(openings: &[(&Self, &[(bool, Integer)], &VectorProof<G>)]) -> bool {
+    let mut indices: Vec<Integer> = openings
+      .iter()
+      .flat_map(|(_, bits, _)| bits.iter().map(|(_, i)| i.clone()))
+      .collect();
+    indices.sort();
+    indices.dedup();
+    let primes: HashMap<&Integer, Integer> = indices.iter().zip(prime_hashes(&indices)).collect();
+    let prime_product = |elems: &[Integer]| {
+      let elem_primes: Vec<Integer> = elems.iter().map(|i| primes[i].clone()).collect();
+      product(&elem_primes)
+    };
+    let mut equations = vec![];
+    for (vc, bits, proof) in openings {
+      match Self::equations_(vc, bits, proof, prime_product) {
+        Some(opening_equations) => equations.extend(opening_equations),
+        None => return false,
+      }
+    }
+    verify_batch(&equations)
+  }
+
+  // Returns the equations that verify an opening, or `None` if it is malformed.
+  fn equations_<F: Fn(&[Integer]) -> Integer>(
     vc: &Self,
     bits: &[(bool, Integer)],
     VectorProof {
       membership_proof,
-      nonmembership_proof,
+      nonmembership_proofs,
     }: &VectorProof<G>,
+    prime_product: F,
+  ) -> Option<Vec<Equation<G>>> {
+    let (elems_with_zero, elems_with_one) = group_elems_by_bit(&bits).ok()?;
+    let mut equations = vc
+      .0
+      .membership_equations_product(&prime_product(&elems_with_one), membership_proof)?;
+    // The zero bit counts come from the proof, so they may be arbitrary.
+    let zero_count = nonmembership_proofs
+      .iter()
+      .try_fold(0_usize, |sum, (n, _)| sum.checked_add(*n));
+    if zero_count != Some(elems_with_zero.len()) {
+      return None;
+    }
+    let mut remaining_zeros = &elems_with_zero[..];
+    for (n, nonmembership_proof) in nonmembership_proofs {
+      let (zeros, rest) = remaining_zeros.split_at(*n);
+      remaining_zeros = rest;
+      equations.extend(
+        vc.0
+          .nonmembership_equations_product(&prime_product(zeros), nonmembership_proof)?,
+      );
+    }
+    Some(equations)
+  }
+
+  /// Updates a VC of `k`-bit entries with a list of (index, value) pairs. As with `update`, bits
+  /// can only be set, so each entry should be written at most once.
+  ///
+  /// # Arguments
+  ///
+  /// * `vc_acc_set` - All bit indices that are set (True), as returned by `value_bits`.
+  /// * `k` - Width of each entry in bits.
+  /// * `entries` - Tuples (entry index, value) to set.
+  pub fn update_values<V: VCValue>(
+    vc: Self,
+    vc_acc_set: &[Integer],
+    k: usize,
+    entries: &[(Integer, V)],
+  ) -> Result<(Self, VectorProof<G>), VCError> {
+    Self::update(vc, vc_acc_set, &value_bits(k, entries)?)
+  }
+
+  /// Opens a VC of `k`-bit entries at the given (index, value) pairs, producing a single proof
+  /// covering every underlying bit position.
+  ///
+  /// # Arguments
+  ///
+  /// * `vc_acc_set` - All bit indices that are set (True).
+  /// * `k` - Width of each entry in bits.
+  /// * `entries` - Tuples (entry index, value) to open.
+  pub fn open_values<V: VCValue>(
+    vc: &Self,
+    vc_acc_set: &[Integer],
+    k: usize,
+    entries: &[(Integer, V)],
+  ) -> Result<VectorProof<G>, VCError> {
+    let bits = value_bits(k, entries)?;
+    let (zero_bits, one_bits) = group_elems_by_bit(&bits)?;
+    let one_bit_set: HashSet<_> = one_bits.iter().collect();
+    if let Some(i) = one_bits.iter().find(|i| !vc_acc_set.contains(i)) {
+      return Err(VCError::InvalidOpen { index: i.clone() });
+    }
+    let others: Vec<Integer> = vc_acc_set
+      .iter()
+      .filter(|i| !one_bit_set.contains(i))
+      .cloned()
+      .collect();
+    let one_bit_witnesses =
+      Witness(Accumulator::<G, Integer>::from(&others[..])).compute_individual_witnesses(&one_bits);
+    Self::open(vc, vc_acc_set, &zero_bits, &one_bit_witnesses)
+  }
+
+  /// Verifies an opening of a VC of `k`-bit entries.
+  ///
+  /// # Arguments
+  ///
+  /// * `k` - Width of each entry in bits.
+  /// * `entries` - Tuples (entry index, value) to verify.
+  /// * `VectorProof` - A `VectorProof` to verify against.
+  pub fn verify_values<V: VCValue>(
+    vc: &Self,
+    k: usize,
+    entries: &[(Integer, V)],
+    proof: &VectorProof<G>,
   ) -> bool {
-    let group_result = group_elems_by_bit(&bits);
-    if group_result.is_err() {
+    match value_bits(k, entries) {
+      Ok(bits) => Self::verify(vc, &bits, proof),
+      Err(_) => false,
+    }
+  }
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+/// A vector commitment to a vector of fixed length `len`, which is bound into the commitment by
+/// accumulating the length marker `-(len + 1)`.
+///
+/// Openings cover the marker along with the set bits, so they only verify against a commitment
+/// that holds the marker for `len`. That alone does not rule out bits set at or past `len`, which
+/// a `BoundProof` does.
+pub struct FixedLengthVectorCommitment<G: UnknownOrderGroup> {
+  vc: VectorCommitment<G>,
+  len: Integer,
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+/// A constant-size proof that a fixed-length vector commitment holds no set bits at or past
+/// `len`, and holds the length marker exactly once. It reveals nothing about which bits are set.
+///
+/// Concretely, it shows that the accumulated prime hash product divides the product of the prime
+/// hashes of `0..len` and the length marker (see `Accumulator::prove_divides_product`). Proving
+/// and verifying both hash all of `0..len` to primes, so take time linear in `len`.
+pub struct BoundProof<G: UnknownOrderGroup>(Poke2<G>);
+
+/// Returns the element accumulated to bind the length `len`. Negative, so it never collides with
+/// an index.
+fn length_marker(len: &Integer) -> Integer {
+  int(-1) - len
+}
+
+/// Returns the prime hash product of `0..len` and the length marker: the largest set a commitment
+/// of length `len` can accumulate.
+fn bound_product(len: &Integer) -> Integer {
+  let mut elems = vec![length_marker(len)];
+  let mut i = int(0);
+  while i < *len {
+    elems.push(i.clone());
+    i += 1;
+  }
+  prime_hash_product(&elems)
+}
+
+fn check_range<'a>(
+  len: &Integer,
+  mut indices: impl Iterator<Item = &'a Integer>,
+) -> Result<(), VCError> {
+  match indices.find(|i| **i < 0 || *i >= len) {
+    Some(i) => Err(VCError::IndexOutOfRange { index: i.clone() }),
+    None => Ok(()),
+  }
+}
+
+impl<G: UnknownOrderGroup> FixedLengthVectorCommitment<G> {
+  /// Initializes a new all-zero vector commitment of length `len`.
+  pub fn new(len: Integer) -> Self {
+    let acc = Accumulator::empty().add(&[length_marker(&len)]);
+    Self {
+      vc: VectorCommitment(acc),
+      len,
+    }
+  }
+
+  /// Returns the length of the committed vector.
+  pub fn len(&self) -> &Integer {
+    &self.len
+  }
+
+  /// Returns whether the committed vector has length zero.
+  pub fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+
+  /// Returns the underlying vector commitment, which also accumulates the length marker.
+  pub fn vector_commitment(&self) -> &VectorCommitment<G> {
+    &self.vc
+  }
+
+  /// Like `VectorCommitment::update`, but rejects indices outside `0..len`, and the proof also
+  /// covers the length marker.
+  ///
+  /// # Arguments
+  ///
+  /// * `vc_acc_set` - All indices that are set (True).
+  /// * `bits` - Tuples (truth value, bit index) to set.
+  /// * `length_witness` - Witness for the length marker, i.e. the accumulator of all indices that
+  ///   were set before this update. Afterwards, the witness is the old one with the newly set
+  ///   indices added.
+  pub fn update(
+    flvc: Self,
+    vc_acc_set: &[Integer],
+    bits: &[(bool, Integer)],
+    length_witness: &Witness<G, Integer>,
+  ) -> Result<(Self, VectorProof<G>), VCError> {
+    check_range(&flvc.len, bits.iter().map(|(_, i)| i))?;
+    let marker = length_marker(&flvc.len);
+    let mut acc_set = vc_acc_set.to_vec();
+    acc_set.push(marker.clone());
+    let (vc, mut proof) = VectorCommitment::update(flvc.vc, &acc_set, bits)?;
+    // The witness for the marker in the old accumulator is one for the marker and the added bits
+    // in the new one.
+    let (_, mut one_bits) = group_elems_by_bit(bits)?;
+    one_bits.push(marker);
+    proof.membership_proof = vc
+      .0
+      .prove_membership_with_witness(&one_bits, length_witness.clone());
+    if !vc
+      .0
+      .verify_membership_batch(&one_bits, &proof.membership_proof)
+    {
+      return Err(VCError::UnexpectedState);
+    }
+    Ok((Self { vc, len: flvc.len }, proof))
+  }
+
+  /// Like `VectorCommitment::open`, but rejects indices outside `0..len`, and the proof also
+  /// covers the length marker.
+  ///
+  /// # Arguments
+  /// * `vc_acc_set` - All indices that are set (True).
+  /// * `zero_bits` - Indices you want to prove are unset (False).
+  /// * `one_bit_witnesses` - Indices you want to prove are set (True) and their witnesses, which
+  ///   must account for the length marker.
+  /// * `length_witness` - Witness for the length marker, i.e. the accumulator of `vc_acc_set`.
+  pub fn open(
+    flvc: &Self,
+    vc_acc_set: &[Integer],
+    zero_bits: &[Integer],
+    one_bit_witnesses: &[(Integer, Witness<G, Integer>)],
+    length_witness: &Witness<G, Integer>,
+  ) -> Result<VectorProof<G>, VCError> {
+    check_range(
+      &flvc.len,
+      zero_bits
+        .iter()
+        .chain(one_bit_witnesses.iter().map(|(i, _)| i)),
+    )?;
+    let mut acc_set = vc_acc_set.to_vec();
+    acc_set.push(length_marker(&flvc.len));
+    let mut one_bit_witnesses = one_bit_witnesses.to_vec();
+    one_bit_witnesses.push((length_marker(&flvc.len), length_witness.clone()));
+    VectorCommitment::open(&flvc.vc, &acc_set, zero_bits, &one_bit_witnesses)
+  }
+
+  /// Like `VectorCommitment::verify`, but rejects indices outside `0..len`, and openings of a
+  /// commitment that does not hold the marker for `len`.
+  pub fn verify(flvc: &Self, bits: &[(bool, Integer)], proof: &VectorProof<G>) -> bool {
+    if check_range(&flvc.len, bits.iter().map(|(_, i)| i)).is_err() {
       return false;
     }
-    let (elems_with_zero, elems_with_one) = group_result.unwrap();
-    let verified_membership = vc
+    let mut bits = bits.to_vec();
+    bits.push((true, length_marker(&flvc.len)));
+    VectorCommitment::verify(&flvc.vc, &bits, proof)
+  }
+
+  /// Proves that no bit at or past `len` is set, given that the set bits are exactly `vc_acc_set`.
+  pub fn prove_bound(flvc: &Self, vc_acc_set: &[Integer]) -> Result<BoundProof<G>, VCError> {
+    check_range(&flvc.len, vc_acc_set.iter())?;
+    let mut acc_set = vc_acc_set.to_vec();
+    acc_set.push(length_marker(&flvc.len));
+    let proof = flvc
+      .vc
       .0
-      .verify_membership_batch(&elems_with_one, membership_proof);
-    let verified_nonmembership = vc
+      .prove_divides_product(&prime_hash_product(&acc_set), &bound_product(&flvc.len))
+      .ok_or(VCError::InvalidSet)?;
+    let proof = BoundProof(proof);
+    if !Self::verify_bound(flvc, &proof) {
+      return Err(VCError::InvalidSet);
+    }
+    Ok(proof)
+  }
+
+  /// Verifies that no bit at or past `len` is set, and that the length marker is accumulated
+  /// exactly once.
+  pub fn verify_bound(flvc: &Self, BoundProof(proof): &BoundProof<G>) -> bool {
+    flvc
+      .vc
       .0
-      .verify_nonmembership(&elems_with_zero, nonmembership_proof);
-    verified_membership && verified_nonmembership
+      .verify_divides_product(&bound_product(&flvc.len), proof)
+  }
+}
+
+#[derive(Clone, Debug)]
+/// A vector commitment together with the bit vector it commits to. Tracks which indices are set
+/// and caches a membership witness for each of them, so that openings need no extra input.
+///
+/// The witness cache is built (in O(N log N) group operations, for N set indices) on the first
+/// opening. After that, each `set` updates the cached witnesses in place, like
+/// `WitnessManager::update` does for its witness.
+pub struct VectorCommitmentManager<G: UnknownOrderGroup> {
+  vc: VectorCommitment<G>,
+  // Set for fixed-length vectors, whose length marker is accumulated alongside `set_indices`.
+  len: Option<Integer>,
+  set_indices: BTreeSet<Integer>,
+  witnesses: Option<HashMap<Integer, Witness<G, Integer>>>,
+}
+
+impl<G: UnknownOrderGroup> Default for VectorCommitmentManager<G> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<G: UnknownOrderGroup> VectorCommitmentManager<G> {
+  /// Creates a manager for the all-zero vector.
+  pub fn new() -> Self {
+    Self {
+      vc: VectorCommitment::empty(),
+      len: None,
+      set_indices: BTreeSet::new(),
+      witnesses: None,
+    }
+  }
+
+  /// Creates a manager for the all-zero vector of fixed length `len`. Reads and writes outside
+  /// `0..len` fail with `VCError::IndexOutOfRange`.
+  pub fn with_len(len: Integer) -> Self {
+    let flvc = FixedLengthVectorCommitment::new(len);
+    Self {
+      vc: flvc.vc,
+      len: Some(flvc.len),
+      set_indices: BTreeSet::new(),
+      witnesses: None,
+    }
+  }
+
+  /// Returns the current commitment.
+  pub fn commitment(&self) -> &VectorCommitment<G> {
+    &self.vc
+  }
+
+  /// Returns the current commitment with its length, if this manager was created by `with_len`.
+  pub fn fixed_length_commitment(&self) -> Option<FixedLengthVectorCommitment<G>> {
+    self.len.as_ref().map(|len| FixedLengthVectorCommitment {
+      vc: self.vc.clone(),
+      len: len.clone(),
+    })
+  }
+
+  /// Returns a `BoundProof` for a fixed-length vector.
+  pub fn prove_bound(&self) -> Result<BoundProof<G>, VCError> {
+    let flvc = self
+      .fixed_length_commitment()
+      .ok_or(VCError::UnexpectedState)?;
+    let vc_acc_set: Vec<Integer> = self.set_indices.iter().cloned().collect();
+    FixedLengthVectorCommitment::prove_bound(&flvc, &vc_acc_set)
+  }
+
+  /// Returns the bit at index `i`.
+  pub fn get(&self, i: &Integer) -> bool {
+    self.set_indices.contains(i)
+  }
+
+  /// Writes the tuples (truth value, bit index) in `bits`, returning a proof of their new values
+  /// against the updated commitment.
+  pub fn set(&mut self, bits: &[(bool, Integer)]) -> Result<VectorProof<G>, VCError> {
+    self.check_range(bits.iter().map(|(_, i)| i))?;
+    let (elems_with_zero, elems_with_one) = group_elems_by_bit(bits)?;
+    let additions: Vec<Integer> = elems_with_one
+      .into_iter()
+      .filter(|i| !self.set_indices.contains(i))
+      .collect();
+    let deletions: Vec<Integer> = elems_with_zero
+      .into_iter()
+      .filter(|i| self.set_indices.contains(i))
+      .collect();
+
+    if !additions.is_empty() || !deletions.is_empty() {
+      let mut acc = self.vc.0.clone();
+      if !deletions.is_empty() {
+        let elem_witnesses = self.witnesses_for(&deletions);
+        acc = acc
+          .delete(&elem_witnesses)
+          .map_err(|_| VCError::UnexpectedState)?;
+      }
+      let (acc, proof) = acc.add_with_proof(&additions);
+      if let Some(mut witnesses) = self.witnesses.take() {
+        for i in &deletions {
+          witnesses.remove(i);
+        }
+        for (i, witness) in witnesses.iter_mut() {
+          *witness = acc
+            .update_membership_witness(witness.clone(), slice::from_ref(i), &additions, &deletions)
+            .map_err(|_| VCError::UnexpectedState)?;
+        }
+        // The addition proof's witness covers all of the additions at once.
+        witnesses.extend(proof.witness.compute_individual_witnesses(&additions));
+        self.witnesses = Some(witnesses);
+      }
+      self.vc = VectorCommitment(acc);
+      for i in deletions {
+        self.set_indices.remove(&i);
+      }
+      self.set_indices.extend(additions);
+    }
+
+    let indices: Vec<Integer> = bits.iter().map(|(_, i)| i.clone()).collect();
+    self.get_with_proof(&indices).map(|(_, proof)| proof)
+  }
+
+  /// Returns the bits at `indices` along with a proof of their values. For a fixed-length vector
+  /// the proof also covers the length marker, so it verifies with
+  /// `FixedLengthVectorCommitment::verify`.
+  pub fn get_with_proof(
+    &mut self,
+    indices: &[Integer],
+  ) -> Result<(Vec<bool>, VectorProof<G>), VCError> {
+    self.check_range(indices.iter())?;
+    let bits: Vec<(bool, Integer)> = indices.iter().map(|i| (self.get(i), i.clone())).collect();
+    let (zero_bits, mut one_bits) = group_elems_by_bit(&bits)?;
+    one_bits.extend(self.len.iter().map(length_marker));
+    let one_bit_witnesses = self.witnesses_for(&one_bits);
+    let vc_acc_set = self.acc_set();
+    let proof = VectorCommitment::open(&self.vc, &vc_acc_set, &zero_bits, &one_bit_witnesses)?;
+    Ok((bits.into_iter().map(|(bit, _)| bit).collect(), proof))
+  }
+
+  /// Returns the bits at indices `start..end` along with a proof of their values.
+  pub fn open_range(
+    &mut self,
+    start: &Integer,
+    end: &Integer,
+  ) -> Result<(Vec<bool>, VectorProof<G>), VCError> {
+    let mut indices = vec![];
+    let mut i = start.clone();
+    while i < *end {
+      indices.push(i.clone());
+      i += 1;
+    }
+    self.get_with_proof(&indices)
+  }
+
+  fn check_range<'a>(&self, indices: impl Iterator<Item = &'a Integer>) -> Result<(), VCError> {
+    match &self.len {
+      Some(len) => check_range(len, indices),
+      None => Ok(()),
+    }
+  }
+
+  /// Returns every element of the underlying accumulator: the set indices and any length marker.
+  fn acc_set(&self) -> Vec<Integer> {
+    let mut acc_set: Vec<Integer> = self.set_indices.iter().cloned().collect();
+    acc_set.extend(self.len.iter().map(length_marker));
+    acc_set
+  }
+
+  /// Returns witnesses for `indices`, all of which must be set, rebuilding the cache if needed.
+  fn witnesses_for(&mut self, indices: &[Integer]) -> Vec<(Integer, Witness<G, Integer>)> {
+    if self.witnesses.is_none() {
+      let elems = self.acc_set();
+      self.witnesses = Some(
+        Witness(Accumulator::empty())
+          .compute_individual_witnesses(&elems)
+          .into_iter()
+          .collect(),
+      );
+    }
+    let witnesses = self.witnesses.as_ref().unwrap();
+    indices
+      .iter()
+      .map(|i| (i.clone(), witnesses[i].clone()))
+      .collect()
   }
 }
 
-// TODO: Write tests.
 #[cfg(test)]
-mod tests {}
+mod tests {
+  use super::*;
+  use crate::group::Rsa2048;
+
+  fn set_indices(bits: &[(bool, Integer)]) -> Vec<Integer> {
+    bits
+      .iter()
+      .filter(|(bit, _)| *bit)
+      .map(|(_, i)| i.clone())
+      .collect()
+  }
+
+  fn bits(pairs: &[(bool, i32)]) -> Vec<(bool, Integer)> {
+    pairs
+      .iter()
+      .map(|(bit, i)| (*bit, Integer::from(*i)))
+      .collect()
+  }
+
+  fn indices(is: &[i32]) -> Vec<Integer> {
+    is.iter().map(|i| Integer::from(*i)).collect()
+  }
+
+  #[test]
+  fn test_conflicting_indices() {
+    let vc = VectorCommitment::<Rsa2048>::empty();
+    let conflicting = bits(&[(true, 1), (false, 1)]);
+    match VectorCommitment::update(vc.clone(), &indices(&[1]), &conflicting) {
+      Err(VCError::ConflictingIndices { index }) => assert_eq!(index, 1),
+      _ => panic!("Expected conflicting indices."),
+    }
+
+    let mut manager = VectorCommitmentManager::<Rsa2048>::new();
+    let proof = manager.set(&bits(&[(true, 1)])).unwrap();
+    assert!(!VectorCommitment::verify(
+      manager.commitment(),
+      &conflicting,
+      &proof
+    ));
+    assert!(manager.set(&conflicting).is_err());
+    assert!(manager.get_with_proof(&indices(&[2, 2])).is_err());
+    // A rejected write leaves the vector untouched.
+    assert!(manager.get(&Integer::from(1)));
+  }
+
+  #[test]
+  fn test_persist() {
+    let mut manager = VectorCommitmentManager::<Rsa2048>::new();
+    manager.set(&bits(&[(true, 0), (true, 2)])).unwrap();
+    let (values, proof) = manager.get_with_proof(&indices(&[0, 1, 2])).unwrap();
+    assert_eq!(values, vec![true, false, true]);
+    let opened = bits(&[(true, 0), (false, 1), (true, 2)]);
+
+    let mut bytes = vec![];
+    manager.commitment().encode(&mut bytes);
+    proof.encode(&mut bytes);
+    let mut rest = &bytes[..];
+    let vc = VectorCommitment::<Rsa2048>::decode(&mut rest).unwrap();
+    let decoded = VectorProof::decode(&mut rest).unwrap();
+    assert!(rest.is_empty());
+    assert!(vc == *manager.commitment());
+    assert!(decoded == proof);
+    assert!(VectorCommitment::verify(&vc, &opened, &decoded));
+    assert!(VectorProof::<Rsa2048>::decode(&mut &bytes[..bytes.len() - 1]).is_none());
+  }
+
+  #[test]
+  fn test_manager_mixed_openings() {
+    let mut manager = VectorCommitmentManager::<Rsa2048>::new();
+    let written = bits(&[(true, 0), (false, 1), (true, 3), (true, 4)]);
+    let proof = manager.set(&written).unwrap();
+    assert!(VectorCommitment::verify(
+      manager.commitment(),
+      &written,
+      &proof
+    ));
+
+    let (values, proof) = manager
+      .open_range(&Integer::from(0), &Integer::from(5))
+      .unwrap();
+    assert_eq!(values, vec![true, false, false, true, true]);
+    let opened = bits(&[(true, 0), (false, 1), (false, 2), (true, 3), (true, 4)]);
+    assert!(VectorCommitment::verify(
+      manager.commitment(),
+      &opened,
+      &proof
+    ));
+
+    // Clearing a set bit and setting a new one in the same write.
+    let rewritten = bits(&[(false, 3), (true, 7)]);
+    let proof = manager.set(&rewritten).unwrap();
+    assert!(VectorCommitment::verify(
+      manager.commitment(),
+      &rewritten,
+      &proof
+    ));
+    let (values, proof) = manager.get_with_proof(&indices(&[7, 4, 3])).unwrap();
+    assert_eq!(values, vec![true, true, false]);
+    let opened = bits(&[(true, 7), (true, 4), (false, 3)]);
+    assert!(VectorCommitment::verify(
+      manager.commitment(),
+      &opened,
+      &proof
+    ));
+
+    // Matches a fresh commitment to the same vector.
+    let mut fresh = VectorCommitmentManager::<Rsa2048>::new();
+    fresh
+      .set(&bits(&[(true, 0), (true, 4), (true, 7)]))
+      .unwrap();
+    assert_eq!(fresh.commitment(), manager.commitment());
+    // The witnesses updated in place match freshly computed ones.
+    let set = indices(&[0, 4, 7]);
+    assert!(fresh.witnesses_for(&set) == manager.witnesses_for(&set));
+  }
+
+  #[test]
+  fn test_rejected_proofs() {
+    let mut manager = VectorCommitmentManager::<Rsa2048>::new();
+    manager.set(&bits(&[(true, 0), (true, 2)])).unwrap();
+    let old_vc = manager.commitment().clone();
+    let (_, proof) = manager.get_with_proof(&indices(&[0, 1])).unwrap();
+    let vc = manager.commitment();
+    assert!(VectorCommitment::verify(
+      vc,
+      &bits(&[(true, 0), (false, 1)]),
+      &proof
+    ));
+    // Flipped bits.
+    assert!(!VectorCommitment::verify(
+      vc,
+      &bits(&[(false, 0), (false, 1)]),
+      &proof
+    ));
+    assert!(!VectorCommitment::verify(
+      vc,
+      &bits(&[(true, 0), (true, 1)]),
+      &proof
+    ));
+    // Different or partial indices.
+    assert!(!VectorCommitment::verify(
+      vc,
+      &bits(&[(true, 2), (false, 1)]),
+      &proof
+    ));
+    assert!(!VectorCommitment::verify(vc, &bits(&[(true, 0)]), &proof));
+
+    // Stale proofs fail against the new commitment, and vice versa.
+    manager.set(&bits(&[(true, 5)])).unwrap();
+    let (_, new_proof) = manager.get_with_proof(&indices(&[0, 1])).unwrap();
+    let vc = manager.commitment();
+    assert!(!VectorCommitment::verify(
+      vc,
+      &bits(&[(true, 0), (false, 1)]),
+      &proof
+    ));
+    assert!(!VectorCommitment::verify(
+      &old_vc,
+      &bits(&[(true, 0), (false, 1)]),
+      &new_proof
+    ));
+
+    // Opening a set bit as unset is impossible.
+    let vc_acc_set = indices(&[0, 2, 5]);
+    assert!(VectorCommitment::open(vc, &vc_acc_set, &indices(&[2]), &[]).is_err());
+  }
+
+  #[test]
+  fn test_combine() {
+    let mut manager = VectorCommitmentManager::<Rsa2048>::new();
+    manager
+      .set(&bits(&[(true, 0), (true, 1), (true, 4), (true, 6)]))
+      .unwrap();
+    let vc = manager.commitment().clone();
+    let opening_1 = bits(&[(true, 0), (false, 2), (true, 4)]);
+    let opening_2 = bits(&[(false, 3), (true, 6), (false, 7)]);
+    let opening_3 = bits(&[(true, 1)]);
+    let (_, proof_1) = manager.get_with_proof(&indices(&[0, 2, 4])).unwrap();
+    let (_, proof_2) = manager.get_with_proof(&indices(&[3, 6, 7])).unwrap();
+    let (_, proof_3) = manager.get_with_proof(&indices(&[1])).unwrap();
+
+    let proof_12 =
+      VectorProof::combine(&vc, &[(&opening_1, &proof_1), (&opening_2, &proof_2)]).unwrap();
+    let bits_12: Vec<_> = opening_1.iter().chain(&opening_2).cloned().collect();
+    assert!(VectorCommitment::verify(&vc, &bits_12, &proof_12));
+    assert!(!VectorCommitment::verify(&vc, &opening_1, &proof_12));
+
+    // Combined openings combine further.
+    let proof_123 =
+      VectorProof::combine(&vc, &[(&bits_12, &proof_12), (&opening_3, &proof_3)]).unwrap();
+    let bits_123: Vec<_> = bits_12.iter().chain(&opening_3).cloned().collect();
+    assert!(VectorCommitment::verify(&vc, &bits_123, &proof_123));
+    let mut flipped = bits_123.clone();
+    flipped[1].0 = true;
+    assert!(!VectorCommitment::verify(&vc, &flipped, &proof_123));
+
+    // Overlapping or invalid openings cannot be combined.
+    match VectorProof::combine(&vc, &[(&opening_1, &proof_1), (&opening_1, &proof_1)]) {
+      Err(VCError::ConflictingIndices { .. }) => (),
+      _ => panic!("Expected conflicting indices."),
+    }
+    assert!(VectorProof::combine(&vc, &[(&opening_1, &proof_2)]).is_err());
+  }
+
+  #[test]
+  fn test_verify_bad_zero_counts() {
+    let mut manager = VectorCommitmentManager::<Rsa2048>::new();
+    let written = bits(&[(true, 0), (false, 1)]);
+    let proof = manager.set(&written).unwrap();
+    let vc = manager.commitment();
+    let nonmembership_proof = proof.nonmembership_proofs[0].1.clone();
+    for counts in &[vec![usize::MAX, 2], vec![2], vec![0, 2]] {
+      let forged = VectorProof {
+        membership_proof: proof.membership_proof.clone(),
+        nonmembership_proofs: counts
+          .iter()
+          .map(|n| (*n, nonmembership_proof.clone()))
+          .collect(),
+      };
+      assert!(!VectorCommitment::verify(vc, &written, &forged));
+    }
+  }
+
+  #[test]
+  fn test_verify_many() {
+    let mut manager_1 = VectorCommitmentManager::<Rsa2048>::new();
+    let mut manager_2 = VectorCommitmentManager::<Rsa2048>::new();
+    let bits_1 = bits(&[(true, 0), (false, 1)]);
+    let bits_2 = bits(&[(false, 0), (true, 1), (true, 2)]);
+    let proof_1 = manager_1.set(&bits_1).unwrap();
+    let proof_2 = manager_2.set(&bits_2).unwrap();
+    let (vc_1, vc_2) = (manager_1.commitment(), manager_2.commitment());
+    assert!(VectorCommitment::verify_many(&[
+      (vc_1, &bits_1, &proof_1),
+      (vc_2, &bits_2, &proof_2),
+    ]));
+    assert!(VectorCommitment::<Rsa2048>::verify_many(&[]));
+    assert!(!VectorCommitment::verify_many(&[
+      (vc_1, &bits_1, &proof_1),
+      (vc_1, &bits_2, &proof_2),
+    ]));
+  }
+
+  #[test]
+  fn test_fixed_length() {
+    let mut manager = VectorCommitmentManager::<Rsa2048>::with_len(Integer::from(8));
+    let written = bits(&[(true, 0), (false, 3), (true, 7)]);
+    let proof = manager.set(&written).unwrap();
+    let flvc = manager.fixed_length_commitment().unwrap();
+    assert_eq!(flvc.len(), &8);
+    assert!(FixedLengthVectorCommitment::verify(&flvc, &written, &proof));
+    let (values, proof) = manager
+      .open_range(&Integer::from(0), &Integer::from(8))
+      .unwrap();
+    assert_eq!(values.iter().filter(|bit| **bit).count(), 2);
+    let all_bits: Vec<_> = values
+      .into_iter()
+      .zip(0..)
+      .map(|(bit, i)| (bit, Integer::from(i)))
+      .collect();
+    assert!(FixedLengthVectorCommitment::verify(
+      &flvc, &all_bits, &proof
+    ));
+
+    // Out-of-range reads and writes.
+    for i in &[-1, 8] {
+      match manager.set(&bits(&[(true, *i)])) {
+        Err(VCError::IndexOutOfRange { index }) => assert_eq!(index, *i),
+        _ => panic!("Expected index out of range."),
+      }
+      assert!(manager.get_with_proof(&indices(&[*i])).is_err());
+    }
+    let proof = VectorCommitmentManager::<Rsa2048>::new()
+      .set(&bits(&[(false, 8)]))
+      .unwrap();
+    assert!(!FixedLengthVectorCommitment::verify(
+      &flvc,
+      &bits(&[(false, 8)]),
+      &proof
+    ));
+
+    // The length is bound into the commitment.
+    let empty_8 = FixedLengthVectorCommitment::<Rsa2048>::new(Integer::from(8));
+    let empty_9 = FixedLengthVectorCommitment::<Rsa2048>::new(Integer::from(9));
+    assert!(empty_8.vector_commitment() != empty_9.vector_commitment());
+    assert!(empty_8.vector_commitment() != &VectorCommitment::empty());
+    let mut manager_9 = VectorCommitmentManager::<Rsa2048>::with_len(Integer::from(9));
+    let (_, proof_9) = manager_9.get_with_proof(&indices(&[0])).unwrap();
+    let flvc_9 = manager_9.fixed_length_commitment().unwrap();
+    assert!(FixedLengthVectorCommitment::verify(
+      &flvc_9,
+      &bits(&[(false, 0)]),
+      &proof_9
+    ));
+    // A length-9 commitment reinterpreted as length 8 accepts none of its openings.
+    let flvc_8 = FixedLengthVectorCommitment {
+      vc: flvc_9.vc.clone(),
+      len: Integer::from(8),
+    };
+    assert!(!FixedLengthVectorCommitment::verify(
+      &flvc_8,
+      &bits(&[(false, 0)]),
+      &proof_9
+    ));
+  }
+
+  #[test]
+  fn test_bound_proof() {
+    let mut manager = VectorCommitmentManager::<Rsa2048>::with_len(Integer::from(8));
+    manager.set(&bits(&[(true, 2), (true, 5)])).unwrap();
+    let flvc = manager.fixed_length_commitment().unwrap();
+    let proof = manager.prove_bound().unwrap();
+    assert!(FixedLengthVectorCommitment::verify_bound(&flvc, &proof));
+    let set_indices = indices(&[2, 5]);
+    assert!(FixedLengthVectorCommitment::prove_bound(&flvc, &indices(&[2])).is_err());
+    let flvc_9 = FixedLengthVectorCommitment {
+      vc: flvc.vc.clone(),
+      len: Integer::from(9),
+    };
+    assert!(!FixedLengthVectorCommitment::verify_bound(&flvc_9, &proof));
+
+    // A commitment with a bit set past its length, built by going around the range checks.
+    let (vc, _) =
+      VectorCommitment::update(flvc.vc.clone(), &indices(&[]), &bits(&[(true, 9)])).unwrap();
+    let padded = FixedLengthVectorCommitment {
+      vc,
+      len: Integer::from(8),
+    };
+    assert!(FixedLengthVectorCommitment::prove_bound(&padded, &set_indices).is_err());
+    assert!(FixedLengthVectorCommitment::prove_bound(&padded, &indices(&[2, 5, 9])).is_err());
+    assert!(!FixedLengthVectorCommitment::verify_bound(&padded, &proof));
+    assert!(VectorCommitmentManager::<Rsa2048>::new()
+      .prove_bound()
+      .is_err());
+
+    let length_witness = Witness(Accumulator::empty().add(&set_indices));
+    match FixedLengthVectorCommitment::open(
+      &flvc,
+      &set_indices,
+      &indices(&[8]),
+      &[],
+      &length_witness,
+    ) {
+      Err(VCError::IndexOutOfRange { index }) => assert_eq!(index, 8),
+      _ => panic!("Expected index out of range."),
+    }
+    let proof =
+      FixedLengthVectorCommitment::open(&flvc, &set_indices, &indices(&[0]), &[], &length_witness)
+        .unwrap();
+    assert!(FixedLengthVectorCommitment::verify(
+      &flvc,
+      &bits(&[(false, 0)]),
+      &proof
+    ));
+    // The opening does not verify against a commitment of another length.
+    assert!(!FixedLengthVectorCommitment::verify(
+      &flvc_9,
+      &bits(&[(false, 0)]),
+      &proof
+    ));
+  }
+
+  #[test]
+  fn test_values() {
+    let entries = [(Integer::from(0), 0xa5_u64), (Integer::from(2), 3)];
+    let bits = value_bits(8, &entries).unwrap();
+    let vc_acc_set = set_indices(&bits);
+    let (vc, proof) = VectorCommitment::<Rsa2048>::update_values(
+      VectorCommitment::empty(),
+      &vc_acc_set,
+      8,
+      &entries,
+    )
+    .unwrap();
+    assert!(VectorCommitment::verify_values(&vc, 8, &entries, &proof));
+
+    let opening = &entries[1..];
+    let proof = VectorCommitment::open_values(&vc, &vc_acc_set, 8, opening).unwrap();
+    assert!(VectorCommitment::verify_values(&vc, 8, opening, &proof));
+    assert!(!VectorCommitment::verify_values(
+      &vc,
+      8,
+      &[(Integer::from(2), 7_u64)],
+      &proof
+    ));
+    assert!(!VectorCommitment::verify_values(
+      &vc,
+      8,
+      &[(Integer::from(1), 3_u64)],
+      &proof
+    ));
+
+    // Entry 1 was never written, so it reads as zero.
+    let zero = [(Integer::from(1), 0_u64)];
+    let proof = VectorCommitment::open_values(&vc, &vc_acc_set, 8, &zero).unwrap();
+    assert!(VectorCommitment::verify_values(&vc, 8, &zero, &proof));
+    assert!(
+      VectorCommitment::open_values(&vc, &vc_acc_set, 8, &[(Integer::from(2), 1_u64)]).is_err()
+    );
+  }
+
+  #[test]
+  fn test_byte_string_values() {
+    let entries = [
+      (Integer::from(0), &b"ab"[..]),
+      (Integer::from(1), &b"\x00\xff"[..]),
+    ];
+    let vc_acc_set = set_indices(&value_bits(16, &entries).unwrap());
+    let (vc, proof) = VectorCommitment::<Rsa2048>::update_values(
+      VectorCommitment::empty(),
+      &vc_acc_set,
+      16,
+      &entries,
+    )
+    .unwrap();
+    assert!(VectorCommitment::verify_values(&vc, 16, &entries, &proof));
+    assert!(value_bits(16, &[(Integer::from(0), &b"abc"[..])]).is_err());
+    assert!(value_bits(4, &[(Integer::from(0), 16_u64)]).is_err());
+  }
+}
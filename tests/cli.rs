@@ -0,0 +1,75 @@
+use accumulator::hash::hash_to_prime;
+use std::fs;
+use std::process::{Command, Output};
+
+fn temp_path(name: &str) -> String {
+  let path = std::env::temp_dir().join(format!("accumulator-cli-{}-{}", std::process::id(), name));
+  path.to_str().unwrap().to_string()
+}
+
+fn cli(args: &[&str]) -> Output {
+  Command::new(env!("CARGO_BIN_EXE_accumulator-cli"))
+    .args(args)
+    .output()
+    .unwrap()
+}
+
+fn code(args: &[&str]) -> Option<i32> {
+  cli(args).status.code()
+}
+
+fn stdout(args: &[&str]) -> String {
+  String::from_utf8(cli(args).stdout).unwrap()
+}
+
+#[test]
+fn test_cli() {
+  let state = temp_path("state");
+  let added = temp_path("added");
+  let deleted = temp_path("deleted");
+  let others = temp_path("others");
+  let witnesses = temp_path("witnesses");
+  let proof = temp_path("proof");
+  fs::write(&added, "dog\ncat\n\nbird\n").unwrap();
+  fs::write(&deleted, "cat\n").unwrap();
+  fs::write(&others, "cow\n").unwrap();
+
+  assert_eq!(code(&["create", "rsa2048", &state]), Some(0));
+  assert_eq!(code(&["add", &state, &added]), Some(0));
+  // Elements may not be accumulated twice.
+  assert_eq!(code(&["add", &state, &deleted]), Some(2));
+  assert!(stdout(&["show", &state]).contains("elements: 3"));
+
+  assert_eq!(code(&["prove-membership", &state, &added, &proof]), Some(0));
+  assert_eq!(stdout(&["verify", &state, &added, &proof]), "valid\n");
+  assert_eq!(code(&["verify", &state, &others, &proof]), Some(1));
+
+  assert_eq!(
+    code(&["prove-nonmembership", &state, &others, &proof]),
+    Some(0)
+  );
+  assert_eq!(code(&["verify", &state, &others, &proof]), Some(0));
+  assert_eq!(
+    code(&["prove-nonmembership", &state, &deleted, &proof]),
+    Some(2)
+  );
+
+  assert_eq!(code(&["witness", &state, &deleted, &witnesses]), Some(0));
+  assert_eq!(code(&["delete", &state, &witnesses]), Some(0));
+  assert!(stdout(&["show", &state]).contains("elements: 2"));
+  assert_eq!(
+    code(&["prove-nonmembership", &state, &deleted, &proof]),
+    Some(0)
+  );
+  assert_eq!(code(&["verify", &state, &deleted, &proof]), Some(0));
+  // The witness no longer matches the accumulator.
+  assert_eq!(code(&["delete", &state, &witnesses]), Some(2));
+
+  let prime = hash_to_prime("cat").to_string();
+  assert_eq!(stdout(&["hash-to-prime", &deleted]), prime + "\n");
+  assert_eq!(code(&["show", &added]), Some(2));
+  assert_eq!(code(&["create", "dsa", &state]), Some(2));
+  for path in &[state, added, deleted, others, witnesses, proof] {
+    fs::remove_file(path).unwrap();
+  }
+}
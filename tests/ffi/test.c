@@ -0,0 +1,89 @@
+/* Exercises the C bindings against the static library. Run with `make -C tests/ffi`. */
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "accumulator.h"
+
+#define CHECK(cond)                                                               \
+  do {                                                                             \
+    if (!(cond)) {                                                                 \
+      fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, #cond);    \
+      exit(1);                                                                     \
+    }                                                                              \
+  } while (0)
+
+static AccSlice slice(const char *s) {
+  AccSlice slice = {(const uint8_t *)s, strlen(s)};
+  return slice;
+}
+
+static void test_group(AccGroup group) {
+  AccSlice set[] = {slice("dog"), slice("cat"), slice("bird")};
+  AccSlice cat[] = {slice("cat")};
+  AccSlice rest[] = {slice("dog"), slice("bird")};
+  AccSlice cow[] = {slice("cow")};
+  bool valid = false;
+
+  AccAccumulator *acc = acc_new(group);
+  AccGroup acc_group_out = ACC_GROUP_RSA2048;
+  CHECK(acc_group(acc, &acc_group_out) == ACC_STATUS_OK && acc_group_out == group);
+  AccMembershipProof *add_proof = NULL;
+  CHECK(acc_add(acc, set, 3, &add_proof) == ACC_STATUS_OK);
+  CHECK(acc_verify_membership(acc, set, 3, add_proof, &valid) == ACC_STATUS_OK && valid);
+
+  /* A verifier that only sees bytes. */
+  AccBuffer acc_bytes, proof_bytes;
+  AccMembershipProof *proof = NULL;
+  CHECK(acc_prove_membership(acc, set, 3, cat, 1, &proof) == ACC_STATUS_OK);
+  CHECK(acc_serialize(acc, &acc_bytes) == ACC_STATUS_OK);
+  CHECK(acc_membership_proof_serialize(proof, &proof_bytes) == ACC_STATUS_OK);
+  AccAccumulator *acc_copy = NULL;
+  AccMembershipProof *proof_copy = NULL;
+  CHECK(acc_deserialize(group, acc_bytes.data, acc_bytes.len, &acc_copy) == ACC_STATUS_OK);
+  CHECK(acc_membership_proof_deserialize(group, proof_bytes.data, proof_bytes.len, &proof_copy) ==
+        ACC_STATUS_OK);
+  CHECK(acc_deserialize(group, proof_bytes.data, 3, &acc_copy) == ACC_STATUS_MALFORMED);
+  CHECK(acc_verify_membership(acc_copy, cat, 1, proof_copy, &valid) == ACC_STATUS_OK && valid);
+  CHECK(acc_verify_membership(acc_copy, cow, 1, proof_copy, &valid) == ACC_STATUS_OK && !valid);
+  acc_buffer_free(acc_bytes);
+  acc_buffer_free(proof_bytes);
+
+  CHECK(acc_delete(acc, cat, 1, proof) == ACC_STATUS_OK);
+  CHECK(acc_delete(acc, cat, 1, proof) == ACC_STATUS_BAD_WITNESS);
+  AccNonmembershipProof *nonmembership_proof = NULL;
+  CHECK(acc_prove_nonmembership(acc, rest, 2, cat, 1, &nonmembership_proof) == ACC_STATUS_OK);
+  CHECK(acc_verify_nonmembership(acc, cat, 1, nonmembership_proof, &valid) == ACC_STATUS_OK &&
+        valid);
+  CHECK(acc_verify_nonmembership(acc_copy, cat, 1, nonmembership_proof, &valid) == ACC_STATUS_OK &&
+        !valid);
+  CHECK(acc_prove_nonmembership(acc, rest, 2, rest, 1, &nonmembership_proof) ==
+        ACC_STATUS_DUPLICATE_ELEM);
+
+  acc_nonmembership_proof_free(nonmembership_proof);
+  acc_membership_proof_free(proof_copy);
+  acc_membership_proof_free(proof);
+  acc_membership_proof_free(add_proof);
+  acc_free(acc_copy);
+  acc_free(acc);
+}
+
+int main(void) {
+  test_group(ACC_GROUP_RSA2048);
+  test_group(ACC_GROUP_CLASS);
+
+  AccAccumulator *rsa_acc = acc_new(ACC_GROUP_RSA2048);
+  AccAccumulator *class_acc = acc_new(ACC_GROUP_CLASS);
+  AccSlice elems[] = {slice("dog")};
+  AccMembershipProof *proof = NULL;
+  bool valid = false;
+  CHECK(acc_add(rsa_acc, elems, 1, &proof) == ACC_STATUS_OK);
+  CHECK(acc_verify_membership(class_acc, elems, 1, proof, &valid) == ACC_STATUS_GROUP_MISMATCH);
+  CHECK(acc_add(NULL, elems, 1, NULL) == ACC_STATUS_NULL_POINTER);
+  acc_membership_proof_free(proof);
+  acc_free(class_acc);
+  acc_free(rsa_acc);
+
+  printf("ok\n");
+  return 0;
+}
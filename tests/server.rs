@@ -0,0 +1,87 @@
+#![cfg(feature = "server")]
+use accumulator::group::Rsa2048;
+use accumulator::server::{Client, RpcError, Server, APP_ERROR};
+use accumulator::Accumulator;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::thread;
+use std::time::Duration;
+
+fn spawn_server_with_timeout(timeout: Duration) -> (Client<Rsa2048>, TcpStream) {
+  let mut server = Server::<Rsa2048>::bind("127.0.0.1:0").unwrap();
+  server.set_timeout(timeout);
+  let addr = server.local_addr().unwrap();
+  thread::spawn(move || server.serve());
+  (Client::new(addr), TcpStream::connect(addr).unwrap())
+}
+
+fn spawn_server() -> Client<Rsa2048> {
+  spawn_server_with_timeout(Duration::from_secs(10)).0
+}
+
+#[test]
+fn test_server() {
+  let client = spawn_server();
+  let (acc, epoch) = client.state().unwrap();
+  assert!(acc == Accumulator::empty());
+  assert_eq!(epoch, 0);
+
+  let (acc, epoch, proof) = client.add(&["dog", "cat", "bird"]).unwrap();
+  assert_eq!(epoch, 1);
+  assert!(acc.verify_membership_batch(
+    &["dog".to_string(), "cat".to_string(), "bird".to_string()],
+    &proof
+  ));
+  assert!(client.state().unwrap().0 == acc);
+
+  let proof = client.prove_membership(&["cat"]).unwrap();
+  assert!(acc.verify_membership(&"cat".to_string(), &proof));
+  assert!(client.verify_membership(&["cat"], &proof).unwrap());
+  assert!(!client.verify_membership(&["cow"], &proof).unwrap());
+  let proof = client.prove_nonmembership(&["cow"]).unwrap();
+  assert!(client.verify_nonmembership(&["cow"], &proof).unwrap());
+
+  let (new_acc, epoch, proof) = client.delete(&["cat"]).unwrap();
+  assert_eq!(epoch, 2);
+  assert!(new_acc == Accumulator::empty().add(&["dog".to_string(), "bird".to_string()]));
+  assert!(acc.verify_membership(&"cat".to_string(), &proof));
+  let proof = client.prove_nonmembership(&["cat"]).unwrap();
+  assert!(client.verify_nonmembership(&["cat"], &proof).unwrap());
+}
+
+#[test]
+fn test_server_errors() {
+  let client = spawn_server();
+  client.add(&["dog"]).unwrap();
+  let is_app_error = |result| match result {
+    Err(RpcError::Remote(code, _)) => code == APP_ERROR,
+    _ => false,
+  };
+  assert!(is_app_error(client.add(&["dog"]).map(|_| ())));
+  assert!(is_app_error(client.delete(&["cat"]).map(|_| ())));
+  assert!(is_app_error(client.prove_membership(&["cat"]).map(|_| ())));
+  assert!(is_app_error(
+    client.prove_nonmembership(&["dog"]).map(|_| ())
+  ));
+  // Failed updates leave the state untouched.
+  assert_eq!(client.state().unwrap().1, 1);
+}
+
+#[test]
+fn test_server_slow_clients() {
+  // The connection returned here is accepted first and never sends anything.
+  let (client, _idle) = spawn_server_with_timeout(Duration::from_millis(200));
+  assert_eq!(client.state().unwrap().1, 0);
+
+  // Header lines without an end are cut off rather than buffered.
+  let (client, mut stream) = spawn_server_with_timeout(Duration::from_secs(10));
+  let mut request = b"POST / HTTP/1.1\r\nX-Padding: ".to_vec();
+  request.extend(vec![b'a'; 1 << 17]);
+  let _ = stream.write_all(&request);
+  let _ = stream.shutdown(std::net::Shutdown::Write);
+  // The server may reset the connection, since it drops the rest of the request unread.
+  let mut response = String::new();
+  let _ = stream.read_to_string(&mut response);
+  assert!(response.is_empty() || response.starts_with("HTTP/1.1 400"));
+  assert_eq!(client.state().unwrap().1, 0);
+}
@@ -0,0 +1,256 @@
+//! Known-answer tests for hashing, proofs, accumulators and vector commitments.
+//!
+//! The vectors live in `tests/vectors/*.txt`, one case per line with whitespace-separated fields;
+//! blank lines and lines starting with `#` are ignored. Each case lists its inputs followed by the
+//! expected outputs, which are decimal for hash outputs and the hex `Persist` encoding otherwise.
+//! Group elements are derived from the group's unknown-order element `g`, so `g^a` is written as
+//! the exponent `a`. Proof exponents are decimal, or `hash:<elem>,...` for the prime hash product
+//! of the listed strings.
+//!
+//! If an output changes on purpose, rerun with `UPDATE_VECTORS=1` to rewrite the expected fields
+//! in place, and commit the resulting diff for review.
+use accumulator::bigint::Integer;
+use accumulator::group::{ClassGroup, Rsa2048, UnknownOrderGroup};
+use accumulator::hash::{blake2b, hash_to_prime};
+use accumulator::proof::{Poe, Pokcr, Poke2};
+use accumulator::store::Persist;
+use accumulator::util::{int, prime_hash_product};
+use accumulator::{Accumulator, VectorCommitment, Witness};
+use std::env;
+use std::fs;
+use std::hash::Hash;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Checks every case in `tests/vectors/<name>.txt`. `compute` takes the fields of a case and
+/// returns how many of them are inputs, along with the outputs it computed from those inputs.
+fn check_vectors<F>(name: &str, compute: F)
+where
+  F: Fn(&[&str]) -> (usize, Vec<String>),
+{
+  let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "tests", "vectors", name]
+    .iter()
+    .collect::<PathBuf>()
+    .with_extension("txt");
+  let contents = fs::read_to_string(&path).unwrap();
+  let update = env::var_os("UPDATE_VECTORS").is_some();
+  let mut updated = String::new();
+  let mut cases = 0;
+  for (i, line) in contents.lines().enumerate() {
+    if line.trim().is_empty() || line.starts_with('#') {
+      updated.push_str(line);
+      updated.push('\n');
+      continue;
+    }
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    let (inputs, outputs) = compute(&fields);
+    if update {
+      updated.push_str(
+        &[
+          &fields[..inputs],
+          &outputs.iter().map(String::as_str).collect::<Vec<_>>()[..],
+        ]
+        .concat()
+        .join(" "),
+      );
+      updated.push('\n');
+    } else {
+      let expected: Vec<String> = fields[inputs..].iter().map(|s| s.to_string()).collect();
+      assert_eq!(expected, outputs, "{}.txt:{}", name, i + 1);
+    }
+    cases += 1;
+  }
+  assert!(cases > 0, "{}.txt has no cases", name);
+  if update {
+    fs::write(&path, updated).unwrap();
+  }
+}
+
+/// Dispatches `$f::<G>($args)` on a group name.
+macro_rules! with_group {
+  ($group:expr, $f:ident($($args:expr),*)) => {
+    match $group {
+      "rsa2048" => $f::<Rsa2048>($($args),*),
+      "class" => $f::<ClassGroup>($($args),*),
+      group => panic!("unknown group `{}`", group),
+    }
+  };
+}
+
+fn hex<P: Persist>(val: &P) -> String {
+  let mut bytes = vec![];
+  val.encode(&mut bytes);
+  bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn unhex(hex: &str) -> Vec<u8> {
+  (0..hex.len())
+    .step_by(2)
+    .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+    .collect()
+}
+
+/// Returns `g^exp`, where `g` is the unknown-order element of `G`.
+fn g_exp<G: UnknownOrderGroup>(exp: &str) -> G::Elem {
+  G::exp(&G::unknown_order_elem(), &int(exp.parse::<u64>().unwrap()))
+}
+
+fn list(field: &str) -> Vec<&str> {
+  field.split(',').collect()
+}
+
+/// Parses a proof exponent: either decimal, or `hash:<elem>,...`.
+fn exponent(field: &str) -> Integer {
+  match field.strip_prefix("hash:") {
+    Some(elems) => prime_hash_product(&list(elems)),
+    None => Integer::from_str(field).unwrap(),
+  }
+}
+
+fn hash<T: Hash + ?Sized>(function: &str, t: &T) -> Integer {
+  match function {
+    "blake2b" => blake2b(t),
+    "hash_to_prime" => hash_to_prime(t),
+    _ => panic!("unknown function `{}`", function),
+  }
+}
+
+#[test]
+fn test_hash_vectors() {
+  // <function> <input type> <input> <output>
+  check_vectors("hash", |fields| {
+    let output = match fields[1] {
+      "str" => hash(fields[0], fields[2]),
+      "bytes" => hash(fields[0], &unhex(fields[2])[..]),
+      "u64" => hash(fields[0], &fields[2].parse::<u64>().unwrap()),
+      ty => panic!("unknown input type `{}`", ty),
+    };
+    (3, vec![output.to_string()])
+  });
+}
+
+fn poe<G: UnknownOrderGroup>(fields: &[&str]) -> Vec<String>
+where
+  G::Elem: Persist,
+{
+  let base = g_exp::<G>(fields[1]);
+  let exp = exponent(fields[2]);
+  let result = G::exp(&base, &exp);
+  let proof = Poe::<G>::prove(&base, &exp, &result);
+  assert!(Poe::verify(&base, &exp, &result, &proof));
+  vec![hex(&proof)]
+}
+
+#[test]
+fn test_poe_vectors() {
+  // <group> <base exponent> <exponent> <proof>
+  check_vectors("poe", |fields| (3, with_group!(fields[0], poe(fields))));
+}
+
+fn poke2<G: UnknownOrderGroup>(fields: &[&str]) -> Vec<String>
+where
+  G::Elem: Persist,
+{
+  let base = g_exp::<G>(fields[1]);
+  let exp = exponent(fields[2]);
+  let result = G::exp(&base, &exp);
+  let proof = Poke2::<G>::prove(&base, &exp, &result);
+  assert!(Poke2::verify(&base, &result, &proof));
+  vec![hex(&proof)]
+}
+
+#[test]
+fn test_poke2_vectors() {
+  // <group> <base exponent> <exponent> <proof>
+  check_vectors("poke2", |fields| (3, with_group!(fields[0], poke2(fields))));
+}
+
+fn pokcr<G: UnknownOrderGroup>(fields: &[&str]) -> Vec<String>
+where
+  G::Elem: Persist,
+{
+  let witnesses: Vec<G::Elem> = list(fields[1]).into_iter().map(g_exp::<G>).collect();
+  let x: Vec<_> = list(fields[2])
+    .into_iter()
+    .map(|x| int(x.parse::<u64>().unwrap()))
+    .collect();
+  let alphas: Vec<G::Elem> = witnesses
+    .iter()
+    .zip(&x)
+    .map(|(w, x)| G::exp(w, x))
+    .collect();
+  let proof = Pokcr::<G>::prove(&witnesses);
+  assert!(Pokcr::verify(&alphas, &x, &proof));
+  assert_eq!(
+    Pokcr::decode(&mut &unhex(&hex(&proof))[..]),
+    Some(proof.clone())
+  );
+  vec![hex(&proof)]
+}
+
+#[test]
+fn test_pokcr_vectors() {
+  // <group> <witness exponents> <coprime roots> <proof>
+  check_vectors("pokcr", |fields| (3, with_group!(fields[0], pokcr(fields))));
+}
+
+fn accumulator<G: UnknownOrderGroup>(ops: &[&str]) -> Vec<String>
+where
+  G::Elem: Persist,
+{
+  let mut acc = Accumulator::<G, String>::empty();
+  let mut set: Vec<String> = vec![];
+  for op in ops {
+    let elem = op[1..].to_string();
+    if op.starts_with('+') {
+      acc = acc.add(std::slice::from_ref(&elem));
+      set.push(elem);
+    } else if op.starts_with('-') {
+      set.retain(|other| *other != elem);
+      let witness = Witness(Accumulator::empty().add(&set));
+      acc = acc.delete(&[(elem, witness)]).unwrap();
+    } else {
+      panic!("unknown operation `{}`", op);
+    }
+  }
+  vec![hex(&acc)]
+}
+
+#[test]
+fn test_accumulator_vectors() {
+  // <group> <operations> <value>, where each operation is `+elem` or `-elem`.
+  check_vectors("accumulator", |fields| {
+    let ops = &fields[1..fields.len() - 1];
+    (fields.len() - 1, with_group!(fields[0], accumulator(ops)))
+  });
+}
+
+fn vector_commitment<G: UnknownOrderGroup>(fields: &[&str]) -> Vec<String>
+where
+  G::Elem: Persist,
+{
+  let bits: Vec<(bool, _)> = list(fields[1])
+    .into_iter()
+    .map(|bit| {
+      let (index, value) = bit.split_at(bit.find('=').unwrap());
+      (value == "=1", int(index.parse::<u64>().unwrap()))
+    })
+    .collect();
+  let vc_acc_set: Vec<_> = bits
+    .iter()
+    .filter(|(bit, _)| *bit)
+    .map(|(_, index)| index.clone())
+    .collect();
+  let (vc, proof) =
+    VectorCommitment::<G>::update(VectorCommitment::empty(), &vc_acc_set, &bits).unwrap();
+  assert!(VectorCommitment::verify(&vc, &bits, &proof));
+  vec![hex(&vc), hex(&proof)]
+}
+
+#[test]
+fn test_vector_commitment_vectors() {
+  // <group> <index=bit,...> <commitment> <proof>
+  check_vectors("vector_commitment", |fields| {
+    (2, with_group!(fields[0], vector_commitment(fields)))
+  });
+}